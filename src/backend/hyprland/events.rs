@@ -0,0 +1,269 @@
+use crate::types::HyprIpcEvent;
+
+/// Parse one line of Hyprland's `.socket2.sock` event stream
+///
+/// Lines look like `EVENT>>DATA`, with comma-separated fields in `DATA`. The
+/// last field of an event may itself contain commas (window titles), so each
+/// event splits only as many fields as it declares. Unknown events and
+/// malformed lines become `HyprIpcEvent::Other`.
+///
+/// Event addresses come without the `0x` prefix used by `j/clients`; they are
+/// normalized so both can be compared directly.
+pub fn parse_event(line: &str) -> HyprIpcEvent {
+    let Some((name, data)) = line.split_once(">>") else {
+        return HyprIpcEvent::Other;
+    };
+
+    parse_fields(name, data).unwrap_or(HyprIpcEvent::Other)
+}
+
+fn parse_fields(name: &str, data: &str) -> Option<HyprIpcEvent> {
+    let event = match name {
+        "openwindow" => {
+            let [address, workspace, class, title] = split_fields(data)?;
+            HyprIpcEvent::OpenWindow {
+                address: normalize_address(address),
+                workspace: workspace.to_string(),
+                class: class.to_string(),
+                title: title.to_string(),
+            }
+        }
+        "closewindow" => HyprIpcEvent::CloseWindow {
+            address: normalize_address(data.trim()),
+        },
+        "activewindowv2" => {
+            let address = data.trim().trim_matches(',');
+            HyprIpcEvent::ActiveWindow {
+                address: (!address.is_empty()).then(|| normalize_address(address)),
+            }
+        }
+        "windowtitlev2" => {
+            let [address, title] = split_fields(data)?;
+            HyprIpcEvent::WindowTitle {
+                address: normalize_address(address),
+                title: title.to_string(),
+            }
+        }
+        "movewindowv2" => {
+            let [address, workspace_id, workspace] = split_fields(data)?;
+            HyprIpcEvent::MoveWindow {
+                address: normalize_address(address),
+                workspace_id: workspace_id.parse().ok()?,
+                workspace: workspace.to_string(),
+            }
+        }
+        "workspacev2" => {
+            let [id, name] = split_fields(data)?;
+            HyprIpcEvent::Workspace {
+                id: id.parse().ok()?,
+                name: name.to_string(),
+            }
+        }
+        "focusedmon" => {
+            let [monitor, workspace] = split_fields(data)?;
+            HyprIpcEvent::FocusedMonitor {
+                monitor: monitor.to_string(),
+                workspace: workspace.to_string(),
+            }
+        }
+        "fullscreen" => HyprIpcEvent::Fullscreen {
+            enabled: parse_flag(data)?,
+        },
+        "urgent" => HyprIpcEvent::Urgent {
+            address: normalize_address(data.trim()),
+        },
+        "changefloatingmode" => {
+            let [address, floating] = split_fields(data)?;
+            HyprIpcEvent::ChangeFloatingMode {
+                address: normalize_address(address),
+                floating: parse_flag(floating)?,
+            }
+        }
+        "monitoradded" => HyprIpcEvent::MonitorAdded {
+            name: data.trim().to_string(),
+        },
+        "monitorremoved" => HyprIpcEvent::MonitorRemoved {
+            name: data.trim().to_string(),
+        },
+        "moveworkspace" => {
+            let [workspace, monitor] = split_fields(data)?;
+            HyprIpcEvent::WorkspaceMoved {
+                workspace: workspace.to_string(),
+                monitor: monitor.to_string(),
+            }
+        }
+        "configreloaded" => HyprIpcEvent::ConfigReloaded,
+        _ => HyprIpcEvent::Other,
+    };
+    Some(event)
+}
+
+/// Split `data` into exactly `N` fields, the last one keeping any remaining commas
+fn split_fields<const N: usize>(data: &str) -> Option<[&str; N]> {
+    let mut parts = data.splitn(N, ',');
+    let mut fields = [""; N];
+    for field in fields.iter_mut() {
+        *field = parts.next()?;
+    }
+    Some(fields)
+}
+
+fn parse_flag(data: &str) -> Option<bool> {
+    match data.trim() {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+fn normalize_address(address: &str) -> String {
+    if address.starts_with("0x") {
+        address.to_string()
+    } else {
+        format!("0x{}", address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded from a Hyprland 0.41 session: open a terminal, retitle it,
+    /// move it to workspace 2, float it, fullscreen, move a workspace and close it
+    const SESSION_LOG: &str = "\
+workspace>>1
+workspacev2>>1,1
+openwindow>>55d0a1b2c3d0,1,kitty,kitty
+activewindow>>kitty,kitty
+activewindowv2>>55d0a1b2c3d0
+windowtitle>>55d0a1b2c3d0
+windowtitlev2>>55d0a1b2c3d0,nvim ~/notes, todo.md
+movewindow>>55d0a1b2c3d0,2
+movewindowv2>>55d0a1b2c3d0,2,2
+changefloatingmode>>55d0a1b2c3d0,1
+fullscreen>>1
+fullscreen>>0
+focusedmon>>HDMI-A-1,web
+moveworkspace>>web,DP-1
+moveworkspacev2>>3,web,DP-1
+urgent>>55d0a1b2c4e0
+closewindow>>55d0a1b2c3d0
+activewindow>>,
+activewindowv2>>
+monitoradded>>DP-2
+monitoraddedv2>>2,DP-2,Dell Inc. U2720Q
+monitorremoved>>DP-2
+configreloaded>>";
+
+    #[test]
+    fn test_parse_session_log() {
+        let events: Vec<HyprIpcEvent> = SESSION_LOG.lines().map(parse_event).collect();
+
+        assert_eq!(
+            events,
+            vec![
+                HyprIpcEvent::Other,
+                HyprIpcEvent::Workspace {
+                    id: 1,
+                    name: "1".to_string()
+                },
+                HyprIpcEvent::OpenWindow {
+                    address: "0x55d0a1b2c3d0".to_string(),
+                    workspace: "1".to_string(),
+                    class: "kitty".to_string(),
+                    title: "kitty".to_string(),
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::ActiveWindow {
+                    address: Some("0x55d0a1b2c3d0".to_string())
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::WindowTitle {
+                    address: "0x55d0a1b2c3d0".to_string(),
+                    title: "nvim ~/notes, todo.md".to_string(),
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::MoveWindow {
+                    address: "0x55d0a1b2c3d0".to_string(),
+                    workspace_id: 2,
+                    workspace: "2".to_string(),
+                },
+                HyprIpcEvent::ChangeFloatingMode {
+                    address: "0x55d0a1b2c3d0".to_string(),
+                    floating: true,
+                },
+                HyprIpcEvent::Fullscreen { enabled: true },
+                HyprIpcEvent::Fullscreen { enabled: false },
+                HyprIpcEvent::FocusedMonitor {
+                    monitor: "HDMI-A-1".to_string(),
+                    workspace: "web".to_string(),
+                },
+                HyprIpcEvent::WorkspaceMoved {
+                    workspace: "web".to_string(),
+                    monitor: "DP-1".to_string(),
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::Urgent {
+                    address: "0x55d0a1b2c4e0".to_string()
+                },
+                HyprIpcEvent::CloseWindow {
+                    address: "0x55d0a1b2c3d0".to_string()
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::ActiveWindow { address: None },
+                HyprIpcEvent::MonitorAdded {
+                    name: "DP-2".to_string()
+                },
+                HyprIpcEvent::Other,
+                HyprIpcEvent::MonitorRemoved {
+                    name: "DP-2".to_string()
+                },
+                HyprIpcEvent::ConfigReloaded,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_window_title_with_commas() {
+        let event = parse_event("openwindow>>80e62df0,3,firefox,Inbox (3), Mail - Mozilla Firefox");
+
+        assert_eq!(
+            event,
+            HyprIpcEvent::OpenWindow {
+                address: "0x80e62df0".to_string(),
+                workspace: "3".to_string(),
+                class: "firefox".to_string(),
+                title: "Inbox (3), Mail - Mozilla Firefox".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_special_workspace_names() {
+        assert_eq!(
+            parse_event("workspacev2>>-98,special:scratch"),
+            HyprIpcEvent::Workspace {
+                id: -98,
+                name: "special:scratch".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        let malformed = [
+            "",
+            "openwindow",
+            "openwindow>>55d0a1b2c3d0,1",
+            "movewindowv2>>55d0a1b2c3d0,two,2",
+            "workspacev2>>1",
+            "fullscreen>>maybe",
+            "changefloatingmode>>55d0a1b2c3d0",
+            "somefutureevent>>a,b,c",
+        ];
+
+        for line in malformed {
+            assert_eq!(parse_event(line), HyprIpcEvent::Other, "line: {:?}", line);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use serde::{Deserialize, Deserializer};
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use crate::types::Client;
+
+/// Error types for requests on Hyprland's command socket
+#[derive(Debug, thiserror::Error)]
+pub enum HyprctlError {
+    #[error("Hyprland instance not found: {0}")]
+    NoInstance(String),
+
+    #[error("Socket error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid JSON reply: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Request '{request}' failed: {reply}")]
+    Rejected { request: String, reply: String },
+}
+
+impl HyprctlError {
+    pub fn rejected(request: &str, reply: &str) -> Self {
+        Self::Rejected {
+            request: request.to_string(),
+            reply: reply.trim().to_string(),
+        }
+    }
+}
+
+/// Reference to a workspace as embedded in monitor replies
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceRef {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A monitor as returned by `j/monitors`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Monitor {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default = "default_monitor_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub focused: bool,
+    #[serde(rename = "activeWorkspace", default)]
+    pub active_workspace: WorkspaceRef,
+}
+
+fn default_monitor_scale() -> f32 {
+    1.0
+}
+
+/// A workspace as returned by `j/workspaces`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub monitor: String,
+    #[serde(default)]
+    pub windows: u32,
+    #[serde(rename = "hasfullscreen", default)]
+    pub has_fullscreen: bool,
+    #[serde(rename = "lastwindow", default)]
+    pub last_window: String,
+}
+
+/// Where a window is, as returned by `j/clients`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ClientGeometry {
+    pub address: String,
+    pub at: [i32; 2],
+    pub size: [i32; 2],
+    pub workspace: WorkspaceRef,
+    /// Id of the monitor, as in `Monitor::id`
+    pub monitor: i64,
+    #[serde(deserialize_with = "fullscreen_state")]
+    pub fullscreen: bool,
+    /// Inactive windows of a group, which aren't drawn
+    pub hidden: bool,
+}
+
+/// Hyprland before 0.42 sends `fullscreen` as a bool, later versions as a mode number
+fn fullscreen_state<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Fullscreen {
+        Flag(bool),
+        Mode(i64),
+    }
+
+    Ok(match Fullscreen::deserialize(deserializer)? {
+        Fullscreen::Flag(enabled) => enabled,
+        Fullscreen::Mode(mode) => mode != 0,
+    })
+}
+
+/// Directory holding the sockets of the running Hyprland instance
+///
+/// Hyprland 0.40 moved its sockets from `/tmp/hypr` to `$XDG_RUNTIME_DIR/hypr`;
+/// both locations are checked.
+pub fn instance_dir() -> Result<PathBuf, HyprctlError> {
+    let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| HyprctlError::NoInstance("HYPRLAND_INSTANCE_SIGNATURE is not set".to_string()))?;
+
+    let mut candidates = Vec::new();
+    if let Ok(runtime_dir) = env::var("XDG_RUNTIME_DIR") {
+        candidates.push(Path::new(&runtime_dir).join("hypr").join(&signature));
+    }
+    candidates.push(Path::new("/tmp/hypr").join(&signature));
+
+    candidates
+        .into_iter()
+        .find(|dir| dir.join(".socket.sock").exists())
+        .ok_or_else(|| HyprctlError::NoInstance(format!("no socket for instance {}", signature)))
+}
+
+/// Client for Hyprland's request socket (`.socket.sock`)
+///
+/// Every request opens a fresh connection, as Hyprland closes the socket
+/// after writing its reply. Calls block, so keep them off the Bevy schedule.
+#[derive(Debug, Clone)]
+pub struct HyprctlSocket {
+    path: PathBuf,
+}
+
+impl HyprctlSocket {
+    /// Locate the request socket of the running Hyprland instance
+    pub fn from_env() -> Result<Self, HyprctlError> {
+        Ok(Self::at(instance_dir()?.join(".socket.sock")))
+    }
+
+    /// Use the socket at `path`
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Send a raw request and return the raw reply
+    pub fn request(&self, request: &str) -> Result<String, HyprctlError> {
+        let mut stream = UnixStream::connect(&self.path)?;
+        stream.write_all(request.as_bytes())?;
+
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply)
+    }
+
+    fn request_json<T: serde::de::DeserializeOwned>(&self, request: &str) -> Result<T, HyprctlError> {
+        let reply = self.request(request)?;
+        serde_json::from_str(&reply).map_err(|e| {
+            if reply.trim_start().starts_with(['[', '{']) {
+                HyprctlError::Json(e)
+            } else {
+                HyprctlError::rejected(request, &reply)
+            }
+        })
+    }
+
+    /// Run a dispatcher, e.g. `dispatch("focuswindow", "address:0x1234")`
+    pub fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<(), HyprctlError> {
+        let request = format!("dispatch {} {}", dispatcher, argument);
+        let reply = self.request(&request)?;
+
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(HyprctlError::rejected(&request, &reply))
+        }
+    }
+
+    pub fn clients(&self) -> Result<Vec<Client>, HyprctlError> {
+        self.request_json("j/clients")
+    }
+
+    pub fn client_geometry(&self) -> Result<Vec<ClientGeometry>, HyprctlError> {
+        self.request_json("j/clients")
+    }
+
+    pub fn monitors(&self) -> Result<Vec<Monitor>, HyprctlError> {
+        self.request_json("j/monitors")
+    }
+
+    pub fn workspaces(&self) -> Result<Vec<Workspace>, HyprctlError> {
+        self.request_json("j/workspaces")
+    }
+
+    pub fn focus_window(&self, address: &str) -> Result<(), HyprctlError> {
+        self.dispatch("focuswindow", &window_selector(address))
+    }
+
+    pub fn close_window(&self, address: &str) -> Result<(), HyprctlError> {
+        self.dispatch("closewindow", &window_selector(address))
+    }
+
+    pub fn exec(&self, command: &str) -> Result<(), HyprctlError> {
+        self.dispatch("exec", command)
+    }
+
+    pub fn switch_workspace(&self, workspace: &str) -> Result<(), HyprctlError> {
+        self.dispatch("workspace", &workspace_selector(workspace))
+    }
+
+    /// Move a window to `workspace` without following it there
+    pub fn move_window_to_workspace(&self, address: &str, workspace: &str) -> Result<(), HyprctlError> {
+        let argument = format!("{},{}", workspace_selector(workspace), window_selector(address));
+        self.dispatch("movetoworkspacesilent", &argument)
+    }
+
+    pub fn toggle_floating(&self, address: &str) -> Result<(), HyprctlError> {
+        self.dispatch("togglefloating", &window_selector(address))
+    }
+
+    /// `fullscreen` only acts on the active window, so the window is focused first
+    pub fn toggle_fullscreen(&self, address: &str) -> Result<(), HyprctlError> {
+        self.focus_window(address)?;
+        self.dispatch("fullscreen", "0")
+    }
+}
+
+/// Numbered workspaces go by id, the rest by name
+fn workspace_selector(workspace: &str) -> String {
+    if workspace.parse::<i64>().is_ok() {
+        workspace.to_string()
+    } else {
+        format!("name:{}", workspace)
+    }
+}
+
+fn window_selector(address: &str) -> String {
+    if address.starts_with("address:") {
+        address.to_string()
+    } else {
+        format!("address:{}", address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use tempfile::TempDir;
+
+    const CLIENTS_JSON: &str = r#"[
+        {"address": "0x55d0a1b2c3d0", "mapped": true, "at": [0, 0], "size": [800, 600],
+         "workspace": {"id": 1, "name": "1"}, "floating": false, "monitor": 0,
+         "class": "kitty", "title": "~", "initialClass": "kitty", "pid": 4242, "fullscreen": false},
+        {"address": "0x55d0a1b2c4e0", "workspace": {"id": 2, "name": "web"}, "monitor": 1,
+         "class": "firefox", "title": "Mozilla Firefox", "pid": 4343, "fullscreen": 2}
+    ]"#;
+
+    const MONITORS_JSON: &str = r#"[
+        {"id": 0, "name": "DP-1", "description": "Dell U2720Q", "make": "Dell",
+         "width": 3840, "height": 2160, "refreshRate": 59.997, "x": 0, "y": 0,
+         "activeWorkspace": {"id": 1, "name": "1"}, "scale": 1.5, "focused": true},
+        {"id": 1, "name": "HDMI-A-1", "width": 1920, "height": 1080, "x": 2560, "y": 0,
+         "activeWorkspace": {"id": 2, "name": "web"}, "scale": 1.0, "focused": false}
+    ]"#;
+
+    const WORKSPACES_JSON: &str = r#"[
+        {"id": 1, "name": "1", "monitor": "DP-1", "monitorID": 0, "windows": 1,
+         "hasfullscreen": false, "lastwindow": "0x55d0a1b2c3d0", "lastwindowtitle": "~"},
+        {"id": 2, "name": "web", "monitor": "HDMI-A-1", "windows": 1,
+         "hasfullscreen": true, "lastwindow": "0x55d0a1b2c4e0"}
+    ]"#;
+
+    /// Stand-in for Hyprland: answers each connection with the next canned reply
+    /// and reports the requests it received
+    fn serve(replies: Vec<&'static str>) -> (TempDir, HyprctlSocket, Receiver<String>) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".socket.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (sender, receiver) = channel();
+
+        thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buffer = [0u8; 1024];
+                let read = stream.read(&mut buffer).unwrap();
+                sender
+                    .send(String::from_utf8_lossy(&buffer[..read]).to_string())
+                    .unwrap();
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+
+        (dir, HyprctlSocket::at(path), receiver)
+    }
+
+    #[test]
+    fn test_clients() {
+        let (_dir, socket, requests) = serve(vec![CLIENTS_JSON]);
+
+        let clients = socket.clients().unwrap();
+
+        assert_eq!(requests.recv().unwrap(), "j/clients");
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients[0].address, "0x55d0a1b2c3d0");
+        assert_eq!(clients[0].class, "kitty");
+        assert_eq!(clients[0].pid, 4242);
+        assert_eq!(clients[1].workspace.name, "web");
+    }
+
+    #[test]
+    fn test_client_geometry() {
+        let (_dir, socket, requests) = serve(vec![CLIENTS_JSON]);
+
+        let clients = socket.client_geometry().unwrap();
+
+        assert_eq!(requests.recv().unwrap(), "j/clients");
+        assert_eq!(clients[0].at, [0, 0]);
+        assert_eq!(clients[0].size, [800, 600]);
+        assert!(!clients[0].fullscreen);
+        assert_eq!(clients[1].monitor, 1);
+        assert_eq!(clients[1].size, [0, 0]);
+        assert!(clients[1].fullscreen);
+    }
+
+    #[test]
+    fn test_monitors() {
+        let (_dir, socket, requests) = serve(vec![MONITORS_JSON]);
+
+        let monitors = socket.monitors().unwrap();
+
+        assert_eq!(requests.recv().unwrap(), "j/monitors");
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].name, "DP-1");
+        assert_eq!(monitors[0].scale, 1.5);
+        assert!(monitors[0].focused);
+        assert_eq!(monitors[1].x, 2560);
+        assert_eq!(monitors[1].description, "");
+        assert_eq!(monitors[1].active_workspace.name, "web");
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let (_dir, socket, requests) = serve(vec![WORKSPACES_JSON]);
+
+        let workspaces = socket.workspaces().unwrap();
+
+        assert_eq!(requests.recv().unwrap(), "j/workspaces");
+        assert_eq!(workspaces[0].monitor, "DP-1");
+        assert_eq!(workspaces[0].last_window, "0x55d0a1b2c3d0");
+        assert!(workspaces[1].has_fullscreen);
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let (_dir, socket, requests) = serve(vec!["ok", "ok", "ok", "ok"]);
+
+        socket.focus_window("0x55d0a1b2c3d0").unwrap();
+        socket.exec("kitty --single-instance").unwrap();
+        socket.switch_workspace("2").unwrap();
+        socket.switch_workspace("web").unwrap();
+
+        assert_eq!(
+            requests.recv().unwrap(),
+            "dispatch focuswindow address:0x55d0a1b2c3d0"
+        );
+        assert_eq!(requests.recv().unwrap(), "dispatch exec kitty --single-instance");
+        assert_eq!(requests.recv().unwrap(), "dispatch workspace 2");
+        assert_eq!(requests.recv().unwrap(), "dispatch workspace name:web");
+    }
+
+    #[test]
+    fn test_window_dispatchers() {
+        let (_dir, socket, requests) = serve(vec!["ok", "ok", "ok", "ok", "ok"]);
+
+        socket.move_window_to_workspace("0x55d0a1b2c3d0", "3").unwrap();
+        socket.move_window_to_workspace("0x55d0a1b2c3d0", "web").unwrap();
+        socket.toggle_floating("0x55d0a1b2c3d0").unwrap();
+        socket.toggle_fullscreen("0x55d0a1b2c3d0").unwrap();
+
+        assert_eq!(
+            requests.recv().unwrap(),
+            "dispatch movetoworkspacesilent 3,address:0x55d0a1b2c3d0"
+        );
+        assert_eq!(
+            requests.recv().unwrap(),
+            "dispatch movetoworkspacesilent name:web,address:0x55d0a1b2c3d0"
+        );
+        assert_eq!(requests.recv().unwrap(), "dispatch togglefloating address:0x55d0a1b2c3d0");
+        assert_eq!(
+            requests.recv().unwrap(),
+            "dispatch focuswindow address:0x55d0a1b2c3d0"
+        );
+        assert_eq!(requests.recv().unwrap(), "dispatch fullscreen 0");
+    }
+
+    #[test]
+    fn test_dispatch_rejected() {
+        let (_dir, socket, _requests) = serve(vec!["No such window found\n"]);
+
+        match socket.focus_window("address:0xdead") {
+            Err(HyprctlError::Rejected { request, reply }) => {
+                assert_eq!(request, "dispatch focuswindow address:0xdead");
+                assert_eq!(reply, "No such window found");
+            }
+            other => panic!("Expected rejected dispatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_json_request_rejected() {
+        let (_dir, socket, _requests) = serve(vec!["unknown request"]);
+
+        assert!(matches!(
+            socket.clients(),
+            Err(HyprctlError::Rejected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_malformed_json() {
+        let (_dir, socket, _requests) = serve(vec![r#"[{"address": 12}]"#]);
+
+        assert!(matches!(socket.clients(), Err(HyprctlError::Json(_))));
+    }
+
+    #[test]
+    fn test_missing_socket() {
+        let dir = TempDir::new().unwrap();
+        let socket = HyprctlSocket::at(dir.path().join(".socket.sock"));
+
+        assert!(matches!(socket.clients(), Err(HyprctlError::Io(_))));
+    }
+}
@@ -0,0 +1,219 @@
+pub mod events;
+pub mod ipc;
+
+use bevy::log::{info, warn};
+use bevy::math::{IRect, IVec2, Vec2};
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::Sender;
+
+use super::{
+    Backoff, BackendError, DesktopGeometry, MonitorGeometry, MonitorInfo, WindowGeometry,
+    WindowManagerBackend,
+};
+use crate::types::{Client, HyprIpcEvent};
+use ipc::{HyprctlError, HyprctlSocket};
+
+/// Backend for Hyprland, using `.socket.sock` for requests and `.socket2.sock` for events
+#[derive(Default)]
+pub struct HyprlandBackend;
+
+impl HyprlandBackend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Run a request on a worker thread, logging failures
+    ///
+    /// Dispatchers are fire-and-forget from the dock's point of view, and the
+    /// socket round trip must not stall the frame that triggered it.
+    fn spawn_request(
+        &self,
+        request: impl FnOnce(&HyprctlSocket) -> Result<(), HyprctlError> + Send + 'static,
+    ) -> Result<(), BackendError> {
+        let socket = HyprctlSocket::from_env()?;
+        std::thread::spawn(move || {
+            if let Err(e) = request(&socket) {
+                warn!("Hyprland request failed: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+enum StreamEnd {
+    /// Hyprland closed the socket
+    Closed,
+    /// The dock stopped listening
+    ReceiverDropped,
+}
+
+/// Connect to `.socket2.sock`, resync the window list and forward events until the socket closes
+///
+/// The listing is taken after connecting, so no event between the two is lost.
+fn stream_events(
+    sender: &Sender<HyprIpcEvent>,
+    backoff: &mut Backoff,
+) -> Result<StreamEnd, BackendError> {
+    let stream = UnixStream::connect(ipc::instance_dir()?.join(".socket2.sock"))?;
+    let clients = HyprctlSocket::from_env()?.clients()?;
+
+    if sender.send(HyprIpcEvent::Resync { clients }).is_err() {
+        return Ok(StreamEnd::ReceiverDropped);
+    }
+    backoff.reset();
+
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        if sender.send(events::parse_event(&line)).is_err() {
+            return Ok(StreamEnd::ReceiverDropped);
+        }
+    }
+    Ok(StreamEnd::Closed)
+}
+
+impl From<HyprctlError> for BackendError {
+    fn from(error: HyprctlError) -> Self {
+        match error {
+            HyprctlError::NoInstance(message) => BackendError::Unavailable(message),
+            HyprctlError::Io(e) => BackendError::Io(e),
+            HyprctlError::Json(e) => BackendError::Protocol(e.to_string()),
+            rejected @ HyprctlError::Rejected { .. } => {
+                BackendError::CommandFailed(rejected.to_string())
+            }
+        }
+    }
+}
+
+impl WindowManagerBackend for HyprlandBackend {
+    fn name(&self) -> &'static str {
+        "Hyprland"
+    }
+
+    fn list_windows(&self) -> Result<Vec<Client>, BackendError> {
+        Ok(HyprctlSocket::from_env()?.clients()?)
+    }
+
+    fn subscribe(&self, sender: Sender<HyprIpcEvent>) -> Result<(), BackendError> {
+        if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_none() {
+            return Err(BackendError::Unavailable(
+                "HYPRLAND_INSTANCE_SIGNATURE is not set".to_string(),
+            ));
+        }
+
+        std::thread::spawn(move || {
+            let mut backoff = Backoff::default();
+            loop {
+                match stream_events(&sender, &mut backoff) {
+                    Ok(StreamEnd::ReceiverDropped) => break,
+                    Ok(StreamEnd::Closed) => warn!("Hyprland event socket closed"),
+                    Err(e) => warn!("Hyprland event socket unavailable: {}", e),
+                }
+
+                if sender.send(HyprIpcEvent::Disconnected).is_err() {
+                    break;
+                }
+                let delay = backoff.next_delay();
+                info!("Reconnecting to Hyprland in {:?}", delay);
+                std::thread::sleep(delay);
+            }
+        });
+
+        Ok(())
+    }
+
+    fn focus(&self, address: &str) -> Result<(), BackendError> {
+        let address = address.to_string();
+        self.spawn_request(move |socket| socket.focus_window(&address))
+    }
+
+    fn close(&self, address: &str) -> Result<(), BackendError> {
+        let address = address.to_string();
+        self.spawn_request(move |socket| socket.close_window(&address))
+    }
+
+    fn launch(&self, command: &str) -> Result<(), BackendError> {
+        let command = command.to_string();
+        self.spawn_request(move |socket| socket.exec(&command))
+    }
+
+    fn switch_workspace(&self, workspace: &str) -> Result<(), BackendError> {
+        let workspace = workspace.to_string();
+        self.spawn_request(move |socket| socket.switch_workspace(&workspace))
+    }
+
+    fn move_to_workspace(&self, address: &str, workspace: &str) -> Result<(), BackendError> {
+        let address = address.to_string();
+        let workspace = workspace.to_string();
+        self.spawn_request(move |socket| socket.move_window_to_workspace(&address, &workspace))
+    }
+
+    fn toggle_floating(&self, address: &str) -> Result<(), BackendError> {
+        let address = address.to_string();
+        self.spawn_request(move |socket| socket.toggle_floating(&address))
+    }
+
+    fn toggle_fullscreen(&self, address: &str) -> Result<(), BackendError> {
+        let address = address.to_string();
+        self.spawn_request(move |socket| socket.toggle_fullscreen(&address))
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, BackendError> {
+        let socket = HyprctlSocket::from_env()?;
+        let workspaces = socket.workspaces()?;
+
+        Ok(socket
+            .monitors()?
+            .into_iter()
+            .map(|monitor| MonitorInfo {
+                workspaces: workspaces
+                    .iter()
+                    .filter(|workspace| workspace.monitor == monitor.name)
+                    .map(|workspace| workspace.name.clone())
+                    .collect(),
+                focused: monitor.focused,
+                active_workspace: monitor.active_workspace.name,
+                name: monitor.name,
+            })
+            .collect())
+    }
+    fn geometry(&self) -> Result<DesktopGeometry, BackendError> {
+        let socket = HyprctlSocket::from_env()?;
+        let monitors = socket.monitors()?;
+
+        let windows = socket
+            .client_geometry()?
+            .into_iter()
+            .filter(|client| !client.hidden)
+            .map(|client| {
+                let monitor = monitors.iter().find(|m| m.id == client.monitor);
+                let at = IVec2::from(client.at);
+                WindowGeometry {
+                    monitor: monitor.map(|m| m.name.clone()).unwrap_or_default(),
+                    workspace: client.workspace.name,
+                    rect: IRect::from_corners(at, at + IVec2::from(client.size)),
+                    fullscreen: client.fullscreen,
+                    address: client.address,
+                }
+            })
+            .collect();
+
+        let monitors = monitors
+            .into_iter()
+            .map(|monitor| {
+                // Clients are placed in logical pixels, monitors report physical ones
+                let size = (Vec2::new(monitor.width as f32, monitor.height as f32) / monitor.scale)
+                    .round()
+                    .as_ivec2();
+                let at = IVec2::new(monitor.x, monitor.y);
+                MonitorGeometry {
+                    rect: IRect::from_corners(at, at + size),
+                    focused: monitor.focused,
+                    active_workspace: monitor.active_workspace.name,
+                    name: monitor.name,
+                }
+            })
+            .collect();
+
+        Ok(DesktopGeometry { monitors, windows })
+    }
+}
@@ -0,0 +1,302 @@
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{BackendError, DesktopGeometry, MonitorInfo, WindowManagerBackend};
+use crate::types::{Client, ClientWorkspace, HyprIpcEvent};
+
+/// A request the dock made to the mock compositor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    Focus(String),
+    Close(String),
+    Launch(String),
+    SwitchWorkspace(String),
+    MoveToWorkspace(String, String),
+    ToggleFloating(String),
+    ToggleFullscreen(String),
+}
+
+/// In-process compositor for tests and demos
+///
+/// Serves a fixed window list, replays a script of events once subscribed,
+/// and lets tests inject more events with `emit`. Focus and close requests
+/// are answered with the events a real compositor would send back.
+#[derive(Default)]
+pub struct MockBackend {
+    clients: Arc<Mutex<Vec<Client>>>,
+    monitors: Vec<MonitorInfo>,
+    geometry: Option<DesktopGeometry>,
+    script: Vec<(Duration, HyprIpcEvent)>,
+    sender: Mutex<Option<Sender<HyprIpcEvent>>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+pub fn mock_client(address: &str, class: &str, workspace: &str) -> Client {
+    Client {
+        address: address.to_string(),
+        class: class.to_string(),
+        title: class.to_string(),
+        pid: 0,
+        workspace: ClientWorkspace {
+            name: workspace.to_string(),
+        },
+    }
+}
+
+/// A monitor showing the first of `workspaces`
+pub fn mock_monitor(name: &str, workspaces: &[&str]) -> MonitorInfo {
+    MonitorInfo {
+        name: name.to_string(),
+        focused: false,
+        active_workspace: workspaces.first().copied().unwrap_or_default().to_string(),
+        workspaces: workspaces.iter().map(|w| w.to_string()).collect(),
+    }
+}
+
+impl MockBackend {
+    pub fn new(clients: Vec<Client>) -> Self {
+        Self {
+            clients: Arc::new(Mutex::new(clients)),
+            ..Self::default()
+        }
+    }
+
+    /// Play `script` after subscribing, waiting the given delay before each event
+    pub fn with_script(mut self, script: Vec<(Duration, HyprIpcEvent)>) -> Self {
+        self.script = script;
+        self
+    }
+
+    /// Report `monitors` from `monitors()`
+    pub fn with_monitors(mut self, monitors: Vec<MonitorInfo>) -> Self {
+        self.monitors = monitors;
+        self
+    }
+
+    /// Report `geometry` from `geometry()`, which fails otherwise
+    pub fn with_geometry(mut self, geometry: DesktopGeometry) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    /// A few windows that come and go, for trying the dock without a compositor
+    pub fn demo() -> Self {
+        let second = Duration::from_secs(2);
+        Self::new(vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "kitty", "2"),
+        ])
+        .with_script(vec![
+            (second, open_event("0x4", "code", "2")),
+            (second, HyprIpcEvent::Urgent { address: "0x1".to_string() }),
+            (second, open_event("0x5", "steam", "3")),
+            (second, HyprIpcEvent::CloseWindow { address: "0x3".to_string() }),
+            (second, HyprIpcEvent::CloseWindow { address: "0x5".to_string() }),
+        ])
+    }
+
+    /// Send `event` to the dock as if the compositor had, keeping the window list in step
+    ///
+    /// Returns false if the dock hasn't subscribed or has stopped listening.
+    pub fn emit(&self, event: HyprIpcEvent) -> bool {
+        apply_event(&mut self.clients.lock().unwrap(), &event);
+        match self.sender.lock().unwrap().as_ref() {
+            Some(sender) => sender.send(event).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Everything the dock asked for so far, oldest first
+    #[allow(dead_code)]
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record(&self, call: MockCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+pub fn open_event(address: &str, class: &str, workspace: &str) -> HyprIpcEvent {
+    HyprIpcEvent::OpenWindow {
+        address: address.to_string(),
+        workspace: workspace.to_string(),
+        class: class.to_string(),
+        title: class.to_string(),
+    }
+}
+
+fn apply_event(clients: &mut Vec<Client>, event: &HyprIpcEvent) {
+    match event {
+        HyprIpcEvent::OpenWindow {
+            address,
+            workspace,
+            class,
+            title,
+        } => clients.push(Client {
+            title: title.clone(),
+            ..mock_client(address, class, workspace)
+        }),
+        HyprIpcEvent::CloseWindow { address } => clients.retain(|c| &c.address != address),
+        HyprIpcEvent::MoveWindow {
+            address, workspace, ..
+        } => {
+            if let Some(client) = clients.iter_mut().find(|c| &c.address == address) {
+                client.workspace.name = workspace.clone();
+            }
+        }
+        _ => {}
+    }
+}
+
+impl WindowManagerBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+
+    fn list_windows(&self) -> Result<Vec<Client>, BackendError> {
+        Ok(self.clients.lock().unwrap().clone())
+    }
+
+    fn subscribe(&self, sender: Sender<HyprIpcEvent>) -> Result<(), BackendError> {
+        *self.sender.lock().unwrap() = Some(sender.clone());
+
+        if !self.script.is_empty() {
+            let script = self.script.clone();
+            let clients = self.clients.clone();
+            std::thread::spawn(move || {
+                for (delay, event) in script {
+                    std::thread::sleep(delay);
+                    apply_event(&mut clients.lock().unwrap(), &event);
+                    if sender.send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn focus(&self, address: &str) -> Result<(), BackendError> {
+        if !self.clients.lock().unwrap().iter().any(|c| c.address == address) {
+            return Err(BackendError::UnknownWindow(address.to_string()));
+        }
+        self.record(MockCall::Focus(address.to_string()));
+        self.emit(HyprIpcEvent::ActiveWindow {
+            address: Some(address.to_string()),
+        });
+        Ok(())
+    }
+
+    fn close(&self, address: &str) -> Result<(), BackendError> {
+        self.record(MockCall::Close(address.to_string()));
+        self.emit(HyprIpcEvent::CloseWindow {
+            address: address.to_string(),
+        });
+        Ok(())
+    }
+
+    fn launch(&self, command: &str) -> Result<(), BackendError> {
+        self.record(MockCall::Launch(command.to_string()));
+        Ok(())
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, BackendError> {
+        Ok(self.monitors.clone())
+    }
+
+    fn geometry(&self) -> Result<DesktopGeometry, BackendError> {
+        self.geometry
+            .clone()
+            .ok_or_else(|| BackendError::Unavailable("no geometry given to the mock".to_string()))
+    }
+
+    fn switch_workspace(&self, workspace: &str) -> Result<(), BackendError> {
+        self.record(MockCall::SwitchWorkspace(workspace.to_string()));
+        self.emit(HyprIpcEvent::Workspace {
+            id: workspace.parse().unwrap_or(-1),
+            name: workspace.to_string(),
+        });
+        Ok(())
+    }
+
+    fn move_to_workspace(&self, address: &str, workspace: &str) -> Result<(), BackendError> {
+        self.record(MockCall::MoveToWorkspace(address.to_string(), workspace.to_string()));
+        self.emit(HyprIpcEvent::MoveWindow {
+            address: address.to_string(),
+            workspace_id: workspace.parse().unwrap_or(-1),
+            workspace: workspace.to_string(),
+        });
+        Ok(())
+    }
+
+    fn toggle_floating(&self, address: &str) -> Result<(), BackendError> {
+        self.record(MockCall::ToggleFloating(address.to_string()));
+        Ok(())
+    }
+
+    fn toggle_fullscreen(&self, address: &str) -> Result<(), BackendError> {
+        self.record(MockCall::ToggleFullscreen(address.to_string()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_emit_keeps_window_list_in_step() {
+        let backend = MockBackend::new(vec![mock_client("0x1", "kitty", "1")]);
+        let (sender, receiver) = channel();
+        backend.subscribe(sender).unwrap();
+
+        assert!(backend.emit(open_event("0x2", "firefox", "1")));
+        assert!(backend.emit(HyprIpcEvent::CloseWindow {
+            address: "0x1".to_string()
+        }));
+
+        let addresses: Vec<String> = backend
+            .list_windows()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.address)
+            .collect();
+        assert_eq!(addresses, vec!["0x2"]);
+        assert_eq!(receiver.try_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_focus_answers_like_a_compositor() {
+        let backend = MockBackend::new(vec![mock_client("0x1", "kitty", "1")]);
+        let (sender, receiver) = channel();
+        backend.subscribe(sender).unwrap();
+
+        backend.focus("0x1").unwrap();
+
+        assert!(backend.focus("0x9").is_err());
+        assert_eq!(backend.calls(), vec![MockCall::Focus("0x1".to_string())]);
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            HyprIpcEvent::ActiveWindow {
+                address: Some("0x1".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_script_plays_after_subscribe() {
+        let backend = MockBackend::new(Vec::new()).with_script(vec![(
+            Duration::ZERO,
+            open_event("0x1", "kitty", "1"),
+        )]);
+        let (sender, receiver) = channel();
+        backend.subscribe(sender).unwrap();
+
+        let event = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(matches!(event, HyprIpcEvent::OpenWindow { ref class, .. } if class == "kitty"));
+    }
+}
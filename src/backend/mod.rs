@@ -0,0 +1,306 @@
+pub mod hyprland;
+pub mod mock;
+pub mod sway;
+pub mod wlr;
+
+use bevy::log::{info, warn};
+use bevy::math::IRect;
+use bevy::prelude::Resource;
+use clap::{PossibleValue, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::types::{Client, HyprIpcEvent};
+
+pub use hyprland::HyprlandBackend;
+pub use mock::MockBackend;
+pub use sway::SwayBackend;
+pub use wlr::WlrBackend;
+
+/// Interface between the dock and the window manager it runs on
+///
+/// Backends translate their compositor's window model into `Client`s and
+/// `HyprIpcEvent`s, so the rest of the dock never talks to a compositor
+/// directly. Window addresses are opaque strings owned by the backend.
+pub trait WindowManagerBackend: Send + Sync {
+    /// Human-readable name of this backend for logging
+    fn name(&self) -> &'static str;
+
+    /// List all currently open windows
+    fn list_windows(&self) -> Result<Vec<Client>, BackendError>;
+
+    /// Start forwarding window events to `sender`
+    ///
+    /// Implementations spawn their own reader thread and return immediately.
+    fn subscribe(&self, sender: Sender<HyprIpcEvent>) -> Result<(), BackendError>;
+
+    /// Give keyboard focus to the window with the given address
+    fn focus(&self, address: &str) -> Result<(), BackendError>;
+
+    /// Ask the window with the given address to close
+    fn close(&self, address: &str) -> Result<(), BackendError>;
+
+    /// Run a shell command through the window manager
+    fn launch(&self, command: &str) -> Result<(), BackendError>;
+
+    /// List monitors and their workspaces, empty when the backend can't tell
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, BackendError> {
+        Ok(Vec::new())
+    }
+
+    /// Show the workspace named `workspace` on the focused monitor
+    fn switch_workspace(&self, workspace: &str) -> Result<(), BackendError> {
+        Err(BackendError::Unavailable(format!(
+            "{} can't switch to workspace {}",
+            self.name(),
+            workspace
+        )))
+    }
+
+    /// Move the window with the given address to `workspace`, leaving focus where it is
+    fn move_to_workspace(&self, address: &str, workspace: &str) -> Result<(), BackendError> {
+        Err(BackendError::Unavailable(format!(
+            "{} can't move {} to workspace {}",
+            self.name(),
+            address,
+            workspace
+        )))
+    }
+
+    /// Switch the window with the given address between floating and tiled
+    fn toggle_floating(&self, address: &str) -> Result<(), BackendError> {
+        Err(BackendError::Unavailable(format!(
+            "{} can't toggle floating on {}",
+            self.name(),
+            address
+        )))
+    }
+
+    /// Make the window with the given address fullscreen, or take it out of fullscreen
+    fn toggle_fullscreen(&self, address: &str) -> Result<(), BackendError> {
+        Err(BackendError::Unavailable(format!(
+            "{} can't toggle fullscreen on {}",
+            self.name(),
+            address
+        )))
+    }
+
+    /// Where monitors and windows are on the desktop, for intellihide
+    fn geometry(&self) -> Result<DesktopGeometry, BackendError> {
+        Err(BackendError::Unavailable(format!(
+            "{} doesn't report window geometry",
+            self.name()
+        )))
+    }
+}
+
+/// A monitor, by connector name, and the workspaces on it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub focused: bool,
+    /// Workspace the monitor is showing
+    pub active_workspace: String,
+    /// Every workspace that lives on the monitor, shown or not
+    pub workspaces: Vec<String>,
+}
+
+/// Monitors and windows laid out on the desktop, in logical pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesktopGeometry {
+    pub monitors: Vec<MonitorGeometry>,
+    pub windows: Vec<WindowGeometry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorGeometry {
+    pub name: String,
+    pub rect: IRect,
+    pub focused: bool,
+    pub active_workspace: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowGeometry {
+    pub address: String,
+    pub monitor: String,
+    pub workspace: String,
+    pub rect: IRect,
+    pub fullscreen: bool,
+}
+
+/// Error types for window manager operations
+#[derive(Debug, thiserror::Error)]
+pub enum BackendError {
+    #[error("Backend unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    #[error("Command failed: {0}")]
+    CommandFailed(String),
+
+    #[error("Unknown window: {0}")]
+    UnknownWindow(String),
+}
+
+/// Exponential backoff between reconnection attempts
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            current: initial,
+        }
+    }
+
+    /// Delay before the next attempt; doubles on every call up to `max`
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    /// Start over from the initial delay after a successful connection
+    pub fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(250), Duration::from_secs(30))
+    }
+}
+
+/// Which backend to use, as written in `config.toml`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Pick a backend from the environment
+    #[default]
+    Auto,
+    Hyprland,
+    Sway,
+    Wlr,
+    /// Scripted demo windows, no compositor needed
+    Mock,
+}
+
+impl BackendKind {
+    /// Detect the running compositor from its environment variables
+    pub fn detect() -> Self {
+        let is_set = |name: &str| std::env::var_os(name).is_some();
+
+        if is_set("HYPRLAND_INSTANCE_SIGNATURE") {
+            BackendKind::Hyprland
+        } else if is_set("SWAYSOCK") || is_set("I3SOCK") {
+            BackendKind::Sway
+        } else if is_set("WAYLAND_DISPLAY") {
+            BackendKind::Wlr
+        } else {
+            BackendKind::Hyprland
+        }
+    }
+}
+
+/// Lets `--backend` take the same names as `config.toml`
+impl ValueEnum for BackendKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            BackendKind::Auto,
+            BackendKind::Hyprland,
+            BackendKind::Sway,
+            BackendKind::Wlr,
+            BackendKind::Mock,
+        ]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue<'a>> {
+        Some(match self {
+            BackendKind::Auto => PossibleValue::new("auto"),
+            BackendKind::Hyprland => PossibleValue::new("hyprland"),
+            BackendKind::Sway => PossibleValue::new("sway"),
+            BackendKind::Wlr => PossibleValue::new("wlr"),
+            BackendKind::Mock => PossibleValue::new("mock"),
+        })
+    }
+}
+
+/// The active window manager backend
+#[derive(Resource, Clone)]
+pub struct WindowManager(pub Arc<dyn WindowManagerBackend>);
+
+impl Deref for WindowManager {
+    type Target = dyn WindowManagerBackend;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Create the backend selected by `kind`, resolving `Auto` from the environment
+///
+/// The wlr backend needs a live Wayland connection; if that fails the dock
+/// falls back to Hyprland, which was the only backend before.
+pub fn connect(kind: BackendKind) -> WindowManager {
+    let kind = match kind {
+        BackendKind::Auto => BackendKind::detect(),
+        other => other,
+    };
+
+    let backend: Arc<dyn WindowManagerBackend> = match kind {
+        BackendKind::Sway => Arc::new(SwayBackend::new()),
+        BackendKind::Wlr => match WlrBackend::connect() {
+            Ok(backend) => Arc::new(backend),
+            Err(e) => {
+                warn!("Failed to start wlr backend: {}, falling back to Hyprland", e);
+                Arc::new(HyprlandBackend::new())
+            }
+        },
+        BackendKind::Mock => Arc::new(MockBackend::demo()),
+        BackendKind::Auto | BackendKind::Hyprland => Arc::new(HyprlandBackend::new()),
+    };
+
+    info!("Using {} window manager backend", backend.name());
+    WindowManager(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(500));
+
+        let delays: Vec<u128> = (0..5).map(|_| backoff.next_delay().as_millis()).collect();
+
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn test_backoff_reset() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+        backoff.next_delay();
+        backoff.next_delay();
+
+        backoff.reset();
+
+        assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    }
+}
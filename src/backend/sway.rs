@@ -0,0 +1,511 @@
+use bevy::log::warn;
+use serde_json::Value;
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::Sender;
+
+use super::{BackendError, MonitorInfo, WindowManagerBackend};
+use crate::types::{Client, ClientWorkspace, HyprIpcEvent};
+
+const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+const WORKSPACE_EVENT: u32 = 0x8000_0000;
+const WINDOW_EVENT: u32 = 0x8000_0003;
+
+/// Backend for Sway and i3, speaking the i3 IPC protocol over `$SWAYSOCK`/`$I3SOCK`
+///
+/// Window addresses are the container ids from the layout tree.
+#[derive(Default)]
+pub struct SwayBackend;
+
+impl SwayBackend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn socket_path() -> Result<String, BackendError> {
+        env::var("SWAYSOCK")
+            .or_else(|_| env::var("I3SOCK"))
+            .map_err(|_| BackendError::Unavailable("SWAYSOCK/I3SOCK is not set".to_string()))
+    }
+
+    fn connect_socket() -> Result<UnixStream, BackendError> {
+        Ok(UnixStream::connect(Self::socket_path()?)?)
+    }
+
+    fn request(&self, message_type: u32, payload: &str) -> Result<Value, BackendError> {
+        let mut stream = Self::connect_socket()?;
+        write_message(&mut stream, message_type, payload)?;
+        let (_, body) = read_message(&mut stream)?;
+        serde_json::from_slice(&body).map_err(|e| BackendError::Protocol(e.to_string()))
+    }
+
+    fn run_command(&self, command: &str) -> Result<(), BackendError> {
+        let reply = self.request(RUN_COMMAND, command)?;
+        let results = reply.as_array().cloned().unwrap_or_default();
+
+        for result in results {
+            if !result["success"].as_bool().unwrap_or(false) {
+                let error = result["error"].as_str().unwrap_or("unknown error");
+                return Err(BackendError::CommandFailed(error.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_message(stream: &mut UnixStream, message_type: u32, payload: &str) -> std::io::Result<()> {
+    let mut message = Vec::with_capacity(14 + payload.len());
+    message.extend_from_slice(IPC_MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    message.extend_from_slice(&message_type.to_le_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    stream.write_all(&message)
+}
+
+fn read_message(stream: &mut UnixStream) -> Result<(u32, Vec<u8>), BackendError> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+
+    if &header[..6] != IPC_MAGIC {
+        return Err(BackendError::Protocol("Invalid i3-ipc magic".to_string()));
+    }
+
+    let length = u32::from_le_bytes([header[6], header[7], header[8], header[9]]) as usize;
+    let message_type = u32::from_le_bytes([header[10], header[11], header[12], header[13]]);
+
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body)?;
+    Ok((message_type, body))
+}
+
+/// Class of a container: Wayland `app_id`, or the X11 class for XWayland/i3 windows
+fn container_class(node: &Value) -> Option<String> {
+    node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str())
+        .filter(|class| !class.is_empty())
+        .map(str::to_string)
+}
+
+fn container_to_client(node: &Value, workspace: &str) -> Option<Client> {
+    let class = container_class(node)?;
+    let id = node["id"].as_i64()?;
+
+    Some(Client {
+        class,
+        address: id.to_string(),
+        title: node["name"].as_str().unwrap_or_default().to_string(),
+        pid: node["pid"].as_i64().unwrap_or(0) as i32,
+        workspace: ClientWorkspace {
+            name: workspace.to_string(),
+        },
+    })
+}
+
+/// Collect every window in a `GET_TREE` reply, remembering which workspace it is on
+fn collect_windows(node: &Value, workspace: &str, clients: &mut Vec<Client>) {
+    let workspace = if node["type"] == "workspace" {
+        node["name"].as_str().unwrap_or(workspace)
+    } else {
+        workspace
+    };
+
+    let children: Vec<&Value> = node["nodes"]
+        .as_array()
+        .into_iter()
+        .chain(node["floating_nodes"].as_array())
+        .flatten()
+        .collect();
+
+    if children.is_empty() {
+        if let Some(client) = container_to_client(node, workspace) {
+            clients.push(client);
+        }
+        return;
+    }
+
+    for child in children {
+        collect_windows(child, workspace, clients);
+    }
+}
+
+/// Workspace of the window with the given address, from a fresh `GET_TREE`
+fn window_workspace(backend: &SwayBackend, address: &str) -> Option<String> {
+    backend
+        .list_windows()
+        .ok()?
+        .into_iter()
+        .find(|client| client.address == address)
+        .map(|client| client.workspace.name)
+}
+
+/// Group a `GET_WORKSPACES` reply by output
+fn workspaces_to_monitors(workspaces: &Value) -> Vec<MonitorInfo> {
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+
+    for workspace in workspaces.as_array().into_iter().flatten() {
+        let (Some(name), Some(output)) = (workspace["name"].as_str(), workspace["output"].as_str())
+        else {
+            continue;
+        };
+        let index = match monitors.iter().position(|monitor| monitor.name == output) {
+            Some(index) => index,
+            None => {
+                monitors.push(MonitorInfo {
+                    name: output.to_string(),
+                    ..Default::default()
+                });
+                monitors.len() - 1
+            }
+        };
+
+        let monitor = &mut monitors[index];
+        monitor.workspaces.push(name.to_string());
+        if workspace["visible"].as_bool() == Some(true) {
+            monitor.active_workspace = name.to_string();
+        }
+        if workspace["focused"].as_bool() == Some(true) {
+            monitor.focused = true;
+        }
+    }
+    monitors
+}
+
+fn parse_workspace_event(body: &[u8]) -> HyprIpcEvent {
+    let event: Value = match serde_json::from_slice(body) {
+        Ok(event) => event,
+        Err(_) => return HyprIpcEvent::Other,
+    };
+    let current = &event["current"];
+    let (Some(workspace), Some(monitor)) = (current["name"].as_str(), current["output"].as_str())
+    else {
+        return HyprIpcEvent::Other;
+    };
+
+    match event["change"].as_str() {
+        Some("focus") => HyprIpcEvent::FocusedMonitor {
+            monitor: monitor.to_string(),
+            workspace: workspace.to_string(),
+        },
+        Some("move") => HyprIpcEvent::WorkspaceMoved {
+            workspace: workspace.to_string(),
+            monitor: monitor.to_string(),
+        },
+        _ => HyprIpcEvent::Other,
+    }
+}
+
+/// Fill in the workspace window events leave out, looking it up with `workspace_of`
+///
+/// `None` for a move whose window is already gone. A new window that can't be
+/// found keeps the empty workspace, for the dock to guess.
+fn with_workspace(
+    event: HyprIpcEvent,
+    workspace_of: impl Fn(&str) -> Option<String>,
+) -> Option<HyprIpcEvent> {
+    Some(match event {
+        HyprIpcEvent::OpenWindow { address, class, title, .. } => HyprIpcEvent::OpenWindow {
+            workspace: workspace_of(&address).unwrap_or_default(),
+            address,
+            class,
+            title,
+        },
+        HyprIpcEvent::MoveWindow { address, .. } => HyprIpcEvent::MoveWindow {
+            workspace: workspace_of(&address)?,
+            address,
+            workspace_id: 0,
+        },
+        event => event,
+    })
+}
+
+fn parse_window_event(body: &[u8]) -> HyprIpcEvent {
+    let event: Value = match serde_json::from_slice(body) {
+        Ok(event) => event,
+        Err(_) => return HyprIpcEvent::Other,
+    };
+    let container = &event["container"];
+    let address = match container["id"].as_i64() {
+        Some(id) => id.to_string(),
+        None => return HyprIpcEvent::Other,
+    };
+
+    match event["change"].as_str() {
+        // Nem `new` nem `move` dizem o workspace, ele é buscado depois
+        Some("new") => match container_class(container) {
+            Some(class) => HyprIpcEvent::OpenWindow {
+                address,
+                workspace: String::new(),
+                class,
+                title: container["name"].as_str().unwrap_or_default().to_string(),
+            },
+            None => HyprIpcEvent::Other,
+        },
+        Some("close") => HyprIpcEvent::CloseWindow { address },
+        Some("move") => HyprIpcEvent::MoveWindow {
+            address,
+            workspace_id: 0,
+            workspace: String::new(),
+        },
+        Some("focus") => HyprIpcEvent::ActiveWindow {
+            address: Some(address),
+        },
+        Some("title") => HyprIpcEvent::WindowTitle {
+            address,
+            title: container["name"].as_str().unwrap_or_default().to_string(),
+        },
+        Some("urgent") if container["urgent"].as_bool() == Some(true) => {
+            HyprIpcEvent::Urgent { address }
+        }
+        _ => HyprIpcEvent::Other,
+    }
+}
+
+impl WindowManagerBackend for SwayBackend {
+    fn name(&self) -> &'static str {
+        "Sway"
+    }
+
+    fn list_windows(&self) -> Result<Vec<Client>, BackendError> {
+        let tree = self.request(GET_TREE, "")?;
+        let mut clients = Vec::new();
+        collect_windows(&tree, "", &mut clients);
+        Ok(clients)
+    }
+
+    fn subscribe(&self, sender: Sender<HyprIpcEvent>) -> Result<(), BackendError> {
+        let mut stream = Self::connect_socket()?;
+        write_message(&mut stream, SUBSCRIBE, r#"["window", "workspace"]"#)?;
+
+        let (_, reply) = read_message(&mut stream)?;
+        let reply: Value =
+            serde_json::from_slice(&reply).map_err(|e| BackendError::Protocol(e.to_string()))?;
+        if !reply["success"].as_bool().unwrap_or(false) {
+            return Err(BackendError::Protocol("Subscription was rejected".to_string()));
+        }
+
+        std::thread::spawn(move || loop {
+            let event = match read_message(&mut stream) {
+                Ok((WINDOW_EVENT, body)) => {
+                    let event = parse_window_event(&body);
+                    match with_workspace(event, |address| window_workspace(&SwayBackend, address)) {
+                        Some(event) => event,
+                        None => continue,
+                    }
+                }
+                Ok((WORKSPACE_EVENT, body)) => parse_workspace_event(&body),
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Sway event stream closed: {}", e);
+                    break;
+                }
+            };
+            if sender.send(event).is_err() {
+                break;
+            }
+        });
+
+        Ok(())
+    }
+
+    fn focus(&self, address: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("[con_id={}] focus", address))
+    }
+
+    fn close(&self, address: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("[con_id={}] kill", address))
+    }
+
+    fn launch(&self, command: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("exec {}", command))
+    }
+
+    fn switch_workspace(&self, workspace: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("workspace \"{}\"", workspace.replace('"', "\\\"")))
+    }
+
+    fn move_to_workspace(&self, address: &str, workspace: &str) -> Result<(), BackendError> {
+        self.run_command(&format!(
+            "[con_id={}] move container to workspace \"{}\"",
+            address,
+            workspace.replace('"', "\\\"")
+        ))
+    }
+
+    fn toggle_floating(&self, address: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("[con_id={}] floating toggle", address))
+    }
+
+    fn toggle_fullscreen(&self, address: &str) -> Result<(), BackendError> {
+        self.run_command(&format!("[con_id={}] fullscreen toggle", address))
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, BackendError> {
+        Ok(workspaces_to_monitors(&self.request(GET_WORKSPACES, "")?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_windows_tracks_workspace() {
+        let tree = json!({
+            "type": "root",
+            "nodes": [{
+                "type": "output",
+                "nodes": [{
+                    "type": "workspace",
+                    "name": "2",
+                    "nodes": [
+                        { "id": 10, "type": "con", "app_id": "foot", "name": "~", "pid": 42, "nodes": [] }
+                    ],
+                    "floating_nodes": [
+                        { "id": 11, "type": "floating_con", "app_id": null,
+                          "window_properties": { "class": "Steam" }, "name": "Steam", "nodes": [] }
+                    ]
+                }]
+            }]
+        });
+
+        let mut clients = Vec::new();
+        collect_windows(&tree, "", &mut clients);
+
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients[0].address, "10");
+        assert_eq!(clients[0].class, "foot");
+        assert_eq!(clients[0].pid, 42);
+        assert_eq!(clients[0].workspace.name, "2");
+        assert_eq!(clients[1].class, "Steam");
+    }
+
+    #[test]
+    fn test_collect_windows_skips_empty_workspaces() {
+        let tree = json!({
+            "type": "root",
+            "nodes": [{ "type": "workspace", "name": "1", "nodes": [] }]
+        });
+
+        let mut clients = Vec::new();
+        collect_windows(&tree, "", &mut clients);
+        assert!(clients.is_empty());
+    }
+
+    #[test]
+    fn test_parse_window_event() {
+        let new = br#"{"change":"new","container":{"id":5,"app_id":"kitty","name":"kitty"}}"#;
+        assert!(matches!(
+            parse_window_event(new),
+            HyprIpcEvent::OpenWindow { ref address, ref class, .. } if address == "5" && class == "kitty"
+        ));
+
+        let close = br#"{"change":"close","container":{"id":5,"app_id":"kitty"}}"#;
+        assert!(matches!(
+            parse_window_event(close),
+            HyprIpcEvent::CloseWindow { ref address } if address == "5"
+        ));
+
+        let focus = br#"{"change":"focus","container":{"id":5,"app_id":"kitty"}}"#;
+        assert_eq!(
+            parse_window_event(focus),
+            HyprIpcEvent::ActiveWindow {
+                address: Some("5".to_string())
+            }
+        );
+
+        let mark = br#"{"change":"mark","container":{"id":5,"app_id":"kitty"}}"#;
+        assert_eq!(parse_window_event(mark), HyprIpcEvent::Other);
+    }
+
+    #[test]
+    fn test_window_events_get_their_workspace() {
+        let workspace_of = |address: &str| (address == "5").then(|| "3".to_string());
+        let open = |address: &str| HyprIpcEvent::OpenWindow {
+            address: address.to_string(),
+            workspace: String::new(),
+            class: "kitty".to_string(),
+            title: "kitty".to_string(),
+        };
+        let moved = |address: &str| HyprIpcEvent::MoveWindow {
+            address: address.to_string(),
+            workspace_id: 0,
+            workspace: String::new(),
+        };
+
+        assert!(matches!(
+            with_workspace(open("5"), workspace_of),
+            Some(HyprIpcEvent::OpenWindow { ref workspace, .. }) if workspace == "3"
+        ));
+        assert!(matches!(
+            with_workspace(moved("5"), workspace_of),
+            Some(HyprIpcEvent::MoveWindow { ref workspace, .. }) if workspace == "3"
+        ));
+        // Janela que já sumiu da árvore
+        assert!(matches!(
+            with_workspace(open("6"), workspace_of),
+            Some(HyprIpcEvent::OpenWindow { ref workspace, .. }) if workspace.is_empty()
+        ));
+        assert_eq!(with_workspace(moved("6"), workspace_of), None);
+        assert_eq!(
+            with_workspace(HyprIpcEvent::CloseWindow { address: "5".to_string() }, workspace_of),
+            Some(HyprIpcEvent::CloseWindow { address: "5".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_workspaces_to_monitors() {
+        let workspaces = json!([
+            { "name": "1", "output": "DP-1", "visible": false, "focused": false },
+            { "name": "2", "output": "HDMI-A-1", "visible": true, "focused": false },
+            { "name": "3", "output": "DP-1", "visible": true, "focused": true }
+        ]);
+
+        let monitors = workspaces_to_monitors(&workspaces);
+
+        assert_eq!(
+            monitors,
+            vec![
+                MonitorInfo {
+                    name: "DP-1".to_string(),
+                    focused: true,
+                    active_workspace: "3".to_string(),
+                    workspaces: vec!["1".to_string(), "3".to_string()],
+                },
+                MonitorInfo {
+                    name: "HDMI-A-1".to_string(),
+                    focused: false,
+                    active_workspace: "2".to_string(),
+                    workspaces: vec!["2".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_workspace_event() {
+        let focus = br#"{"change":"focus","current":{"name":"3","output":"DP-1"}}"#;
+        assert_eq!(
+            parse_workspace_event(focus),
+            HyprIpcEvent::FocusedMonitor {
+                monitor: "DP-1".to_string(),
+                workspace: "3".to_string()
+            }
+        );
+
+        let moved = br#"{"change":"move","current":{"name":"3","output":"HDMI-A-1"}}"#;
+        assert_eq!(
+            parse_workspace_event(moved),
+            HyprIpcEvent::WorkspaceMoved {
+                workspace: "3".to_string(),
+                monitor: "HDMI-A-1".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use bevy::log::warn;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+use super::{BackendError, WindowManagerBackend};
+use crate::types::{Client, HyprIpcEvent};
+
+/// A toplevel as announced by the compositor
+struct Toplevel {
+    handle: ZwlrForeignToplevelHandleV1,
+    title: String,
+    app_id: String,
+    /// Set after the first `done` event, once title and app_id are known
+    announced: bool,
+    fullscreen: bool,
+}
+
+#[derive(Default)]
+struct ToplevelList {
+    toplevels: HashMap<String, Toplevel>,
+    sender: Option<Sender<HyprIpcEvent>>,
+}
+
+impl ToplevelList {
+    fn send(&self, event: HyprIpcEvent) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+/// Wayland event queue state, shared with the backend through `ToplevelList`
+struct WlrState {
+    list: Arc<Mutex<ToplevelList>>,
+}
+
+/// Backend for any compositor implementing `wlr-foreign-toplevel-management`
+///
+/// Works on river, Wayfire, labwc and others. The protocol has no notion of
+/// workspaces or pids, so those fields are left empty.
+pub struct WlrBackend {
+    connection: Connection,
+    seat: WlSeat,
+    list: Arc<Mutex<ToplevelList>>,
+}
+
+impl WlrBackend {
+    /// Connect to the compositor and start dispatching toplevel events
+    pub fn connect() -> Result<Self, BackendError> {
+        let connection = Connection::connect_to_env()
+            .map_err(|e| BackendError::Unavailable(e.to_string()))?;
+        let (globals, mut queue) = registry_queue_init::<WlrState>(&connection)
+            .map_err(|e| BackendError::Unavailable(e.to_string()))?;
+        let qh = queue.handle();
+
+        let manager: ZwlrForeignToplevelManagerV1 = globals
+            .bind(&qh, 1..=3, ())
+            .map_err(|e| BackendError::Unavailable(e.to_string()))?;
+        let seat: WlSeat = globals
+            .bind(&qh, 1..=7, ())
+            .map_err(|e| BackendError::Unavailable(e.to_string()))?;
+
+        let list = Arc::new(Mutex::new(ToplevelList::default()));
+        let mut state = WlrState { list: list.clone() };
+
+        // Primeiro roundtrip anuncia os toplevels, o segundo entrega title/app_id
+        for _ in 0..2 {
+            queue
+                .roundtrip(&mut state)
+                .map_err(|e| BackendError::Protocol(e.to_string()))?;
+        }
+
+        std::thread::spawn(move || {
+            let _manager = manager;
+            loop {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    warn!("Wayland connection closed: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            connection,
+            seat,
+            list,
+        })
+    }
+
+    fn with_handle(
+        &self,
+        address: &str,
+        action: impl FnOnce(&Toplevel) -> Result<(), BackendError>,
+    ) -> Result<(), BackendError> {
+        {
+            let list = self.list.lock().unwrap();
+            let toplevel = list
+                .toplevels
+                .get(address)
+                .ok_or_else(|| BackendError::UnknownWindow(address.to_string()))?;
+            action(toplevel)?;
+        }
+        self.connection
+            .flush()
+            .map_err(|e| BackendError::Protocol(e.to_string()))
+    }
+}
+
+impl WindowManagerBackend for WlrBackend {
+    fn name(&self) -> &'static str {
+        "wlr-foreign-toplevel"
+    }
+
+    fn list_windows(&self) -> Result<Vec<Client>, BackendError> {
+        let list = self.list.lock().unwrap();
+        Ok(list
+            .toplevels
+            .iter()
+            .filter(|(_, toplevel)| toplevel.announced && !toplevel.app_id.is_empty())
+            .map(|(address, toplevel)| Client {
+                class: toplevel.app_id.clone(),
+                address: address.clone(),
+                title: toplevel.title.clone(),
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    fn subscribe(&self, sender: Sender<HyprIpcEvent>) -> Result<(), BackendError> {
+        self.list.lock().unwrap().sender = Some(sender);
+        Ok(())
+    }
+
+    fn focus(&self, address: &str) -> Result<(), BackendError> {
+        self.with_handle(address, |toplevel| {
+            toplevel.handle.activate(&self.seat);
+            Ok(())
+        })
+    }
+
+    fn close(&self, address: &str) -> Result<(), BackendError> {
+        self.with_handle(address, |toplevel| {
+            toplevel.handle.close();
+            Ok(())
+        })
+    }
+
+    fn toggle_fullscreen(&self, address: &str) -> Result<(), BackendError> {
+        self.with_handle(address, |toplevel| {
+            // set_fullscreen só existe a partir da versão 2 do protocolo
+            if toplevel.handle.version() < 2 {
+                return Err(BackendError::Unavailable(
+                    "compositor doesn't support fullscreen requests".to_string(),
+                ));
+            }
+            if toplevel.fullscreen {
+                toplevel.handle.unset_fullscreen();
+            } else {
+                toplevel.handle.set_fullscreen(None);
+            }
+            Ok(())
+        })
+    }
+
+    fn launch(&self, command: &str) -> Result<(), BackendError> {
+        Command::new("sh").arg("-c").arg(command).spawn()?;
+        Ok(())
+    }
+}
+
+fn toplevel_address(handle: &ZwlrForeignToplevelHandleV1) -> String {
+    handle.id().protocol_id().to_string()
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for WlrState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSeat, ()> for WlrState {
+    fn event(
+        _: &mut Self,
+        _: &WlSeat,
+        _: <WlSeat as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            let address = toplevel_address(&toplevel);
+            state.list.lock().unwrap().toplevels.insert(
+                address,
+                Toplevel {
+                    handle: toplevel,
+                    title: String::new(),
+                    app_id: String::new(),
+                    announced: false,
+                    fullscreen: false,
+                },
+            );
+        }
+    }
+
+    event_created_child!(WlrState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ())
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let address = toplevel_address(handle);
+        let mut list = state.list.lock().unwrap();
+
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                if let Some(toplevel) = list.toplevels.get_mut(&address) {
+                    toplevel.title = title;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                if let Some(toplevel) = list.toplevels.get_mut(&address) {
+                    toplevel.app_id = app_id;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state } => {
+                if let Some(toplevel) = list.toplevels.get_mut(&address) {
+                    toplevel.fullscreen = state
+                        .chunks_exact(4)
+                        .map(|value| u32::from_ne_bytes([value[0], value[1], value[2], value[3]]))
+                        .any(|value| {
+                            value == zwlr_foreign_toplevel_handle_v1::State::Fullscreen as u32
+                        });
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                let opened = match list.toplevels.get_mut(&address) {
+                    Some(toplevel) if !toplevel.announced && !toplevel.app_id.is_empty() => {
+                        toplevel.announced = true;
+                        Some(HyprIpcEvent::OpenWindow {
+                            address: address.clone(),
+                            workspace: String::new(),
+                            class: toplevel.app_id.clone(),
+                            title: toplevel.title.clone(),
+                        })
+                    }
+                    _ => None,
+                };
+                if let Some(event) = opened {
+                    list.send(event);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                if let Some(toplevel) = list.toplevels.remove(&address) {
+                    if toplevel.announced {
+                        list.send(HyprIpcEvent::CloseWindow { address });
+                    }
+                    toplevel.handle.destroy();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::log::Level;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anny_dock::backend::BackendKind;
+use anny_dock::config::{default_config_toml, get_config_path, parse_config, unknown_keys};
+use anny_dock::control::{self, ControlRequest, DockItem};
+use anny_dock::utils::default_favorites_path;
+
+/// How to run the dock, from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub config_path: Option<PathBuf>,
+    pub favorites_path: Option<PathBuf>,
+    /// Overrides `backend` from the config
+    pub backend: Option<BackendKind>,
+    pub log_level: Level,
+    pub monitor: Option<String>,
+    /// Control socket to listen on instead of the default one
+    pub socket: Option<PathBuf>,
+}
+
+impl Options {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        Self {
+            config_path: matches
+                .get_one::<PathBuf>("config")
+                .cloned()
+                .or_else(get_config_path),
+            favorites_path: matches
+                .get_one::<PathBuf>("favorites")
+                .cloned()
+                .or_else(default_favorites_path),
+            backend: matches.get_one::<BackendKind>("backend").copied(),
+            log_level: matches
+                .get_one::<String>("log-level")
+                .and_then(|level| Level::from_str(level).ok())
+                .unwrap_or(Level::INFO),
+            monitor: matches.get_one::<String>("monitor").cloned(),
+            socket: matches.get_one::<PathBuf>("socket").cloned(),
+        }
+    }
+
+    /// Control socket to listen on: `--socket`, or the default one
+    pub fn socket_path(&self) -> PathBuf {
+        self.socket.clone().unwrap_or_else(control::socket_path)
+    }
+}
+
+pub fn command() -> Command<'static> {
+    Command::new("anny-dock")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .help("Config file to use [default: ~/.config/anny-dock/config.toml]"),
+        )
+        .arg(
+            Arg::new("favorites")
+                .long("favorites")
+                .takes_value(true)
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .help("Favorites file to use [default: ~/.local/share/anny-dock/favorites.json]"),
+        )
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .takes_value(true)
+                .value_name("BACKEND")
+                .value_parser(value_parser!(BackendKind))
+                .help("Window manager backend, overriding the config"),
+        )
+        .arg(
+            Arg::new("log-level")
+                .long("log-level")
+                .takes_value(true)
+                .value_name("LEVEL")
+                .value_parser(["error", "warn", "info", "debug", "trace"])
+                .default_value("info")
+                .help("Most verbose level of messages to log"),
+        )
+        .arg(
+            Arg::new("monitor")
+                .long("monitor")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Open the dock on this monitor, e.g. DP-1"),
+        )
+        .arg(
+            Arg::new("socket")
+                .long("socket")
+                .takes_value(true)
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .global(true)
+                .help("Listen for commands on this socket, or send them to it [default: $XDG_RUNTIME_DIR/anny-dock.sock]"),
+        )
+        .arg(
+            Arg::new("print-default-config")
+                .long("print-default-config")
+                .help("Print the default config.toml, with every key documented, and exit"),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Work with config files")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("check")
+                        .about("Validate a config file, exiting non-zero if it has errors")
+                        .arg(
+                            Arg::new("path")
+                                .value_parser(value_parser!(PathBuf))
+                                .help("File to check [default: ~/.config/anny-dock/config.toml]"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("pin")
+                .about("Pin an application to the running dock")
+                .arg(Arg::new("class").required(true).help("Window class of the application")),
+        )
+        .subcommand(
+            Command::new("unpin")
+                .about("Unpin an application from the running dock")
+                .arg(Arg::new("class").required(true).help("Window class of the application")),
+        )
+        .subcommand(Command::new("list").about("List the icons of the running dock"))
+        .subcommand(Command::new("reload").about("Make the running dock reload its config"))
+        .subcommand(Command::new("quit").about("Close the running dock"))
+}
+
+/// Handle flags and subcommands that don't start the dock
+///
+/// Returns the exit code when the process should end instead of running the dock.
+pub fn run(matches: &ArgMatches) -> Option<i32> {
+    if matches.contains_id("print-default-config") {
+        print!("{}", default_config_toml());
+        return Some(0);
+    }
+
+    let class = |matches: &ArgMatches| matches.get_one::<String>("class").cloned().unwrap_or_default();
+    let request = match matches.subcommand() {
+        Some(("config", config)) => {
+            return match config.subcommand() {
+                Some(("check", check)) => Some(check_config(check.get_one::<PathBuf>("path").cloned())),
+                _ => Some(2),
+            }
+        }
+        Some(("pin", pin)) => ControlRequest::Pin { class: class(pin) },
+        Some(("unpin", unpin)) => ControlRequest::Unpin { class: class(unpin) },
+        Some(("list", _)) => ControlRequest::List,
+        Some(("reload", _)) => ControlRequest::ReloadConfig,
+        Some(("quit", _)) => ControlRequest::Quit,
+        _ => return None,
+    };
+
+    Some(send_request(&Options::from_matches(matches).socket_path(), &request))
+}
+
+fn send_request(socket: &Path, request: &ControlRequest) -> i32 {
+    let reply = match control::send(socket, request) {
+        Ok(reply) => reply,
+        Err(e) => {
+            eprintln!("anny-dock: {}", e);
+            return 1;
+        }
+    };
+
+    if *request == ControlRequest::List {
+        let items: Vec<DockItem> = reply
+            .data
+            .and_then(|data| serde_json::from_value(data).ok())
+            .unwrap_or_default();
+        for item in items {
+            let pinned = if item.pinned { "pinned" } else { "-" };
+            println!("{}\t{}\t{}", item.class, pinned, item.windows.len());
+        }
+    }
+    0
+}
+
+fn check_config(path: Option<PathBuf>) -> i32 {
+    let Some(path) = path.or_else(get_config_path) else {
+        eprintln!("No config directory found");
+        return 1;
+    };
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            return 1;
+        }
+    };
+
+    for key in unknown_keys(&contents) {
+        eprintln!("{}: warning: unknown key `{}`", path.display(), key);
+    }
+
+    match parse_config(&contents) {
+        Ok(_) => {
+            println!("{}: OK", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dock_options() {
+        let matches = command()
+            .try_get_matches_from([
+                "anny-dock",
+                "--config",
+                "/tmp/dock.toml",
+                "--backend",
+                "sway",
+                "--log-level",
+                "debug",
+                "--monitor",
+                "DP-1",
+            ])
+            .unwrap();
+
+        let options = Options::from_matches(&matches);
+
+        assert_eq!(options.config_path, Some(PathBuf::from("/tmp/dock.toml")));
+        assert_eq!(options.backend, Some(BackendKind::Sway));
+        assert_eq!(options.log_level, Level::DEBUG);
+        assert_eq!(options.monitor.as_deref(), Some("DP-1"));
+        assert_eq!(options.socket_path(), control::socket_path());
+
+        let matches = command()
+            .try_get_matches_from(["anny-dock", "--socket", "/tmp/dock.sock"])
+            .unwrap();
+        assert_eq!(
+            Options::from_matches(&matches).socket_path(),
+            PathBuf::from("/tmp/dock.sock")
+        );
+    }
+
+    #[test]
+    fn test_commands_go_to_the_given_socket() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket = dir.path().join("dock.sock");
+        let requests = control::listen(&socket).unwrap();
+        let dock = std::thread::spawn(move || {
+            let (request, reply) = requests.0.lock().unwrap().recv().unwrap();
+            reply.send(control::ControlReply::ok()).unwrap();
+            request
+        });
+
+        let matches = command()
+            .try_get_matches_from(["anny-dock", "pin", "kitty", "--socket", socket.to_str().unwrap()])
+            .unwrap();
+
+        assert_eq!(run(&matches), Some(0));
+        assert_eq!(
+            dock.join().unwrap(),
+            ControlRequest::Pin {
+                class: "kitty".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_backend() {
+        assert!(command()
+            .try_get_matches_from(["anny-dock", "--backend", "kwin"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_pin_requires_class() {
+        assert!(command().try_get_matches_from(["anny-dock", "pin"]).is_err());
+    }
+}
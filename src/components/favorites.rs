@@ -3,17 +3,106 @@ use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use bevy::sprite::SpriteBundle;
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Component)]
 pub struct FavoritePin;
 
-#[derive(Resource, Deserialize, Serialize, Clone, Default)]
-pub struct Favorites(pub Vec<String>);
+/// A pinned application and its optional per-pin overrides
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Pin {
+    pub class: String,
+    /// Command run instead of the desktop entry's `Exec`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Image used instead of the resolved icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<PathBuf>,
+    /// Text shown instead of the class when titles are on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Slot among the pinned icons, rewritten on every save
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    /// `command` is a shell command to run inside the configured terminal
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub terminal: bool,
+}
+
+impl Pin {
+    pub fn new(class: impl Into<String>) -> Self {
+        Self {
+            class: class.into(),
+            command: None,
+            icon: None,
+            label: None,
+            position: None,
+            terminal: false,
+        }
+    }
+}
+
+/// Pinned applications in dock order
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct Favorites(pub Vec<Pin>);
+
+impl Favorites {
+    pub fn contains(&self, class: &str) -> bool {
+        self.get(class).is_some()
+    }
+
+    pub fn get(&self, class: &str) -> Option<&Pin> {
+        self.0.iter().find(|pin| pin.class == class)
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|pin| pin.class.as_str())
+    }
+
+    /// Pin `class` at the end, keeping any existing pin untouched
+    pub fn add(&mut self, class: &str) {
+        if !self.contains(class) {
+            self.0.push(Pin::new(class));
+        }
+    }
+
+    pub fn remove(&mut self, class: &str) {
+        self.0.retain(|pin| pin.class != class);
+    }
+
+    /// Sort pins to follow `classes`, keeping pins that aren't listed after the rest
+    pub fn reorder(&mut self, classes: &[String]) {
+        self.0.sort_by_key(|pin| {
+            classes
+                .iter()
+                .position(|class| *class == pin.class)
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Text shown under the icon of `class`
+    pub fn label<'a>(&'a self, class: &'a str) -> &'a str {
+        self.get(class)
+            .and_then(|pin| pin.label.as_deref())
+            .unwrap_or(class)
+    }
+
+    /// Launch command configured for `class`, if any
+    pub fn command(&self, class: &str) -> Option<&str> {
+        self.get(class).and_then(|pin| pin.command.as_deref())
+    }
+
+    /// Whether the pinned command of `class` runs inside a terminal
+    pub fn runs_in_terminal(&self, class: &str) -> bool {
+        self.get(class).is_some_and(|pin| pin.terminal)
+    }
+}
 
 #[derive(Component, Debug)]
 pub struct Favorite;
 
-use crate::{config::Config, ClientAddress, IconText};
+use crate::types::{ClientAddress, IconText};
+use crate::{config::Config, utils::load_icon};
 
 pub(crate) fn add_client_address(commands: &mut Commands, entity: Entity, address: String) {
     commands.entity(entity).insert(ClientAddress(address));
@@ -23,12 +112,29 @@ pub(crate) fn add_favorite(
     commands: &mut Commands,
     entity: Entity,
     images: &mut Assets<Image>,
-    config: &Res<Config>,
+    config: &Config,
 ) {
     commands.entity(entity).insert(Favorite);
     set_favorite_pin(commands, images, entity, config);
 }
 
+/// Swap the icon texture for the pin's custom image, if it has one
+pub(crate) fn apply_pin_icon(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    entity: Entity,
+    pin: &Pin,
+) {
+    if let Some(path) = &pin.icon {
+        match load_icon(path) {
+            Some(image) => {
+                commands.entity(entity).insert(images.add(image));
+            }
+            None => warn!("Failed to load custom icon for {}: {}", pin.class, path.display()),
+        }
+    }
+}
+
 pub(crate) fn add_icon_text(
     commands: &mut Commands,
     entity: Entity,
@@ -36,7 +142,7 @@ pub(crate) fn add_icon_text(
     transform: Transform,
     scale: f32,
     _asset_server: &AssetServer,
-    config: &Res<Config>,
+    config: &Config,
 ) {
     const TEXT_OFFSET: f32 = 8.0;
 
@@ -118,7 +224,7 @@ pub(crate) fn set_favorite_pin(
     commands: &mut Commands,
     images: &mut Assets<Image>,
     parent_entity: Entity,
-    config: &Res<Config>,
+    config: &Config,
 ) {
     const PIN_ICON_SVG: &[u8] = include_bytes!("../../assets/icons/pin_stroke_rounded.svg");
     if let Some(image) = load_svg_pin_from_bytes(PIN_ICON_SVG) {
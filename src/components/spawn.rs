@@ -1,7 +1,10 @@
 use bevy::{
-    asset::{AssetServer, Assets},
+    asset::{AssetServer, Assets, Handle},
     core::Name,
-    ecs::{entity::Entity, system::Commands},
+    ecs::{
+        entity::Entity,
+        system::{Commands, Res, ResMut, SystemParam},
+    },
     log::error,
     render::{
         color::Color,
@@ -12,11 +15,10 @@ use bevy::{
     transform::components::Transform,
     utils::default,
 };
-use std::path::Path;
-
 use crate::{
+    icon::{CachedIcon, IconCache, IconContext, IconResolver},
     utils::{get_icon_path, hover::HoverState, load_icon},
-    ClientClass, ClientIcon, HoverTarget,
+    types::{ClientClass, ClientIcon, HoverTarget},
 };
 
 static FALLBACK_ICON_SVG: &[u8] = include_bytes!("../../assets/icons/dock_icon.svg");
@@ -83,78 +85,84 @@ fn load_svg_from_bytes(svg_bytes: &[u8], target_size: u32) -> Option<Image> {
     Some(image)
 }
 
-pub(crate) fn spawn_icon_entity(
-    commands: &mut Commands,
-    images: &mut Assets<Image>,
-    _asset_server: &AssetServer,
-    class: &str,
-    transform: Transform,
-    scale: f32,
-    alpha: f32,
-    index: usize,
-) -> Entity {
-    let icon_path = get_icon_path(class);
-
-    let handle = if icon_path == "memory://fallback_icon" {
-        if let Some(img) = load_svg_from_bytes(FALLBACK_ICON_SVG, 56) {
-            images.add(img)
-        } else {
-            error!("Failed to render fallback SVG icon!");
-            let img = Image::new_fill(
-                Extent3d {
-                    width: 56,
-                    height: 56,
-                    depth_or_array_layers: 1,
-                },
-                TextureDimension::D2,
-                &[255, 0, 0, 255],
-                TextureFormat::Rgba8Unorm,
-            );
-            images.add(img)
-        }
+fn fallback_icon_handle(images: &mut Assets<Image>) -> Handle<Image> {
+    if let Some(img) = load_svg_from_bytes(FALLBACK_ICON_SVG, 56) {
+        images.add(img)
     } else {
-        let path = Path::new(&icon_path);
-        if let Some(img) = load_icon(path) {
-            images.add(img)
+        error!("Failed to render fallback SVG icon!");
+        let img = Image::new_fill(
+            Extent3d {
+                width: 56,
+                height: 56,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 0, 0, 255],
+            TextureFormat::Rgba8Unorm,
+        );
+        images.add(img)
+    }
+}
+
+/// Everything needed to spawn an app icon
+#[derive(SystemParam)]
+pub struct IconSpawner<'w> {
+    pub(crate) images: ResMut<'w, Assets<Image>>,
+    pub(crate) asset_server: Res<'w, AssetServer>,
+    icon_resolver: Res<'w, IconResolver>,
+    icon_cache: ResMut<'w, IconCache>,
+}
+
+impl IconSpawner<'_> {
+    /// Spawn the sprite of an app icon at `transform`, loading its image unless it's cached
+    pub(crate) fn spawn(
+        &mut self,
+        commands: &mut Commands,
+        context: &IconContext,
+        transform: Transform,
+        alpha: f32,
+        index: usize,
+    ) -> Entity {
+        let class = context.class.as_str();
+
+        let handle = if let Some(cached) = self.icon_cache.get(class) {
+            cached.handle.clone()
         } else {
-            error!("Failed to load icon for {}, using fallback", class);
-            if let Some(img) = load_svg_from_bytes(FALLBACK_ICON_SVG, 56) {
-                images.add(img)
-            } else {
-                let img = Image::new_fill(
-                    Extent3d {
-                        width: 56,
-                        height: 56,
-                        depth_or_array_layers: 1,
-                    },
-                    TextureDimension::D2,
-                    &[255, 0, 0, 255],
-                    TextureFormat::Rgba8Unorm,
-                );
-                images.add(img)
+            match get_icon_path(&self.icon_resolver, &mut self.icon_cache, context) {
+                Some(path) => {
+                    if let Some(img) = load_icon(&path) {
+                        let handle = self.images.add(img);
+                        self.icon_cache.store(class.to_string(), CachedIcon::new(handle.clone(), path));
+                        handle
+                    } else {
+                        error!("Failed to load icon for {}, using fallback", class);
+                        fallback_icon_handle(&mut self.images)
+                    }
+                }
+                None => fallback_icon_handle(&mut self.images),
             }
-        }
-    };
+        };
 
-    let color = Color::rgba(1.0, 1.0, 1.0, alpha);
-    commands
-        .spawn(SpriteBundle {
-            texture: handle,
-            transform,
-            sprite: Sprite { color, ..default() },
-            ..default()
-        })
-        .insert(ClientIcon)
-        .insert(ClientClass(class.to_string()))
-        .insert(HoverTarget {
-            original_position: transform.translation.truncate(),
-            original_z: transform.translation.z,
-            original_scale: scale,
-            index,
-            is_hovered: false,
-            hover_exit_timer: None,
-        })
-        .insert(HoverState::default())
-        .insert(Name::new(class.to_string()))
-        .id()
+        let color = Color::rgba(1.0, 1.0, 1.0, alpha);
+        commands
+            .spawn(SpriteBundle {
+                texture: handle,
+                transform,
+                sprite: Sprite { color, ..default() },
+                ..default()
+            })
+            .insert(ClientIcon)
+            .insert(ClientClass(class.to_string()))
+            .insert(HoverTarget {
+                original_position: transform.translation.truncate(),
+                original_z: transform.translation.z,
+                original_scale: transform.scale.x,
+                index,
+                is_hovered: false,
+                hover_exit_timer: None,
+            })
+            .insert(HoverState::default())
+            .insert(Name::new(class.to_string()))
+            .id()
+    }
 }
@@ -1,10 +1,17 @@
+use bevy::log::{error, info, warn};
 use bevy::prelude::Resource;
+use inotify::{Inotify, WatchMask};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
 
-#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+use crate::backend::BackendKind;
+
+/// Dock settings from `config.toml`; any missing field takes its value from `Config::default()`
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct Config {
     pub icon_size: f32,
     pub margin_x: f32,
@@ -16,6 +23,46 @@ pub struct Config {
     pub scroll_speed: f32,
     pub visible_items: usize,
     pub tilt_y: f32,
+    pub layout: LayoutKind,
+    pub backend: BackendKind,
+    pub indicator_style: IndicatorStyle,
+    pub urgent: UrgentConfig,
+    pub magnification: MagnificationConfig,
+    pub visibility: VisibilityConfig,
+    pub layer: LayerConfig,
+    pub monitors: MonitorConfig,
+    pub workspaces: WorkspaceConfig,
+    pub terminal: TerminalConfig,
+}
+
+/// How icons are arranged on screen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutKind {
+    /// Tilted toward `tilt_y`, icons shrinking into the distance
+    #[default]
+    Diagonal,
+    /// A classic row along the bottom edge
+    Horizontal,
+    /// A column along the left edge
+    VerticalLeft,
+    /// A column along the right edge
+    VerticalRight,
+    /// A fan along an arc rising from the bottom edge
+    Arc,
+}
+
+/// How running windows and the focused window are marked under each icon
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IndicatorStyle {
+    /// One dot per window, the focused one larger and highlighted
+    #[default]
+    Dot,
+    /// One short bar per window, the focused one wider and highlighted
+    Bar,
+    /// Dots per window plus a glow behind the focused application
+    Glow,
 }
 
 impl Default for Config {
@@ -31,39 +78,772 @@ impl Default for Config {
             scroll_speed: 15.0,
             visible_items: 8,
             tilt_y: 0.25,
+            layout: LayoutKind::Diagonal,
+            backend: BackendKind::Auto,
+            indicator_style: IndicatorStyle::Dot,
+            urgent: UrgentConfig::default(),
+            magnification: MagnificationConfig::default(),
+            visibility: VisibilityConfig::default(),
+            layer: LayerConfig::default(),
+            monitors: MonitorConfig::default(),
+            workspaces: WorkspaceConfig::default(),
+            terminal: TerminalConfig::default(),
+        }
+    }
+}
+
+/// How an icon asks for attention when one of its windows becomes urgent
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UrgentAnimation {
+    /// Jump up and down
+    #[default]
+    Bounce,
+    /// Grow and shrink in place
+    Pulse,
+    /// Don't animate urgent windows at all
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct UrgentConfig {
+    pub animation: UrgentAnimation,
+    /// Length of one bounce or pulse, in seconds
+    pub duration: f32,
+    /// Number of bounces or pulses; 0 keeps going until the window is focused
+    pub repeat: u32,
+    /// Application classes that never animate
+    pub disabled_classes: Vec<String>,
+}
+
+impl Default for UrgentConfig {
+    fn default() -> Self {
+        Self {
+            animation: UrgentAnimation::Bounce,
+            duration: 0.6,
+            repeat: 0,
+            disabled_classes: Vec::new(),
+        }
+    }
+}
+
+impl UrgentConfig {
+    pub fn is_enabled_for(&self, class: &str) -> bool {
+        self.animation != UrgentAnimation::None
+            && !self
+                .disabled_classes
+                .iter()
+                .any(|disabled| disabled.eq_ignore_ascii_case(class))
+    }
+}
+
+/// Screen edge the dock's layer surface is anchored to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Layer-shell stacking layer, from below every window to above fullscreen ones
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    Background,
+    Bottom,
+    #[default]
+    Top,
+    Overlay,
+}
+
+/// When the dock gets keyboard focus, and with it its keybinds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyboardInteractivity {
+    /// Never; keybinds don't work
+    None,
+    /// Always, taking the keyboard from every window
+    Exclusive,
+    /// When clicked, like a regular window
+    #[default]
+    OnDemand,
+}
+
+/// How quickly magnification fades with distance from the cursor
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Falloff {
+    /// Straight down to nothing at `radius`
+    Linear,
+    /// Flat near the cursor, then easing out to nothing at `radius`
+    #[default]
+    Cosine,
+    /// A bell curve, dropping fast and trailing off near `radius`
+    Gaussian,
+}
+
+impl Falloff {
+    /// Share of the full magnification at `distance`, from 1 at the cursor to 0 at `radius`
+    pub fn weight(self, distance: f32, radius: f32) -> f32 {
+        let x = distance.abs() / radius;
+        if x.is_nan() || x >= 1.0 {
+            return 0.0;
+        }
+        match self {
+            Falloff::Linear => 1.0 - x,
+            Falloff::Cosine => (1.0 + (x * std::f32::consts::PI).cos()) / 2.0,
+            Falloff::Gaussian => (-4.5 * x * x).exp(),
+        }
+    }
+}
+
+/// How icons grow and rise as the cursor passes over them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MagnificationConfig {
+    /// Distance along the dock, in pixels, over which icons grow toward the cursor;
+    /// 0 grows only the hovered icon
+    pub radius: f32,
+    /// Scale of the icon under the cursor, relative to its resting size
+    pub scale: f32,
+    /// Pixels the icon under the cursor rises by
+    pub lift: f32,
+    pub falloff: Falloff,
+    /// How far neighbours move apart to make room: 0 = not at all, 1 = all the room grown icons need
+    pub spread: f32,
+}
+
+impl Default for MagnificationConfig {
+    fn default() -> Self {
+        Self {
+            radius: 0.0,
+            scale: 1.15,
+            lift: 15.0,
+            falloff: Falloff::Cosine,
+            spread: 1.0,
+        }
+    }
+}
+
+/// When the dock slides off screen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VisibilityMode {
+    /// Never hide
+    #[default]
+    Always,
+    /// Hide once the pointer has left the dock for `delay` seconds
+    AutoHide,
+    /// Like `auto-hide`, but only while a window covers the dock or one is fullscreen
+    Intellihide,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct VisibilityConfig {
+    pub mode: VisibilityMode,
+    /// Seconds between the pointer leaving the dock and the dock hiding
+    pub delay: f32,
+    /// Seconds the slide in or out takes
+    pub duration: f32,
+    /// Width, in pixels, of the strip along the screen edge that brings a hidden dock back
+    pub reveal_distance: f32,
+}
+
+impl Default for VisibilityConfig {
+    fn default() -> Self {
+        Self {
+            mode: VisibilityMode::Always,
+            delay: 0.8,
+            duration: 0.25,
+            reveal_distance: 4.0,
+        }
+    }
+}
+
+/// How the dock sits on screen as a `zwlr_layer_shell_v1` panel
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LayerConfig {
+    /// Use a layer surface when the compositor supports one, otherwise a regular window
+    pub enabled: bool,
+    pub edge: Edge,
+    pub layer: Layer,
+    /// Height of the panel, or width when anchored left or right, in pixels
+    pub size: u32,
+    /// Pixels kept free of windows along the edge; 0 overlaps them, -1 also overlaps other panels
+    pub exclusive_zone: i32,
+    pub keyboard_interactivity: KeyboardInteractivity,
+}
+
+impl Default for LayerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            edge: Edge::Bottom,
+            layer: Layer::Top,
+            size: 360,
+            exclusive_zone: 0,
+            keyboard_interactivity: KeyboardInteractivity::OnDemand,
+        }
+    }
+}
+
+/// How many docks to open
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorMode {
+    /// One dock, on `output` or wherever the compositor puts it
+    #[default]
+    Single,
+    /// One dock per monitor, following monitors as they are plugged in and out
+    All,
+}
+
+/// Which windows a dock shows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowScope {
+    /// Every window, wherever it is
+    #[default]
+    All,
+    /// Windows on the dock's monitor
+    Monitor,
+    /// Windows on the workspace the dock's monitor is showing
+    Workspace,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MonitorConfig {
+    pub mode: MonitorMode,
+    /// Monitor for a single dock, by connector name; empty leaves it to the compositor
+    pub output: String,
+    pub windows: WindowScope,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Order running applications by workspace, with a separator between workspaces
+    pub grouped: bool,
+    /// Show a button per occupied workspace above the dock; clicking one switches to it
+    pub strip: bool,
+}
+
+/// Terminal emulator for `Terminal=true` applications and pins with `terminal` set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Terminal to run, or `auto` for `$TERMINAL` or the first of kitty, alacritty, foot and wezterm found
+    pub program: String,
+    /// Arguments between the terminal and the command it runs; empty uses the right ones
+    /// for known terminals and `-e` for the rest
+    pub args: Vec<String>,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            program: "auto".to_string(),
+            args: Vec::new(),
+        }
+    }
+}
+
+/// Error types for loading `config.toml`
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("line {line}, column {column}: {message}")]
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("invalid value for `{field}`: {message}")]
+    Invalid { field: &'static str, message: String },
+}
+
+impl ConfigError {
+    fn parse(contents: &str, error: &toml::de::Error) -> Self {
+        let offset = error.span().map(|span| span.start).unwrap_or(0);
+        let before = &contents[..offset.min(contents.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+        Self::Parse {
+            line,
+            column,
+            message: error.message().to_string(),
+        }
+    }
+
+    fn invalid(field: &'static str, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl Config {
+    /// Check that values are in a range the layout code can work with
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.scale_factor > 0.0 && self.scale_factor <= 1.0) {
+            return Err(ConfigError::invalid("scale_factor", "must be in (0, 1]"));
+        }
+        if self.visible_items < 1 {
+            return Err(ConfigError::invalid("visible_items", "must be at least 1"));
+        }
+        if !is_positive(self.icon_size) {
+            return Err(ConfigError::invalid("icon_size", "must be greater than 0"));
+        }
+        if !is_positive(self.base_scale) {
+            return Err(ConfigError::invalid("base_scale", "must be greater than 0"));
         }
+        if !is_positive(self.urgent.duration) {
+            return Err(ConfigError::invalid("urgent.duration", "must be greater than 0"));
+        }
+        if self.magnification.radius.is_nan() || self.magnification.radius < 0.0 {
+            return Err(ConfigError::invalid("magnification.radius", "must be 0 or more"));
+        }
+        if !is_positive(self.magnification.scale) {
+            return Err(ConfigError::invalid("magnification.scale", "must be greater than 0"));
+        }
+        if !(0.0..=1.0).contains(&self.magnification.spread) {
+            return Err(ConfigError::invalid("magnification.spread", "must be in [0, 1]"));
+        }
+        if self.visibility.delay.is_nan() || self.visibility.delay < 0.0 {
+            return Err(ConfigError::invalid("visibility.delay", "must be 0 or more"));
+        }
+        if !is_positive(self.visibility.duration) {
+            return Err(ConfigError::invalid("visibility.duration", "must be greater than 0"));
+        }
+        if self.visibility.reveal_distance.is_nan() || self.visibility.reveal_distance < 1.0 {
+            return Err(ConfigError::invalid("visibility.reveal_distance", "must be at least 1"));
+        }
+        if self.layer.size == 0 {
+            return Err(ConfigError::invalid("layer.size", "must be greater than 0"));
+        }
+        if self.layer.exclusive_zone < -1 {
+            return Err(ConfigError::invalid("layer.exclusive_zone", "must be -1 or more"));
+        }
+        if self.terminal.program.trim().is_empty() {
+            return Err(ConfigError::invalid("terminal.program", "must name a terminal or be \"auto\""));
+        }
+        Ok(())
+    }
+}
+
+/// False for zero, negative values and NaN
+fn is_positive(value: f32) -> bool {
+    value > 0.0
+}
+
+pub fn parse_config(contents: &str) -> Result<Config, ConfigError> {
+    let config: Config =
+        toml::from_str(contents).map_err(|e| ConfigError::parse(contents, &e))?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// Dotted paths of keys in `contents` that `Config` doesn't know about
+///
+/// Serde silently ignores them, which hides typos like `visible_item`.
+pub fn unknown_keys(contents: &str) -> Vec<String> {
+    let (Ok(table), Ok(known)) = (
+        contents.parse::<toml::Table>(),
+        toml::Table::try_from(Config::default()),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut unknown = Vec::new();
+    collect_unknown_keys(&table, &known, "", &mut unknown);
+    unknown
+}
+
+fn collect_unknown_keys(
+    table: &toml::Table,
+    known: &toml::Table,
+    prefix: &str,
+    unknown: &mut Vec<String>,
+) {
+    for (key, value) in table {
+        let path = format!("{}{}", prefix, key);
+        match (value, known.get(key)) {
+            (_, None) => unknown.push(path),
+            (toml::Value::Table(table), Some(toml::Value::Table(known))) => {
+                collect_unknown_keys(table, known, &format!("{}.", path), unknown)
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn read_config(path: &Path) -> Result<Config, ConfigError> {
+    let contents = fs::read_to_string(path)?;
+    let config = parse_config(&contents)?;
+    for key in unknown_keys(&contents) {
+        warn!("Unknown key `{}` in {}", key, path.display());
+    }
+    Ok(config)
+}
+
+/// Round `f32`s to their shortest decimal form, so `0.9` isn't written as `0.8999999761581421`
+fn round_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(f) => {
+            *f = (*f as f32).to_string().parse().unwrap_or(*f);
+        }
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| round_floats(v)),
+        toml::Value::Array(array) => array.iter_mut().for_each(round_floats),
+        _ => {}
     }
 }
 
-fn get_config_path() -> Option<PathBuf> {
-    dirs::config_dir()
-        .map(|mut path| {
-            path.push("anny-dock");
-            fs::create_dir_all(&path).ok()?;
-            path.push("config.toml");
-            Some(path)
-        })
-        .flatten()
+fn plain_default_config_toml() -> String {
+    let mut value = toml::Value::try_from(Config::default()).expect("Default config serializes");
+    round_floats(&mut value);
+    toml::to_string_pretty(&value).expect("Default config serializes")
 }
 
-pub fn load_config() -> Config {
-    if let Some(path) = get_config_path() {
-        if path.exists() {
-            let mut file = fs::File::open(path).expect("Failed to open config file");
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)
-                .expect("Failed to read config file");
-            toml::from_str(&contents).expect("Failed to parse config file")
+/// Description of every config key, written above it by `default_config_toml`
+const KEY_DOCS: &[(&str, &str)] = &[
+    ("icon_size", "Base size of icons, in pixels (> 0)"),
+    ("margin_x", "Horizontal margin from the screen edge"),
+    ("margin_y", "Vertical margin from the screen edge"),
+    ("spacing", "Space between icons"),
+    ("z_spacing", "Depth spacing for the 3D effect"),
+    ("base_scale", "Scale of the first icon (> 0)"),
+    ("scale_factor", "Each following icon is scaled by this factor, in (0, 1]"),
+    ("scroll_speed", "Scroll sensitivity"),
+    ("visible_items", "Number of icons visible at once (>= 1)"),
+    ("tilt_y", "Inclination towards the screen center (0.0 = horizontal, 0.5 = strong diagonal)"),
+    ("layout", "Icon arrangement: diagonal (uses tilt_y), horizontal, vertical-left, vertical-right, arc"),
+    ("backend", "Window manager backend: auto, hyprland, sway, wlr, mock (needs a restart)"),
+    ("indicator_style", "Running/focused window indicator: dot, bar, glow"),
+    ("urgent", "Attention animation for windows that request it"),
+    ("urgent.animation", "bounce, pulse or none"),
+    ("urgent.duration", "Seconds per bounce or pulse (> 0)"),
+    ("urgent.repeat", "Number of bounces or pulses, 0 = until the window is focused"),
+    ("urgent.disabled_classes", "Application classes that never animate, e.g. [\"Slack\"]"),
+    ("magnification", "Icons growing and rising as the cursor passes over them"),
+    ("magnification.radius", "Pixels along the dock over which icons grow toward the cursor; 0 = only the hovered icon (>= 0)"),
+    ("magnification.scale", "Scale of the icon under the cursor, e.g. 1.15 or 1.8 (> 0)"),
+    ("magnification.lift", "Pixels the icon under the cursor rises by"),
+    ("magnification.falloff", "How magnification fades toward the radius: linear, cosine, gaussian"),
+    ("magnification.spread", "How far neighbours move apart to make room, in [0, 1]"),
+    ("visibility", "Hiding the dock off screen until the pointer reaches the screen edge"),
+    ("visibility.mode", "always, auto-hide (after the pointer leaves), intellihide (while a window covers the dock or is fullscreen)"),
+    ("visibility.delay", "Seconds after the pointer leaves before the dock hides (>= 0)"),
+    ("visibility.duration", "Seconds the dock takes to slide in or out (> 0)"),
+    ("visibility.reveal_distance", "Pixels from the screen edge that bring a hidden dock back (>= 1)"),
+    ("layer", "Running as a Wayland layer-shell panel, like waybar"),
+    ("layer.enabled", "Use a layer surface when the compositor supports one (needs a restart)"),
+    ("layer.edge", "Screen edge to anchor to: top, bottom, left, right"),
+    ("layer.layer", "Stacking layer: background, bottom, top, overlay"),
+    ("layer.size", "Height of the panel, or width on the left and right edges, in pixels (> 0)"),
+    ("layer.exclusive_zone", "Space kept free of windows: 0 = overlap them, -1 = overlap other panels too"),
+    ("layer.keyboard_interactivity", "Keyboard focus for keybinds: none, exclusive, on-demand"),
+    ("monitors", "Docks on multiple monitors"),
+    ("monitors.mode", "single = one dock, all = one dock per monitor (needs a restart)"),
+    ("monitors.output", "Monitor for a single dock, e.g. \"DP-1\"; empty = the focused one (needs a restart)"),
+    ("monitors.windows", "Windows each dock shows: all, monitor (on its monitor), workspace (on its active workspace)"),
+    ("workspaces", "Workspace grouping and switcher"),
+    ("workspaces.grouped", "Order running applications by workspace, with separators in between"),
+    ("workspaces.strip", "Show the occupied workspaces above the dock; click one to switch to it"),
+    ("terminal", "Terminal emulator for applications with Terminal=true and pins with \"terminal\": true"),
+    ("terminal.program", "auto = $TERMINAL or the first of kitty, alacritty, foot, wezterm installed; or any terminal, e.g. \"ghostty\""),
+    ("terminal.args", "Arguments before the command to run, e.g. [\"-e\"]; empty = the right ones for known terminals, -e otherwise"),
+];
+
+/// The default config as TOML, with a comment above every key
+pub fn default_config_toml() -> String {
+    let plain = plain_default_config_toml();
+    let doc_for = |key: &str| KEY_DOCS.iter().find(|(k, _)| *k == key).map(|(_, doc)| *doc);
+
+    let mut output = String::from("# anny-dock configuration\n# Every key is optional; missing keys use the values below.\n\n");
+    let mut table = String::new();
+
+    for line in plain.lines() {
+        let trimmed = line.trim();
+        let key = if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            table = format!("{}.", name);
+            Some(name.to_string())
         } else {
-            let config = Config::default();
-            let toml_string =
-                toml::to_string_pretty(&config).expect("Failed to serialize config");
-            let mut file = fs::File::create(path).expect("Failed to create config file");
-            file.write_all(toml_string.as_bytes())
-                .expect("Failed to write to config file");
-            config
-        }
-    } else {
+            trimmed
+                .split_once(" = ")
+                .map(|(key, _)| format!("{}{}", table, key))
+        };
+
+        if let Some(doc) = key.as_deref().and_then(doc_for) {
+            output.push_str(&format!("# {}\n", doc));
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+pub fn get_config_path() -> Option<PathBuf> {
+    dirs::config_dir().and_then(|mut path| {
+        path.push("anny-dock");
+        fs::create_dir_all(&path).ok()?;
+        path.push("config.toml");
+        Some(path)
+    })
+}
+
+/// Where the config is read from: `--config`, or the default location
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ConfigPath(pub Option<PathBuf>);
+
+/// Load the config, falling back to defaults if it is missing or invalid
+///
+/// A missing file is created with the documented defaults.
+pub fn load_config(path: Option<&Path>) -> Config {
+    let Some(path) = path else {
+        return Config::default();
+    };
+
+    if !path.exists() {
+        let config = Config::default();
+        if let Err(e) = fs::write(path, default_config_toml()) {
+            warn!("Failed to write default config to {}: {}", path.display(), e);
+        }
+        return config;
+    }
+
+    read_config(path).unwrap_or_else(|e| {
+        error!("Invalid config {}: {}, using defaults", path.display(), e);
         Config::default()
+    })
+}
+
+/// Re-parsed configs, sent every time `config.toml` is written
+#[derive(Resource, Clone)]
+pub struct ConfigReloadReceiver(pub Arc<Mutex<Receiver<Result<Config, ConfigError>>>>);
+
+/// Watch `path` and re-parse it off the main thread whenever it is saved
+///
+/// The parent directory is watched rather than the file itself, since most
+/// editors save by writing a new file and renaming it over the old one.
+pub fn watch_config(path: PathBuf) -> std::io::Result<ConfigReloadReceiver> {
+    let directory = path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "config has no parent"))?;
+    let file_name = path.file_name().map(|name| name.to_os_string());
+
+    let mut inotify = Inotify::init()?;
+    inotify
+        .watches()
+        .add(&directory, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)?;
+
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let events = match inotify.read_events_blocking(&mut buffer) {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("Config watcher stopped: {}", e);
+                    break;
+                }
+            };
+
+            let touched = events
+                .into_iter()
+                .any(|event| event.name.map(|name| name.to_os_string()) == file_name);
+            if touched {
+                info!("Config file changed, reloading");
+                if sender.send(read_config(&path)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(ConfigReloadReceiver(Arc::new(Mutex::new(receiver))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_round_trips() {
+        let toml_string = toml::to_string_pretty(&Config::default()).unwrap();
+        let config: Config = toml::from_str(&toml_string).unwrap();
+
+        assert_eq!(config.visible_items, 8);
+        assert_eq!(config.urgent.animation, UrgentAnimation::Bounce);
+    }
+
+    #[test]
+    fn test_parse_error_reports_position() {
+        let contents = toml::to_string_pretty(&Config::default())
+            .unwrap()
+            .replace("visible_items = 8", "visible_items = \"eight\"");
+        let line = contents
+            .lines()
+            .position(|l| l.starts_with("visible_items"))
+            .unwrap()
+            + 1;
+
+        match parse_config(&contents) {
+            Err(ConfigError::Parse { line: l, column, .. }) => {
+                assert_eq!(l, line);
+                assert_eq!(column, 17);
+            }
+            other => panic!("Expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let invalid = [
+            ("scale_factor", Config { scale_factor: 0.0, ..Config::default() }),
+            ("scale_factor", Config { scale_factor: 1.5, ..Config::default() }),
+            ("visible_items", Config { visible_items: 0, ..Config::default() }),
+            ("icon_size", Config { icon_size: -4.0, ..Config::default() }),
+            ("icon_size", Config { icon_size: f32::NAN, ..Config::default() }),
+        ];
+
+        for (expected, config) in invalid {
+            match config.validate() {
+                Err(ConfigError::Invalid { field, .. }) => assert_eq!(field, expected),
+                other => panic!("Expected {} to be invalid, got {:?}", expected, other),
+            }
+        }
+
+        assert!(Config { scale_factor: 1.0, ..Config::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config = parse_config("visible_items = 4\n[urgent]\nrepeat = 3\n").unwrap();
+
+        assert_eq!(config.visible_items, 4);
+        assert_eq!(config.icon_size, Config::default().icon_size);
+        assert_eq!(config.urgent.repeat, 3);
+        assert_eq!(config.urgent.duration, UrgentConfig::default().duration);
+    }
+
+    #[test]
+    fn test_layer_config() {
+        let config = parse_config(
+            "[layer]\nedge = \"left\"\nlayer = \"overlay\"\nexclusive_zone = -1\nkeyboard_interactivity = \"none\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.layer.edge, Edge::Left);
+        assert_eq!(config.layer.layer, Layer::Overlay);
+        assert_eq!(config.layer.exclusive_zone, -1);
+        assert_eq!(config.layer.keyboard_interactivity, KeyboardInteractivity::None);
+        assert_eq!(config.layer.size, LayerConfig::default().size);
+
+        let mut invalid = Config::default();
+        invalid.layer.exclusive_zone = -2;
+        assert!(matches!(
+            invalid.validate(),
+            Err(ConfigError::Invalid { field: "layer.exclusive_zone", .. })
+        ));
+    }
+
+    #[test]
+    fn test_magnification_config() {
+        let config = parse_config("[magnification]\nradius = 120.0\nfalloff = \"linear\"\n").unwrap();
+
+        assert_eq!(config.magnification.radius, 120.0);
+        assert_eq!(config.magnification.falloff, Falloff::Linear);
+        assert_eq!(config.magnification.scale, MagnificationConfig::default().scale);
+        assert!(matches!(
+            parse_config("[magnification]\nspread = 2.0\n"),
+            Err(ConfigError::Invalid { field: "magnification.spread", .. })
+        ));
+
+        for falloff in [Falloff::Linear, Falloff::Cosine, Falloff::Gaussian] {
+            assert_eq!(falloff.weight(0.0, 100.0), 1.0);
+            assert_eq!(falloff.weight(-100.0, 100.0), 0.0);
+            assert!(falloff.weight(30.0, 100.0) > falloff.weight(60.0, 100.0));
+        }
+    }
+
+    #[test]
+    fn test_visibility_config() {
+        let config = parse_config("[visibility]\nmode = \"intellihide\"\ndelay = 0.0\n").unwrap();
+
+        assert_eq!(config.visibility.mode, VisibilityMode::Intellihide);
+        assert_eq!(config.visibility.delay, 0.0);
+        assert_eq!(config.visibility.duration, VisibilityConfig::default().duration);
+        assert_eq!(
+            parse_config("[visibility]\nmode = \"auto-hide\"\n").unwrap().visibility.mode,
+            VisibilityMode::AutoHide
+        );
+        assert!(matches!(
+            parse_config("[visibility]\nreveal_distance = 0.0\n"),
+            Err(ConfigError::Invalid { field: "visibility.reveal_distance", .. })
+        ));
+    }
+
+    #[test]
+    fn test_terminal_config() {
+        let config = parse_config("[terminal]\nprogram = \"ghostty\"\nargs = [\"-e\"]\n").unwrap();
+
+        assert_eq!(config.terminal.program, "ghostty");
+        assert_eq!(config.terminal.args, vec!["-e"]);
+        assert_eq!(parse_config("").unwrap().terminal, TerminalConfig::default());
+        assert!(matches!(
+            parse_config("[terminal]\nprogram = \"  \"\n"),
+            Err(ConfigError::Invalid { field: "terminal.program", .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let contents = "visible_item = 4\nicon_size = 48.0\n[urgent]\nanimaton = \"pulse\"\n[theme]\ncolor = 1\n";
+
+        assert_eq!(
+            unknown_keys(contents),
+            vec!["visible_item", "urgent.animaton", "theme"]
+        );
+    }
+
+    #[test]
+    fn test_default_config_toml_documents_every_key() {
+        let commented = default_config_toml();
+
+        let lines: Vec<&str> = commented.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            if !line.is_empty() && !line.starts_with('#') {
+                assert!(
+                    i > 0 && lines[i - 1].starts_with('#'),
+                    "Missing doc for `{}`",
+                    line
+                );
+            }
+        }
+        assert_eq!(parse_config(&commented).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_urgent_disabled_classes() {
+        let urgent = UrgentConfig {
+            disabled_classes: vec!["Slack".to_string()],
+            ..Default::default()
+        };
+
+        assert!(!urgent.is_enabled_for("slack"));
+        assert!(urgent.is_enabled_for("firefox"));
     }
 }
@@ -0,0 +1,277 @@
+use bevy::log::{info, warn};
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// A command sent to a running dock, one JSON object per line
+///
+/// Indexes count icons from the start of the dock, as in `list`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    /// Click the icon at `index`: focus its window, or launch it if nothing is open
+    Focus { index: usize },
+    Launch { class: String },
+    Pin { class: String },
+    Unpin { class: String },
+    /// Move the icon at `from` to `to`
+    Reorder { from: usize, to: usize },
+    /// Scroll by `delta` icons, backwards when negative
+    Scroll { delta: f32 },
+    ToggleTitles,
+    Show,
+    Hide,
+    List,
+    ReloadConfig,
+    DumpState,
+    Quit,
+}
+
+/// The dock's answer to a `ControlRequest`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ControlReply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ControlReply {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            error: None,
+            data: None,
+        }
+    }
+
+    pub fn data(data: impl Serialize) -> Self {
+        Self {
+            data: serde_json::to_value(data).ok(),
+            ..Self::ok()
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(message.into()),
+            data: None,
+        }
+    }
+}
+
+/// One icon of the dock, as reported by `list`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DockItem {
+    pub class: String,
+    pub pinned: bool,
+    pub windows: Vec<String>,
+}
+
+/// Everything a script may want to know about the dock, as reported by `dump-state`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DockState {
+    pub items: Vec<DockItem>,
+    pub focused: Option<String>,
+    /// How far the dock is scrolled, in icons
+    pub scroll: f32,
+    pub show_titles: bool,
+    pub visible: bool,
+    pub connected: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ControlError {
+    #[error("no running dock at {0}")]
+    NotRunning(PathBuf),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid reply: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// A request, with the channel its reply goes back on
+pub type ControlMessage = (ControlRequest, Sender<ControlReply>);
+
+/// Requests waiting for the dock
+#[derive(Resource, Clone)]
+pub struct ControlReceiver(pub Arc<Mutex<Receiver<ControlMessage>>>);
+
+/// Where the dock listens for commands, `None` when scripting is disabled
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ControlSocketPath(pub Option<PathBuf>);
+
+pub fn socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("anny-dock.sock")
+}
+
+/// Socket of the dock on `monitor`, when one dock runs per monitor
+pub fn monitor_socket_path(monitor: &str) -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("anny-dock-{}.sock", monitor))
+}
+
+/// Listen on `path`, handing every request to the returned receiver
+///
+/// A socket file left behind by a dock that didn't shut down cleanly is
+/// replaced; one that still accepts connections means another dock is running.
+pub fn listen(path: &Path) -> io::Result<ControlReceiver> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another dock is listening on {}", path.display()),
+            ));
+        }
+        fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    let (sender, receiver) = channel();
+    info!("Listening for commands on {}", path.display());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || serve_client(stream, sender));
+                }
+                Err(e) => warn!("Failed to accept control connection: {}", e),
+            }
+        }
+    });
+
+    Ok(ControlReceiver(Arc::new(Mutex::new(receiver))))
+}
+
+fn serve_client(stream: UnixStream, sender: Sender<ControlMessage>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => {
+                let (reply_sender, reply_receiver) = channel();
+                if sender.send((request, reply_sender)).is_err() {
+                    break;
+                }
+                match reply_receiver.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => break,
+                }
+            }
+            Err(e) => ControlReply::error(format!("invalid request: {}", e)),
+        };
+
+        let Ok(json) = serde_json::to_string(&reply) else {
+            break;
+        };
+        if writeln!(writer, "{}", json).is_err() {
+            break;
+        }
+    }
+}
+
+/// Send `request` to the dock listening on `path` and wait for its reply
+pub fn send(path: &Path, request: &ControlRequest) -> Result<ControlReply, ControlError> {
+    let mut stream =
+        UnixStream::connect(path).map_err(|_| ControlError::NotRunning(path.to_path_buf()))?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let reply: ControlReply = serde_json::from_str(&line)?;
+
+    if reply.ok {
+        Ok(reply)
+    } else {
+        Err(ControlError::Rejected(reply.error.unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_request_wire_format() {
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"command":"pin","class":"firefox"}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::Pin {
+                class: "firefox".to_string()
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::ReloadConfig).unwrap(),
+            r#"{"command":"reload-config"}"#
+        );
+    }
+
+    #[test]
+    fn test_send_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("anny-dock.sock");
+        let receiver = listen(&path).unwrap();
+
+        let dock = std::thread::spawn(move || {
+            let (request, reply) = receiver.0.lock().unwrap().recv().unwrap();
+            assert_eq!(request, ControlRequest::List);
+            reply.send(ControlReply::data(vec!["kitty"])).unwrap();
+        });
+
+        let reply = send(&path, &ControlRequest::List).unwrap();
+        dock.join().unwrap();
+
+        assert_eq!(reply.data, Some(serde_json::json!(["kitty"])));
+    }
+
+    #[test]
+    fn test_stale_socket_is_replaced() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("anny-dock.sock");
+        drop(UnixListener::bind(&path).unwrap());
+
+        assert!(listen(&path).is_ok());
+        assert_eq!(
+            listen(&path).err().map(|e| e.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+    }
+
+    #[test]
+    fn test_send_without_dock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("anny-dock.sock");
+
+        assert!(matches!(
+            send(&path, &ControlRequest::Quit),
+            Err(ControlError::NotRunning(_))
+        ));
+    }
+}
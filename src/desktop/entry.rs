@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The `[Desktop Entry]` group of an application's `.desktop` file
+///
+/// Only what the dock needs is kept. Strings are unescaped and `name` is
+/// already localized; `exec` still holds its quoting and field codes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesktopEntry {
+    /// Desktop file id without `.desktop`, e.g. `org.gnome.Nautilus`
+    pub id: String,
+    pub path: PathBuf,
+    pub name: String,
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub no_display: bool,
+    /// The entry counts as deleted, hiding any entry with the same id further down
+    pub hidden: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
+    pub startup_wm_class: Option<String>,
+    /// Started by `org.freedesktop.Application.Activate` on D-Bus rather than `Exec`
+    pub dbus_activatable: bool,
+}
+
+impl DesktopEntry {
+    /// Parses `content`, picking the `Name` translation for `locale`
+    ///
+    /// `None` when there is no `[Desktop Entry]` group or the entry isn't an application.
+    pub fn parse(id: &str, path: &Path, content: &str, locale: Option<&str>) -> Option<Self> {
+        let keys = desktop_entry_group(content)?;
+        if keys.get("Type").is_some_and(|kind| *kind != "Application") {
+            return None;
+        }
+
+        let string = |key: &str| keys.get(key).map(|value| unescape(value));
+        let boolean = |key: &str| keys.get(key).is_some_and(|value| value.eq_ignore_ascii_case("true"));
+        let list = |key: &str| keys.get(key).map(|value| split_list(value)).unwrap_or_default();
+
+        Some(Self {
+            id: id.to_string(),
+            path: path.to_path_buf(),
+            name: localized(&keys, "Name", locale)
+                .map(unescape)
+                .unwrap_or_default(),
+            exec: string("Exec"),
+            try_exec: string("TryExec"),
+            icon: string("Icon"),
+            terminal: boolean("Terminal"),
+            no_display: boolean("NoDisplay"),
+            hidden: boolean("Hidden"),
+            only_show_in: list("OnlyShowIn"),
+            not_show_in: list("NotShowIn"),
+            startup_wm_class: string("StartupWMClass"),
+            dbus_activatable: boolean("DBusActivatable"),
+        })
+    }
+
+    /// Whether the entry should be offered in any of `desktops`, from `XDG_CURRENT_DESKTOP`
+    pub fn shown_in(&self, desktops: &[String]) -> bool {
+        let listed = |list: &[String]| list.iter().any(|desktop| desktops.contains(desktop));
+        if !self.only_show_in.is_empty() && !listed(&self.only_show_in) {
+            return false;
+        }
+        !listed(&self.not_show_in)
+    }
+
+    /// Name of the program the entry runs, from `TryExec` or the first word of `Exec`
+    ///
+    /// An `env VAR=value` prefix is skipped.
+    pub fn executable(&self) -> Option<String> {
+        let program = match &self.try_exec {
+            Some(program) => program.clone(),
+            None => self
+                .command_line(&[])
+                .ok()?
+                .into_iter()
+                .find(|word| word != "env" && !word.contains('='))?,
+        };
+        Path::new(&program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(str::to_string)
+    }
+}
+
+/// Keys and raw values of the `[Desktop Entry]` group, localized keys included
+fn desktop_entry_group(content: &str) -> Option<HashMap<&str, &str>> {
+    let mut keys = HashMap::new();
+    let mut found = false;
+    let mut in_entry = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            in_entry = group == "Desktop Entry";
+            found |= in_entry;
+        } else if in_entry {
+            if let Some((key, value)) = line.split_once('=') {
+                // A primeira ocorrência de uma chave é a que vale
+                keys.entry(key.trim()).or_insert(value.trim());
+            }
+        }
+    }
+
+    found.then_some(keys)
+}
+
+/// The value of `key` translated for `locale`, e.g. `pt_BR.UTF-8@latin`
+///
+/// Tries `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`
+/// before the untranslated key, as the spec orders them.
+fn localized<'a>(keys: &HashMap<&str, &'a str>, key: &str, locale: Option<&str>) -> Option<&'a str> {
+    let translated = locale.and_then(|locale| {
+        let (locale, modifier) = match locale.split_once('@') {
+            Some((locale, modifier)) => (locale, Some(modifier)),
+            None => (locale, None),
+        };
+        let locale = locale.split('.').next().unwrap_or(locale);
+        let (lang, country) = match locale.split_once('_') {
+            Some((lang, country)) => (lang, Some(country)),
+            None => (locale, None),
+        };
+
+        let mut candidates = Vec::new();
+        if let (Some(country), Some(modifier)) = (country, modifier) {
+            candidates.push(format!("{}_{}@{}", lang, country, modifier));
+        }
+        if let Some(country) = country {
+            candidates.push(format!("{}_{}", lang, country));
+        }
+        if let Some(modifier) = modifier {
+            candidates.push(format!("{}@{}", lang, modifier));
+        }
+        candidates.push(lang.to_string());
+
+        candidates
+            .iter()
+            .find_map(|candidate| keys.get(format!("{}[{}]", key, candidate).as_str()))
+            .copied()
+    });
+    translated.or_else(|| keys.get(key).copied())
+}
+
+/// Undoes the `\s`, `\n`, `\t`, `\r` and `\\` escapes of string values
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits a `;`-separated list, where `\;` is a literal semicolon
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.clone().next() == Some(';') => {
+                chars.next();
+                item.push(';');
+            }
+            ';' => items.push(std::mem::take(&mut item)),
+            _ => item.push(c),
+        }
+    }
+    items.push(item);
+    items
+        .into_iter()
+        .map(|item| unescape(&item))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIREFOX: &str = "\
+[Desktop Entry]
+Version=1.0
+Name=Firefox
+Name[pt_BR]=Navegador Firefox
+Name[pt]=Firefox (pt)
+Name[de]=Firefox-Webbrowser
+Comment=Browse the Web
+Exec=/usr/lib/firefox/firefox %u
+Icon=firefox
+Terminal=false
+Type=Application
+StartupWMClass=firefox
+Categories=Network;WebBrowser;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=/usr/lib/firefox/firefox --new-window %u
+";
+
+    fn parse(content: &str, locale: Option<&str>) -> Option<DesktopEntry> {
+        DesktopEntry::parse("firefox", Path::new("/usr/share/applications/firefox.desktop"), content, locale)
+    }
+
+    #[test]
+    fn test_parse_application() {
+        let entry = parse(FIREFOX, None).unwrap();
+
+        assert_eq!(entry.name, "Firefox");
+        // O Exec da ação não sobrescreve o da entrada principal
+        assert_eq!(entry.exec.as_deref(), Some("/usr/lib/firefox/firefox %u"));
+        assert_eq!(entry.startup_wm_class.as_deref(), Some("firefox"));
+        assert_eq!(entry.executable().as_deref(), Some("firefox"));
+        assert!(!entry.terminal && !entry.hidden && !entry.no_display);
+    }
+
+    #[test]
+    fn test_localized_name() {
+        let name = |locale| parse(FIREFOX, Some(locale)).unwrap().name;
+
+        assert_eq!(name("pt_BR.UTF-8"), "Navegador Firefox");
+        assert_eq!(name("pt_PT.UTF-8@euro"), "Firefox (pt)");
+        assert_eq!(name("de_AT"), "Firefox-Webbrowser");
+        assert_eq!(name("fr_FR.UTF-8"), "Firefox");
+    }
+
+    #[test]
+    fn test_skips_files_that_are_not_applications() {
+        assert!(parse("[Desktop Entry]\nType=Link\nName=Docs\nURL=https://example.org\n", None).is_none());
+        assert!(parse("[Other Group]\nName=Nope\nExec=nope\n", None).is_none());
+    }
+
+    #[test]
+    fn test_escapes_and_lists() {
+        let entry = parse(
+            "[Desktop Entry]\nName=Two\\sWords\nExec=env GDK_BACKEND=x11 myapp --flag\n\
+             OnlyShowIn=GNOME;Unity\\;Lite;\nNotShowIn=KDE;\n",
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(entry.name, "Two Words");
+        assert_eq!(entry.only_show_in, vec!["GNOME", "Unity;Lite"]);
+        assert_eq!(entry.not_show_in, vec!["KDE"]);
+        assert_eq!(entry.executable().as_deref(), Some("myapp"));
+        assert!(entry.shown_in(&["GNOME".to_string()]));
+        assert!(!entry.shown_in(&["Hyprland".to_string()]));
+    }
+}
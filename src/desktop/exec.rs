@@ -0,0 +1,424 @@
+//! `Exec` lines as the Desktop Entry spec reads them, and starting them
+//!
+//! Commands are split and expanded here and run directly, never through a
+//! shell, so quoting in the entry is all that decides the arguments.
+
+use bevy::log::{info, warn};
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+use super::{terminal_command, DesktopEntry};
+use crate::config::TerminalConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExecError {
+    #[error("entry has no Exec line")]
+    Missing,
+    #[error("Exec line has no program")]
+    Empty,
+    #[error("unterminated quote in Exec line")]
+    UnterminatedQuote,
+    #[error("unknown field code %{0}")]
+    InvalidFieldCode(char),
+    #[error("no terminal emulator found; set terminal.program in config.toml")]
+    NoTerminal,
+    #[error("failed to start program: {0}")]
+    Spawn(#[from] io::Error),
+}
+
+/// One word of an `Exec` line, with its quotes and escapes removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    text: String,
+    /// Some of the word was inside double quotes, where field codes aren't expanded
+    quoted: bool,
+}
+
+/// Splits an `Exec` value into words
+///
+/// Words are separated by spaces. Inside double quotes, `\"`, `` \` ``, `\$`
+/// and `\\` stand for the character after the backslash. `value` must already
+/// have had the string escapes undone, as `DesktopEntry::exec` has.
+fn split_words(value: &str) -> Result<Vec<Word>, ExecError> {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => words.extend(current.take()),
+            '"' => {
+                let word = current.get_or_insert_with(|| Word {
+                    text: String::new(),
+                    quoted: true,
+                });
+                word.quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '`' | '$' | '\\')) => word.text.push(escaped),
+                            Some(other) => {
+                                word.text.push('\\');
+                                word.text.push(other);
+                            }
+                            None => return Err(ExecError::UnterminatedQuote),
+                        },
+                        Some(other) => word.text.push(other),
+                        None => return Err(ExecError::UnterminatedQuote),
+                    }
+                }
+            }
+            _ => current
+                .get_or_insert_with(|| Word {
+                    text: String::new(),
+                    quoted: false,
+                })
+                .text
+                .push(c),
+        }
+    }
+    words.extend(current);
+    Ok(words)
+}
+
+/// What field codes expand to
+#[derive(Debug, Clone, Default)]
+pub struct FieldValues<'a> {
+    /// Translated `Name`, for `%c`
+    pub name: &'a str,
+    /// `Icon`, for `%i`
+    pub icon: Option<&'a str>,
+    /// Location of the `.desktop` file, for `%k`
+    pub path: &'a str,
+    /// Files or URLs to open, for `%f`, `%F`, `%u` and `%U`
+    pub targets: &'a [String],
+}
+
+/// The arguments `exec` runs, with field codes expanded
+///
+/// `%f`/`%u` take the first target and `%F`/`%U` all of them; standing alone
+/// and with no targets they leave no argument behind. `%i` becomes
+/// `--icon <Icon>` when there is an icon, `%%` a literal `%`, and the
+/// deprecated `%d %D %n %N %v %m` nothing. Quoted words are kept as written.
+pub fn expand_exec(exec: &str, values: &FieldValues) -> Result<Vec<String>, ExecError> {
+    let mut arguments = Vec::new();
+
+    for word in split_words(exec)? {
+        if word.quoted {
+            arguments.push(word.text);
+            continue;
+        }
+        match word.text.as_str() {
+            "%F" | "%U" => arguments.extend(values.targets.iter().cloned()),
+            "%f" | "%u" => arguments.extend(values.targets.first().cloned()),
+            "%i" => {
+                if let Some(icon) = values.icon.filter(|icon| !icon.is_empty()) {
+                    arguments.push("--icon".to_string());
+                    arguments.push(icon.to_string());
+                }
+            }
+            "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+            text => arguments.push(expand_word(text, values)?),
+        }
+    }
+
+    match arguments.first() {
+        Some(program) if !program.is_empty() => Ok(arguments),
+        _ => Err(ExecError::Empty),
+    }
+}
+
+/// Expands field codes embedded in a longer word, like `--name=%c`
+fn expand_word(word: &str, values: &FieldValues) -> Result<String, ExecError> {
+    let mut expanded = String::with_capacity(word.len());
+    let mut chars = word.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some('f' | 'u' | 'F' | 'U') => {
+                expanded.push_str(values.targets.first().map_or("", String::as_str))
+            }
+            Some('c') => expanded.push_str(values.name),
+            Some('k') => expanded.push_str(values.path),
+            Some('i') => expanded.push_str(values.icon.unwrap_or_default()),
+            Some('d' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+            Some(code) => return Err(ExecError::InvalidFieldCode(code)),
+            None => return Err(ExecError::InvalidFieldCode(' ')),
+        }
+    }
+    Ok(expanded)
+}
+
+impl DesktopEntry {
+    /// The arguments this entry runs to open `targets`
+    pub fn command_line(&self, targets: &[String]) -> Result<Vec<String>, ExecError> {
+        let exec = self.exec.as_deref().ok_or(ExecError::Missing)?;
+        let path = self.path.to_string_lossy();
+        expand_exec(
+            exec,
+            &FieldValues {
+                name: &self.name,
+                icon: self.icon.as_deref(),
+                path: &path,
+                targets,
+            },
+        )
+    }
+}
+
+/// Starts `entry`, through D-Bus activation when it asks for it
+///
+/// D-Bus activation goes through `gdbus`; should that fail or be missing,
+/// the `Exec` line is run instead. `Terminal=true` entries run inside the
+/// terminal from `terminal`.
+pub fn launch_entry(entry: &DesktopEntry, terminal: &TerminalConfig) -> Result<(), ExecError> {
+    if entry.terminal {
+        let command_line = in_terminal(terminal, entry.command_line(&[])?)?;
+        return spawn_detached(&command_line, None);
+    }
+
+    let command_line = entry.command_line(&[]);
+    if !entry.dbus_activatable {
+        return spawn_detached(&command_line?, None);
+    }
+
+    let fallback = command_line
+        .map_err(|e| warn!("No Exec to fall back on for {}: {}", entry.id, e))
+        .ok();
+    match (spawn_detached(&dbus_activation(&entry.id), fallback.clone()), fallback) {
+        (Err(e), Some(fallback)) => {
+            warn!("D-Bus activation of {} failed: {}", entry.id, e);
+            spawn_detached(&fallback, None)
+        }
+        (result, _) => result,
+    }
+}
+
+/// Runs the shell `command` inside the terminal from `terminal`
+pub fn launch_in_terminal(command: &str, terminal: &TerminalConfig) -> Result<(), ExecError> {
+    let shell = ["sh", "-c", command].map(str::to_string).to_vec();
+    spawn_detached(&in_terminal(terminal, shell)?, None)
+}
+
+/// `arguments` prefixed with the terminal that should run them
+fn in_terminal(terminal: &TerminalConfig, arguments: Vec<String>) -> Result<Vec<String>, ExecError> {
+    let mut command_line = terminal_command(terminal).ok_or(ExecError::NoTerminal)?;
+    command_line.extend(arguments);
+    Ok(command_line)
+}
+
+/// `gdbus` call to `org.freedesktop.Application.Activate` on the application `id`
+fn dbus_activation(id: &str) -> Vec<String> {
+    let object_path = format!("/{}", id.replace('.', "/").replace('-', "_"));
+    [
+        "gdbus",
+        "call",
+        "--session",
+        "--dest",
+        id,
+        "--object-path",
+        &object_path,
+        "--method",
+        "org.freedesktop.Application.Activate",
+        "{}",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+/// Runs `arguments` in a session of its own, with no stdio from the dock
+///
+/// A thread waits on the child so it never lingers as a zombie, and runs
+/// `fallback` the same way if the child exits unsuccessfully.
+fn spawn_detached(arguments: &[String], fallback: Option<Vec<String>>) -> Result<(), ExecError> {
+    let (program, arguments) = arguments.split_first().ok_or(ExecError::Empty)?;
+    let mut command = Command::new(program);
+    command
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Sessão própria: o app não morre junto com o dock nem recebe seus sinais
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command.spawn()?;
+
+    let program = program.clone();
+    std::thread::Builder::new()
+        .name("launch-reaper".to_string())
+        .spawn(move || match child.wait() {
+            Ok(status) if status.success() => {}
+            status => {
+                if let Some(fallback) = fallback {
+                    info!("{} failed ({:?}), running {:?}", program, status, fallback);
+                    if let Err(e) = spawn_detached(&fallback, None) {
+                        warn!("Fallback launch failed: {}", e);
+                    }
+                }
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn expand(exec: &str) -> Result<Vec<String>, ExecError> {
+        expand_exec(
+            exec,
+            &FieldValues {
+                name: "Fancy App",
+                icon: Some("fancy"),
+                path: "/usr/share/applications/fancy.desktop",
+                targets: &[],
+            },
+        )
+    }
+
+    fn args(exec: &str) -> Vec<String> {
+        expand(exec).unwrap()
+    }
+
+    #[test]
+    fn test_field_codes_standing_alone_disappear() {
+        assert_eq!(args("firefox %u"), vec!["firefox"]);
+        assert_eq!(args("code --unity-launch %F"), vec!["code", "--unity-launch"]);
+        // Argumentos depois do código de campo não se perdem
+        assert_eq!(args("gimp %U --new-instance"), vec!["gimp", "--new-instance"]);
+        assert_eq!(args("app %d %D %n %N %v %m"), vec!["app"]);
+    }
+
+    #[test]
+    fn test_flatpak_exec() {
+        assert_eq!(
+            args("/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=firefox --file-forwarding org.mozilla.firefox @@u %u @@"),
+            vec![
+                "/usr/bin/flatpak",
+                "run",
+                "--branch=stable",
+                "--arch=x86_64",
+                "--command=firefox",
+                "--file-forwarding",
+                "org.mozilla.firefox",
+                "@@u",
+                "@@",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quoted_arguments() {
+        assert_eq!(
+            args("\"/opt/My App/bin/app\" --title \"a \\\"quoted\\\" \\$HOME \\`x\\` \\\\ word\""),
+            vec!["/opt/My App/bin/app", "--title", "a \"quoted\" $HOME `x` \\ word"]
+        );
+        assert_eq!(
+            args("sh -c \"echo %u; exec app\""),
+            vec!["sh", "-c", "echo %u; exec app"]
+        );
+        assert_eq!(args("app --name=\"two words\""), vec!["app", "--name=two words"]);
+    }
+
+    #[test]
+    fn test_other_field_codes() {
+        assert_eq!(args("app %i --class=%c"), vec!["app", "--icon", "fancy", "--class=Fancy App"]);
+        assert_eq!(
+            args("app --desktop-file=%k 100%%"),
+            vec!["app", "--desktop-file=/usr/share/applications/fancy.desktop", "100%"]
+        );
+
+        let values = FieldValues {
+            targets: &["/tmp/a b.txt".to_string(), "/tmp/c.txt".to_string()],
+            ..FieldValues::default()
+        };
+        assert_eq!(
+            expand_exec("editor %F", &values).unwrap(),
+            vec!["editor", "/tmp/a b.txt", "/tmp/c.txt"]
+        );
+        assert_eq!(expand_exec("editor --file=%f", &values).unwrap(), vec!["editor", "--file=/tmp/a b.txt"]);
+        assert_eq!(expand_exec("viewer %i", &values).unwrap(), vec!["viewer"]);
+    }
+
+    #[test]
+    fn test_invalid_exec_lines() {
+        assert!(matches!(expand("app %z"), Err(ExecError::InvalidFieldCode('z'))));
+        assert!(matches!(expand("app \"unterminated"), Err(ExecError::UnterminatedQuote)));
+        assert!(matches!(expand("%U"), Err(ExecError::Empty)));
+        assert!(matches!(expand("   "), Err(ExecError::Empty)));
+    }
+
+    #[test]
+    fn test_entry_command_line_after_string_escapes() {
+        // No arquivo, uma barra literal entre aspas precisa de quatro barras
+        let content = "[Desktop Entry]\nType=Application\nName=Wine App\nIcon=wine\n\
+                       Exec=wine \"C:\\\\\\\\Program Files\\\\\\\\App\\\\\\\\app.exe\" %i\n";
+        let entry = DesktopEntry::parse("wine-app", Path::new("/tmp/wine-app.desktop"), content, None).unwrap();
+
+        assert_eq!(
+            entry.command_line(&[]).unwrap(),
+            vec!["wine", "C:\\Program Files\\App\\app.exe", "--icon", "wine"]
+        );
+        assert_eq!(entry.executable().as_deref(), Some("wine"));
+    }
+
+    #[test]
+    fn test_dbus_activation_object_path() {
+        assert_eq!(
+            dbus_activation("org.gnome.Nautilus")[6],
+            "/org/gnome/Nautilus"
+        );
+        assert_eq!(
+            dbus_activation("org.gnome.font-viewer")[6],
+            "/org/gnome/font_viewer"
+        );
+    }
+
+    #[test]
+    fn test_terminal_entry_runs_inside_terminal() {
+        let content = "[Desktop Entry]\nType=Application\nName=htop\nExec=htop --tree\nTerminal=true\n";
+        let entry = DesktopEntry::parse("htop", Path::new("/tmp/htop.desktop"), content, None).unwrap();
+        let terminal = |program: &str| TerminalConfig {
+            program: program.to_string(),
+            args: Vec::new(),
+        };
+
+        assert_eq!(
+            in_terminal(&terminal("foot"), entry.command_line(&[]).unwrap()).unwrap(),
+            vec!["foot", "htop", "--tree"]
+        );
+        assert_eq!(
+            in_terminal(&terminal("alacritty"), entry.command_line(&[]).unwrap()).unwrap(),
+            vec!["alacritty", "-e", "htop", "--tree"]
+        );
+    }
+
+    #[test]
+    fn test_spawn_detached_runs_without_a_shell() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("has spaces; and $chars");
+
+        spawn_detached(&["touch".to_string(), marker.to_string_lossy().to_string()], None).unwrap();
+
+        for _ in 0..100 {
+            if marker.exists() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("touch never ran");
+    }
+}
@@ -0,0 +1,402 @@
+//! Installed applications, from the `.desktop` files in the XDG data directories
+//!
+//! `DesktopEntryIndex` is built once at startup and rebuilt by a watcher
+//! thread whenever an application directory changes, so launching a pinned
+//! icon never touches the disk. `launch_entry` then runs the entry's `Exec`
+//! line without a shell, inside the configured terminal for `Terminal=true`
+//! entries.
+
+mod entry;
+mod exec;
+mod terminal;
+
+pub use entry::DesktopEntry;
+pub use exec::{expand_exec, launch_entry, launch_in_terminal, ExecError, FieldValues};
+pub use terminal::{terminal_command, terminal_command_from};
+
+use bevy::log::{info, warn};
+use bevy::prelude::Resource;
+use inotify::{Inotify, WatchMask};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long to wait for a package manager to finish writing before rescanning
+const RESCAN_DELAY: Duration = Duration::from_millis(500);
+
+/// What decides which entries apply: the desktop, the language and `PATH`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesktopContext {
+    /// `XDG_CURRENT_DESKTOP`, for `OnlyShowIn` and `NotShowIn`
+    pub desktops: Vec<String>,
+    /// Locale for `Name`, from `LC_ALL`, `LC_MESSAGES` or `LANG`
+    pub locale: Option<String>,
+    /// Directories searched for `TryExec` programs
+    pub path: Vec<PathBuf>,
+}
+
+impl DesktopContext {
+    pub fn from_env() -> Self {
+        let desktops = std::env::var("XDG_CURRENT_DESKTOP")
+            .map(|desktops| {
+                desktops
+                    .split(':')
+                    .filter(|desktop| !desktop.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find(|locale| !locale.is_empty())
+            .filter(|locale| locale != "C" && locale != "POSIX");
+        let path = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect())
+            .unwrap_or_default();
+
+        Self {
+            desktops,
+            locale,
+            path,
+        }
+    }
+
+    /// Whether `program` is an executable file, directly or somewhere on `path`
+    fn finds(&self, program: &str) -> bool {
+        find_program(program, &self.path)
+    }
+}
+
+/// Whether `program` is an executable file, directly or in one of `path`'s directories
+fn find_program(program: &str, path: &[PathBuf]) -> bool {
+    let is_executable = |path: &Path| {
+        fs::metadata(path)
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    };
+    if program.contains('/') {
+        is_executable(Path::new(program))
+    } else {
+        path.iter().any(|dir| is_executable(&dir.join(program)))
+    }
+}
+
+/// `applications` directories in precedence order, the user's own first
+///
+/// Follows `XDG_DATA_HOME` and `XDG_DATA_DIRS`, then adds the Flatpak export
+/// directories in case the session didn't put them on `XDG_DATA_DIRS`.
+pub fn application_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+    let data_dirs: Vec<PathBuf> = std::env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .map(|dirs| std::env::split_paths(&dirs).collect())
+        .unwrap_or_else(|| vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]);
+    let flatpak = [
+        home.map(|home| home.join(".local/share/flatpak/exports/share")),
+        Some(PathBuf::from("/var/lib/flatpak/exports/share")),
+    ];
+
+    let mut dirs = Vec::new();
+    for dir in data_home.into_iter().chain(data_dirs).chain(flatpak.into_iter().flatten()) {
+        let applications = dir.join("applications");
+        if !dirs.contains(&applications) {
+            dirs.push(applications);
+        }
+    }
+    dirs
+}
+
+/// Every installed application, looked up by what a window's class might match
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DesktopEntryIndex {
+    entries: Vec<DesktopEntry>,
+    /// Keys are lowercased, each pointing into `entries`
+    by_id: HashMap<String, usize>,
+    by_wm_class: HashMap<String, usize>,
+    by_executable: HashMap<String, usize>,
+    by_name: HashMap<String, usize>,
+    dirs: Vec<PathBuf>,
+    context: DesktopContext,
+}
+
+impl DesktopEntryIndex {
+    /// Index of the applications installed for this session
+    pub fn scan() -> Self {
+        Self::build(application_dirs(), DesktopContext::from_env())
+    }
+
+    /// Index of the `.desktop` files under `dirs`, the first directory winning
+    ///
+    /// An entry is left out, along with any entry of the same id in later
+    /// directories, when it is `Hidden`, not meant for `context.desktops`, or
+    /// its `TryExec` program isn't installed.
+    pub fn build(dirs: Vec<PathBuf>, context: DesktopContext) -> Self {
+        let mut index = Self {
+            dirs,
+            context,
+            ..Self::default()
+        };
+        let mut seen = HashSet::new();
+
+        for dir in &index.dirs {
+            for (id, path) in desktop_files(dir) {
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+                let Ok(content) = fs::read_to_string(&path) else {
+                    continue;
+                };
+                let Some(entry) =
+                    DesktopEntry::parse(&id, &path, &content, index.context.locale.as_deref())
+                else {
+                    continue;
+                };
+                let installed = match &entry.try_exec {
+                    Some(program) => index.context.finds(program),
+                    None => true,
+                };
+                if !entry.hidden && installed && entry.shown_in(&index.context.desktops) {
+                    index.entries.push(entry);
+                }
+            }
+        }
+
+        for (position, entry) in index.entries.iter().enumerate() {
+            let insert = |map: &mut HashMap<String, usize>, key: &str| {
+                map.entry(key.to_lowercase()).or_insert(position);
+            };
+            insert(&mut index.by_id, &entry.id);
+            if let Some(class) = &entry.startup_wm_class {
+                insert(&mut index.by_wm_class, class);
+            }
+            if let Some(executable) = entry.executable() {
+                insert(&mut index.by_executable, &executable);
+            }
+            if !entry.name.is_empty() {
+                insert(&mut index.by_name, &entry.name);
+            }
+        }
+        index
+    }
+
+    /// The same directories and context, read again
+    pub fn rescan(&self) -> Self {
+        Self::build(self.dirs.clone(), self.context.clone())
+    }
+
+    /// The entry for windows of `class`
+    ///
+    /// Matches, in order, the desktop file id, `StartupWMClass`, the program
+    /// `Exec` runs and `Name`, ignoring case.
+    pub fn lookup(&self, class: &str) -> Option<&DesktopEntry> {
+        let class = class.to_lowercase();
+        let id = class.strip_suffix(".desktop").unwrap_or(&class);
+        self.by_id
+            .get(id)
+            .or_else(|| self.by_wm_class.get(&class))
+            .or_else(|| self.by_executable.get(&class))
+            .or_else(|| self.by_name.get(&class))
+            .map(|&position| &self.entries[position])
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Directories the index was built from
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+}
+
+/// `.desktop` files under `dir` with their ids, subdirectories included
+///
+/// A file in a subdirectory gets the subdirectory as a prefix, so
+/// `applications/kde/konsole.desktop` has the id `kde-konsole`.
+fn desktop_files(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let Ok(read_dir) = fs::read_dir(&current) else {
+            continue;
+        };
+        for path in read_dir.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "desktop") {
+                let id = path
+                    .strip_prefix(dir)
+                    .unwrap_or(&path)
+                    .with_extension("")
+                    .to_string_lossy()
+                    .replace('/', "-");
+                files.push((id, path));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Fresh indexes, sent every time an application directory changes
+#[derive(Resource, Clone)]
+pub struct DesktopEntryReceiver(pub Arc<Mutex<Receiver<DesktopEntryIndex>>>);
+
+/// Watch the directories `index` was built from, rebuilding it off the main thread on changes
+///
+/// Directories that don't exist yet aren't watched; new subdirectories are
+/// picked up by the next rescan but not watched themselves.
+pub fn watch_applications(index: &DesktopEntryIndex) -> std::io::Result<DesktopEntryReceiver> {
+    let mut inotify = Inotify::init()?;
+    let mask = WatchMask::CREATE
+        | WatchMask::DELETE
+        | WatchMask::CLOSE_WRITE
+        | WatchMask::MOVED_TO
+        | WatchMask::MOVED_FROM;
+    for dir in index.dirs() {
+        let mut pending = vec![dir.clone()];
+        while let Some(current) = pending.pop() {
+            if inotify.watches().add(&current, mask).is_err() {
+                continue;
+            }
+            if let Ok(read_dir) = fs::read_dir(&current) {
+                pending.extend(read_dir.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()));
+            }
+        }
+    }
+
+    let (sender, receiver) = channel();
+    let mut index = index.clone();
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        loop {
+            if let Err(e) = inotify.read_events_blocking(&mut buffer) {
+                warn!("Application directory watcher stopped: {}", e);
+                break;
+            }
+            // Instalações mexem em vários arquivos de uma vez; espera terminarem
+            std::thread::sleep(RESCAN_DELAY);
+            while inotify
+                .read_events(&mut buffer)
+                .is_ok_and(|mut events| events.next().is_some())
+            {}
+
+            index = index.rescan();
+            info!("Applications changed, {} desktop entries indexed", index.len());
+            if sender.send(index.clone()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(DesktopEntryReceiver(Arc::new(Mutex::new(receiver))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn entry(name: &str, exec: &str, extra: &str) -> String {
+        format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n{}", name, exec, extra)
+    }
+
+    /// A user directory and a system one, the user's taking precedence
+    fn fixture() -> (TempDir, Vec<PathBuf>) {
+        let root = TempDir::new().unwrap();
+        let user = root.path().join("home/applications");
+        let system = root.path().join("usr/applications");
+
+        write(&system, "org.mozilla.firefox.desktop", &entry("Firefox", "firefox %u", "StartupWMClass=firefox\n"));
+        write(&system, "org.gnome.Nautilus.desktop", &entry("Files", "nautilus --new-window %U", ""));
+        write(&system, "kde/konsole.desktop", &entry("Konsole", "konsole", ""));
+        write(&system, "code.desktop", &entry("Visual Studio Code", "/usr/share/code/code %F", ""));
+        write(&system, "htop.desktop", &entry("Htop", "htop", "Terminal=true\n"));
+        write(&system, "gnome-only.desktop", &entry("Gnome Only", "gnome-only", "OnlyShowIn=GNOME;\n"));
+        write(&system, "not-hypr.desktop", &entry("Not Hypr", "not-hypr", "NotShowIn=Hyprland;\n"));
+        write(&system, "missing.desktop", &entry("Missing", "missing", "TryExec=definitely-not-installed\n"));
+        write(&system, "steam.desktop", &entry("Steam", "steam %U", ""));
+        write(&system, "README", "not a desktop file");
+
+        // O usuário sobrescreve o Code e esconde o Steam
+        write(&user, "code.desktop", &entry("Code (custom)", "code --ozone-platform=wayland %F", ""));
+        write(&user, "steam.desktop", "[Desktop Entry]\nHidden=true\n");
+
+        (root, vec![user, system])
+    }
+
+    fn index() -> (TempDir, DesktopEntryIndex) {
+        let (root, dirs) = fixture();
+        let context = DesktopContext {
+            desktops: vec!["Hyprland".to_string()],
+            locale: None,
+            path: Vec::new(),
+        };
+        (root, DesktopEntryIndex::build(dirs, context))
+    }
+
+    fn exec_for(index: &DesktopEntryIndex, class: &str) -> Option<String> {
+        index.lookup(class).and_then(|entry| entry.exec.clone())
+    }
+
+    #[test]
+    fn test_lookup_by_id_class_executable_and_name() {
+        let (_root, index) = index();
+
+        assert_eq!(exec_for(&index, "org.gnome.Nautilus").as_deref(), Some("nautilus --new-window %U"));
+        assert_eq!(exec_for(&index, "org.mozilla.firefox.desktop").as_deref(), Some("firefox %u"));
+        assert_eq!(exec_for(&index, "Firefox").as_deref(), Some("firefox %u"));
+        assert_eq!(exec_for(&index, "nautilus").as_deref(), Some("nautilus --new-window %U"));
+        assert_eq!(exec_for(&index, "files").as_deref(), Some("nautilus --new-window %U"));
+        assert_eq!(index.lookup("kde-konsole").unwrap().name, "Konsole");
+        assert!(index.lookup("htop").unwrap().terminal);
+        assert!(index.lookup("unknown").is_none());
+    }
+
+    #[test]
+    fn test_user_entries_override_and_hide_system_ones() {
+        let (_root, index) = index();
+
+        assert_eq!(index.lookup("code").unwrap().name, "Code (custom)");
+        assert!(index.lookup("steam").is_none());
+    }
+
+    #[test]
+    fn test_desktop_filters_and_try_exec() {
+        let (_root, index) = index();
+
+        assert!(index.lookup("gnome-only").is_none());
+        assert!(index.lookup("not-hypr").is_none());
+        assert!(index.lookup("missing").is_none());
+        assert_eq!(index.len(), 5);
+    }
+
+    #[test]
+    fn test_rescan_picks_up_new_entries() {
+        let (root, index) = index();
+        assert!(index.lookup("kitty").is_none());
+
+        write(&root.path().join("home/applications"), "kitty.desktop", &entry("kitty", "kitty", ""));
+
+        assert_eq!(exec_for(&index.rescan(), "kitty").as_deref(), Some("kitty"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Which terminal emulator runs `Terminal=true` applications, and how
+//!
+//! Terminals disagree on how they take the command to run: kitty and foot
+//! take it as their last arguments, most others want `-e` in front of it,
+//! and wezterm needs `start --`.
+
+use std::path::{Path, PathBuf};
+
+use super::find_program;
+use crate::config::TerminalConfig;
+
+/// Terminals tried in order when `terminal.program` is `auto` and `$TERMINAL` isn't usable
+const AUTO_TERMINALS: [&str; 4] = ["kitty", "alacritty", "foot", "wezterm"];
+
+/// Arguments `program` needs before the command it should run, for terminals we know
+fn known_args(program: &str) -> Option<&'static [&'static str]> {
+    let name = Path::new(program).file_name()?.to_str()?;
+    Some(match name {
+        "kitty" | "foot" | "footclient" => &[],
+        "alacritty" | "konsole" | "xterm" | "urxvt" | "st" | "ghostty" => &["-e"],
+        "wezterm" => &["start", "--"],
+        "gnome-terminal" | "kgx" => &["--"],
+        _ => return None,
+    })
+}
+
+/// The terminal and arguments to put in front of a command, read from the environment
+///
+/// `None` when `program` is `auto` and none of the usual terminals is installed.
+pub fn terminal_command(config: &TerminalConfig) -> Option<Vec<String>> {
+    let path: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    let terminal = std::env::var("TERMINAL").ok();
+    terminal_command_from(config, terminal.as_deref(), |program| find_program(program, &path))
+}
+
+/// `terminal_command` with `$TERMINAL` and the `PATH` lookup passed in
+pub fn terminal_command_from(
+    config: &TerminalConfig,
+    terminal_env: Option<&str>,
+    finds: impl Fn(&str) -> bool,
+) -> Option<Vec<String>> {
+    let mut command: Vec<String> = if config.program.trim() == "auto" {
+        let from_env = terminal_env
+            .map(|terminal| terminal.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            .filter(|words| words.first().is_some_and(|program| finds(program)));
+        match from_env {
+            Some(words) => words,
+            None => vec![AUTO_TERMINALS
+                .iter()
+                .find(|program| finds(program))?
+                .to_string()],
+        }
+    } else {
+        config.program.split_whitespace().map(str::to_string).collect()
+    };
+
+    let program = command.first()?.clone();
+    if !config.args.is_empty() {
+        command.extend(config.args.iter().cloned());
+    } else {
+        let args = known_args(&program).unwrap_or(&["-e"]);
+        command.extend(args.iter().map(|arg| arg.to_string()));
+    }
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(program: &str, args: &[&str]) -> TerminalConfig {
+        TerminalConfig {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn installed<'a>(programs: &'a [&'a str]) -> impl Fn(&str) -> bool + 'a {
+        move |program| programs.contains(&program)
+    }
+
+    #[test]
+    fn test_auto_picks_first_installed_terminal() {
+        let auto = config("auto", &[]);
+
+        assert_eq!(
+            terminal_command_from(&auto, None, installed(&["foot", "alacritty"])),
+            Some(vec!["alacritty".to_string(), "-e".to_string()])
+        );
+        assert_eq!(
+            terminal_command_from(&auto, None, installed(&["wezterm"])),
+            Some(vec!["wezterm".to_string(), "start".to_string(), "--".to_string()])
+        );
+        assert_eq!(
+            terminal_command_from(&auto, None, installed(&["kitty", "foot"])),
+            Some(vec!["kitty".to_string()])
+        );
+        assert_eq!(terminal_command_from(&auto, None, installed(&["xterm"])), None);
+    }
+
+    #[test]
+    fn test_auto_prefers_terminal_variable() {
+        let auto = config("auto", &[]);
+
+        assert_eq!(
+            terminal_command_from(&auto, Some("foot"), installed(&["kitty", "foot"])),
+            Some(vec!["foot".to_string()])
+        );
+        // $TERMINAL aponta para algo que não existe: cai na lista
+        assert_eq!(
+            terminal_command_from(&auto, Some("urxvt"), installed(&["kitty"])),
+            Some(vec!["kitty".to_string()])
+        );
+        assert_eq!(
+            terminal_command_from(&auto, Some("xterm -fa Mono"), installed(&["xterm"])),
+            Some(["xterm", "-fa", "Mono", "-e"].map(str::to_string).to_vec())
+        );
+    }
+
+    #[test]
+    fn test_explicit_program_and_args() {
+        let nothing = installed(&[]);
+
+        assert_eq!(
+            terminal_command_from(&config("/usr/bin/ghostty", &[]), None, &nothing),
+            Some(vec!["/usr/bin/ghostty".to_string(), "-e".to_string()])
+        );
+        assert_eq!(
+            terminal_command_from(&config("mystery-term", &[]), None, &nothing),
+            Some(vec!["mystery-term".to_string(), "-e".to_string()])
+        );
+        assert_eq!(
+            terminal_command_from(&config("kitty --single-instance", &["--hold"]), None, &nothing),
+            Some(["kitty", "--single-instance", "--hold"].map(str::to_string).to_vec())
+        );
+    }
+}
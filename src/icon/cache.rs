@@ -4,7 +4,7 @@ use std::time::{Duration, Instant};
 use std::fs;
 use std::io::Write;
 
-use bevy::prelude::Handle;
+use bevy::prelude::{Handle, Resource};
 use bevy::render::texture::Image;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
@@ -175,6 +175,7 @@ impl Default for CacheConfig {
 }
 
 /// Icon cache with LRU memory cache and persistent storage
+#[derive(Resource)]
 pub struct IconCache {
     /// In-memory LRU cache of loaded icons
     memory_cache: LruCache<String, CachedIcon>,
@@ -261,8 +262,12 @@ impl IconCache {
     /// Store an icon in the cache
     pub fn store(&mut self, class: String, icon: CachedIcon) {
         // Store in memory cache
-        if let Some(_evicted) = self.memory_cache.put(class.clone(), icon.clone()) {
-            self.metrics.evictions += 1;
+        // `push` also hands back the old value when the key is replaced,
+        // which is not an eviction
+        if let Some((evicted_class, _)) = self.memory_cache.push(class.clone(), icon.clone()) {
+            if evicted_class != class {
+                self.metrics.evictions += 1;
+            }
         }
 
         // Store in persistent cache if enabled
@@ -291,21 +296,17 @@ impl IconCache {
     /// Remove an entry from all caches
     pub fn remove(&mut self, class: &str) {
         self.memory_cache.pop(class);
-        if self.config.enable_persistent_cache {
-            if self.persistent_cache.remove(class).is_some() {
-                self.persistent_dirty = true;
-            }
+        if self.config.enable_persistent_cache && self.persistent_cache.remove(class).is_some() {
+            self.persistent_dirty = true;
         }
     }
 
     /// Clear all caches
     pub fn clear(&mut self) {
         self.memory_cache.clear();
-        if self.config.enable_persistent_cache {
-            if !self.persistent_cache.is_empty() {
-                self.persistent_cache.clear();
-                self.persistent_dirty = true;
-            }
+        if self.config.enable_persistent_cache && !self.persistent_cache.is_empty() {
+            self.persistent_cache.clear();
+            self.persistent_dirty = true;
         }
         self.metrics.reset();
     }
@@ -433,6 +434,12 @@ impl IconCache {
     }
 }
 
+impl Default for IconCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for IconCache {
     fn drop(&mut self) {
         // Save persistent cache on drop
@@ -4,9 +4,7 @@ pub mod cache;
 pub mod resolver;
 pub mod strategies;
 
-// Re-export main types and traits for when they're needed
+// Re-export the types the rest of the dock uses
 pub use types::*;
-pub use traits::*;
 pub use cache::*;
-pub use resolver::*;
-pub use strategies::*;
\ No newline at end of file
+pub use resolver::*;
\ No newline at end of file
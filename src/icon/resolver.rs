@@ -1,15 +1,19 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use bevy::prelude::Resource;
 use tracing::{debug, info, warn, error};
 
 use crate::icon::types::{IconContext, IconResult, IconError};
 use crate::icon::traits::{IconDetectionStrategy, StrategyProvider};
+use crate::icon::strategies::{DirectoryStrategy, HyprlandStrategy, MappingStrategy};
 
 /// Manages and executes icon detection strategies in priority order
 /// 
 /// The IconResolver maintains a collection of strategies and executes them
 /// in priority order until one successfully finds an icon. It provides
 /// strategy registration, priority ordering, and fallback logic.
+#[derive(Resource)]
 pub struct IconResolver {
     /// Registered strategies, sorted by priority (highest first)
     strategies: Arc<RwLock<Vec<Box<dyn IconDetectionStrategy>>>>,
@@ -46,6 +50,28 @@ impl IconResolver {
         }
     }
 
+    /// Create an IconResolver with the built-in strategies registered
+    ///
+    /// Registers `HyprlandStrategy`, `MappingStrategy` and `DirectoryStrategy`;
+    /// they are ordered by their own priorities. Strategies that fail to
+    /// register are logged and skipped.
+    pub fn with_default_strategies() -> Self {
+        let mut resolver = Self::new();
+        let strategies: Vec<Box<dyn IconDetectionStrategy>> = vec![
+            Box::new(HyprlandStrategy::new()),
+            Box::new(MappingStrategy::new()),
+            Box::new(DirectoryStrategy::new()),
+        ];
+
+        for strategy in strategies {
+            if let Err(e) = resolver.register_strategy(strategy) {
+                warn!("Failed to register default strategy: {}", e);
+            }
+        }
+
+        resolver
+    }
+
     /// Register a single strategy
     /// 
     /// The strategy will be inserted in the correct position based on its priority.
@@ -192,6 +218,35 @@ impl IconResolver {
     /// Executes strategies in priority order until one returns a successful result.
     /// Returns the first successful result, or None if all strategies fail.
     pub fn resolve(&self, context: &IconContext) -> Option<IconResult> {
+        self.resolve_matching(context, |_| true)
+    }
+
+    /// Resolve an icon to a file on disk
+    ///
+    /// Some strategies (e.g. `MappingStrategy`) answer with an icon name rather
+    /// than a file. Such a name is resolved again as a class of its own, and if
+    /// that fails the original context is retried accepting only real files.
+    pub fn resolve_path(&self, context: &IconContext) -> Option<PathBuf> {
+        let result = self.resolve(context)?;
+        if result.path.is_absolute() {
+            return Some(result.path);
+        }
+
+        let alias = result.path.to_string_lossy().to_string();
+        debug!("Resolving icon name '{}' returned by '{}' for class '{}'",
+               alias, result.strategy_used, context.class);
+
+        self.resolve_matching(&IconContext::new(alias), |r| r.path.is_absolute())
+            .or_else(|| self.resolve_matching(context, |r| r.path.is_absolute()))
+            .map(|r| r.path)
+    }
+
+    /// Execute strategies in priority order, returning the first result accepted by `accept`
+    fn resolve_matching(
+        &self,
+        context: &IconContext,
+        accept: impl Fn(&IconResult) -> bool,
+    ) -> Option<IconResult> {
         let strategies = match self.strategies.read() {
             Ok(s) => s,
             Err(e) => {
@@ -220,7 +275,7 @@ impl IconResolver {
             debug!("Trying strategy '{}' for class '{}'", strategy_name, context.class);
             
             let start_time = std::time::Instant::now();
-            let result = strategy.detect_icon(context);
+            let result = strategy.detect_icon(context).filter(|r| accept(r));
             let execution_time = start_time.elapsed();
 
             // Update statistics
@@ -500,26 +555,61 @@ mod tests {
         assert_eq!(stats.get("fail").unwrap().1, 0); // successes
     }
 
-    // Mock provider for testing
-    struct MockProvider {
-        strategies: Vec<Box<dyn IconDetectionStrategy>>,
-    }
+    // Mock strategy that answers with an icon name instead of a file, like MappingStrategy
+    struct MockAliasStrategy;
 
-    impl MockProvider {
-        fn new() -> Self {
-            Self {
-                strategies: vec![
-                    Box::new(MockStrategy::new("provider_strategy1", 60, true)),
-                    Box::new(MockStrategy::new("provider_strategy2", 40, false)),
-                ],
+    impl IconDetectionStrategy for MockAliasStrategy {
+        fn detect_icon(&self, context: &IconContext) -> Option<IconResult> {
+            if context.class == "org.example.App" {
+                Some(IconResult::new(
+                    PathBuf::from("example-app"),
+                    "alias".to_string(),
+                    0.9,
+                    IconMetadata::new(IconFormat::Other("mapped".to_string())),
+                ))
+            } else {
+                None
             }
         }
+
+        fn priority(&self) -> u8 {
+            90
+        }
+
+        fn name(&self) -> &'static str {
+            "alias"
+        }
+    }
+
+    #[test]
+    fn test_resolve_path_follows_icon_name() {
+        let mut resolver = IconResolver::new();
+
+        resolver.register_strategy(Box::new(MockAliasStrategy)).unwrap();
+        resolver.register_strategy(Box::new(MockStrategy::new("files", 10, true))).unwrap();
+
+        let context = IconContext::new("org.example.App".to_string());
+        let path = resolver.resolve_path(&context);
+
+        assert_eq!(path, Some(PathBuf::from("/mock/example-app.png")));
+    }
+
+    #[test]
+    fn test_resolve_path_ignores_unresolvable_icon_name() {
+        let mut resolver = IconResolver::new();
+
+        resolver.register_strategy(Box::new(MockAliasStrategy)).unwrap();
+
+        let context = IconContext::new("org.example.App".to_string());
+        assert!(resolver.resolve(&context).is_some());
+        assert!(resolver.resolve_path(&context).is_none());
     }
 
+    // Mock provider for testing
+    struct MockProvider;
+
     impl StrategyProvider for MockProvider {
         fn get_strategies(&self) -> Vec<Box<dyn IconDetectionStrategy>> {
-            // Note: In a real implementation, we'd need to clone or recreate strategies
-            // For this test, we'll create new instances
             vec![
                 Box::new(MockStrategy::new("provider_strategy1", 60, true)),
                 Box::new(MockStrategy::new("provider_strategy2", 40, false)),
@@ -534,7 +624,7 @@ mod tests {
     #[test]
     fn test_provider_registration() {
         let mut resolver = IconResolver::new();
-        let provider = Box::new(MockProvider::new());
+        let provider = Box::new(MockProvider);
         
         resolver.register_provider(provider).unwrap();
         
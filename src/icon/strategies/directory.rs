@@ -69,6 +69,7 @@ impl DirectoryStrategy {
     }
 
     /// Create a DirectoryStrategy with custom directories
+    #[cfg(test)]
     pub fn with_directories(directories: Vec<PathBuf>) -> Self {
         let mut strategy = Self::new();
         strategy.search_directories = directories;
@@ -76,33 +77,28 @@ impl DirectoryStrategy {
     }
 
     /// Create a DirectoryStrategy with custom cache TTL
+    #[cfg(test)]
     pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
         self.cache_ttl = ttl;
         self
     }
 
     /// Create a DirectoryStrategy with custom max depth
+    #[cfg(test)]
     pub fn with_max_depth(mut self, max_depth: usize) -> Self {
         self.max_depth = max_depth;
         self
     }
 
-    /// Add additional search directories
-    pub fn add_directory(&mut self, directory: PathBuf) {
-        if !self.search_directories.contains(&directory) {
-            self.search_directories.push(directory);
-        }
-    }
-
     /// Get the default icon search directories
     fn default_search_directories() -> Vec<PathBuf> {
-        let mut directories = Vec::new();
-
         // System-wide icon directories
-        directories.push(PathBuf::from("/usr/share/icons"));
-        directories.push(PathBuf::from("/usr/share/pixmaps"));
-        directories.push(PathBuf::from("/usr/local/share/icons"));
-        directories.push(PathBuf::from("/usr/local/share/pixmaps"));
+        let mut directories = vec![
+            PathBuf::from("/usr/share/icons"),
+            PathBuf::from("/usr/share/pixmaps"),
+            PathBuf::from("/usr/local/share/icons"),
+            PathBuf::from("/usr/local/share/pixmaps"),
+        ];
 
         // User-specific directories
         if let Some(home) = std::env::var_os("HOME") {
@@ -262,7 +258,7 @@ impl DirectoryStrategy {
                 // Check if this is an icon file
                 if let Some(icon_name) = self.extract_icon_name(&path) {
                     icons.entry(icon_name)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(path);
                 }
             }
@@ -301,7 +297,7 @@ impl DirectoryStrategy {
         }
 
         // Replace common separators with hyphens
-        let hyphenated = lowercase_class.replace('_', "-").replace('.', "-");
+        let hyphenated = lowercase_class.replace(['_', '.'], "-");
         if hyphenated != lowercase_class {
             names.push(hyphenated);
         }
@@ -317,6 +313,15 @@ impl DirectoryStrategy {
             names.push(cleaned);
         }
 
+        // Reverse-DNS classes (e.g. "org.gnome.Nautilus") often ship their
+        // icon under the last component only
+        if let Some(last) = lowercase_class.rsplit('.').next() {
+            let last = last.to_string();
+            if !last.is_empty() && !names.contains(&last) {
+                names.push(last);
+            }
+        }
+
         // If we have an executable name, try that too
         if let Some(ref executable) = context.executable {
             let exec_lower = executable.to_lowercase();
@@ -332,7 +337,7 @@ impl DirectoryStrategy {
             
             // Try splitting on common separators and take the first/last part
             for separator in &[" - ", " – ", " | ", ": "] {
-                if let Some(parts) = title_lower.split(separator).collect::<Vec<_>>().get(0) {
+                if let Some(parts) = title_lower.split(separator).next() {
                     let app_name = parts.trim().to_string();
                     if !app_name.is_empty() && !names.contains(&app_name) {
                         names.push(app_name);
@@ -355,6 +360,7 @@ impl DirectoryStrategy {
     }
 
     /// Get cache statistics
+    #[cfg(test)]
     pub fn cache_stats(&self) -> Option<(usize, usize)> {
         if let Ok(cache) = self.directory_cache.read() {
             let total_entries = cache.len();
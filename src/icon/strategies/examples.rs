@@ -1,11 +1,10 @@
-/// Example usage of strategies with IconResolver
-/// 
-/// This module provides examples of how to integrate various strategies
-/// with the icon resolution system.
+//! Example usage of strategies with IconResolver
+//!
+//! This module provides examples of how to integrate various strategies
+//! with the icon resolution system.
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
     use tempfile::TempDir;
     use std::fs;
     
@@ -234,7 +234,7 @@ impl HyprlandStrategy {
 
         // Try using ps command as fallback
         if let Ok(output) = Command::new("ps")
-            .args(&["-p", &pid.to_string(), "-o", "comm="])
+            .args(["-p", &pid.to_string(), "-o", "comm="])
             .output()
         {
             if output.status.success() {
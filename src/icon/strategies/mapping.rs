@@ -30,6 +30,7 @@ impl ApplicationMapper {
     }
 
     /// Create an ApplicationMapper with custom mappings only
+    #[cfg(test)]
     pub fn with_custom_mappings(mappings: HashMap<String, Vec<String>>) -> Self {
         let mut mapper = Self {
             class_mappings: HashMap::new(),
@@ -63,10 +64,11 @@ impl ApplicationMapper {
     }
 
     /// Add a single alias for a window class
+    #[cfg(test)]
     pub fn add_alias(&mut self, class: String, icon_name: String) {
         self.class_mappings
             .entry(class)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(icon_name);
     }
 
@@ -89,20 +91,17 @@ impl ApplicationMapper {
     }
 
     /// Get the preferred class name for an icon name (reverse lookup)
+    #[cfg(test)]
     pub fn get_preferred_class(&self, icon_name: &str) -> Option<&String> {
         self.reverse_mappings.get(icon_name)
     }
 
     /// Check if a class has any mappings
+    #[cfg(test)]
     pub fn has_mapping(&self, class: &str) -> bool {
         self.get_icon_names(class).is_some()
     }
 
-    /// Get all mapped classes
-    pub fn get_all_classes(&self) -> Vec<&String> {
-        self.class_mappings.keys().collect()
-    }
-
     /// Get the number of mappings
     pub fn mapping_count(&self) -> usize {
         self.class_mappings.len()
@@ -293,6 +292,7 @@ impl ApplicationMapper {
     }
 
     /// Merge another mapper's mappings into this one
+    #[cfg(test)]
     pub fn merge(&mut self, other: &ApplicationMapper) {
         for (class, icon_names) in &other.class_mappings {
             // If we already have this class, extend the icon names
@@ -310,6 +310,7 @@ impl ApplicationMapper {
     }
 
     /// Clear all mappings
+    #[cfg(test)]
     pub fn clear(&mut self) {
         self.class_mappings.clear();
         self.reverse_mappings.clear();
@@ -343,15 +344,8 @@ impl MappingStrategy {
         }
     }
 
-    /// Create a MappingStrategy with a custom mapper
-    pub fn with_mapper(mapper: ApplicationMapper) -> Self {
-        Self {
-            mapper: Arc::new(RwLock::new(mapper)),
-            fuzzy_matching: true,
-        }
-    }
-
     /// Create a MappingStrategy with custom mappings
+    #[cfg(test)]
     pub fn with_mappings(mappings: HashMap<String, Vec<String>>) -> Self {
         Self {
             mapper: Arc::new(RwLock::new(ApplicationMapper::with_custom_mappings(mappings))),
@@ -360,12 +354,14 @@ impl MappingStrategy {
     }
 
     /// Enable or disable fuzzy matching
+    #[cfg(test)]
     pub fn with_fuzzy_matching(mut self, enabled: bool) -> Self {
         self.fuzzy_matching = enabled;
         self
     }
 
     /// Add a mapping to the strategy
+    #[cfg(test)]
     pub fn add_mapping(&self, class: String, icon_names: Vec<String>) -> Result<(), String> {
         match self.mapper.write() {
             Ok(mut mapper) => {
@@ -377,6 +373,7 @@ impl MappingStrategy {
     }
 
     /// Add an alias for a class
+    #[cfg(test)]
     pub fn add_alias(&self, class: String, icon_name: String) -> Result<(), String> {
         match self.mapper.write() {
             Ok(mut mapper) => {
@@ -388,7 +385,8 @@ impl MappingStrategy {
     }
 
     /// Get the current mapper (read-only access)
-    pub fn get_mapper(&self) -> Result<std::sync::RwLockReadGuard<ApplicationMapper>, String> {
+    #[cfg(test)]
+    pub fn get_mapper(&self) -> Result<std::sync::RwLockReadGuard<'_, ApplicationMapper>, String> {
         self.mapper.read().map_err(|e| format!("Failed to acquire read lock: {}", e))
     }
 
@@ -418,15 +416,11 @@ impl MappingStrategy {
             .trim_start_matches("com.")
             .trim_start_matches("net.")
             .trim_end_matches(".desktop")
-            .replace('-', "")
-            .replace('_', "")
-            .replace('.', "");
+            .replace(['-', '_', '.'], "");
 
         for (mapped_class, icon_names) in mapper.class_mappings.iter() {
             let cleaned_mapped = mapped_class.to_lowercase()
-                .replace('-', "")
-                .replace('_', "")
-                .replace('.', "");
+                .replace(['-', '_', '.'], "");
             
             if cleaned_class == cleaned_mapped {
                 debug!("MappingStrategy: Cleaned fuzzy match '{}' -> '{}' -> {:?}", 
@@ -6,5 +6,5 @@ pub mod hyprland;
 mod examples;
 
 pub use directory::DirectoryStrategy;
-pub use mapping::{MappingStrategy, ApplicationMapper};
+pub use mapping::MappingStrategy;
 pub use hyprland::HyprlandStrategy;
\ No newline at end of file
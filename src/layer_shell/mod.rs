@@ -0,0 +1,478 @@
+//! Running the dock as a `zwlr_layer_shell_v1` panel instead of a winit window
+//!
+//! winit can't create layer surfaces, so `LayerShellPlugin` replaces it: the
+//! panel's `wl_surface` is handed to Bevy's renderer as the primary window,
+//! and its pointer and keyboard input is turned into Bevy's input events.
+
+mod surface;
+
+pub use surface::{LayerShellError, LayerSurface, PanelEvent};
+
+use bevy::app::{AppExit, PluginsState};
+use bevy::ecs::event::ManualEventReader;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel};
+use bevy::input::ButtonState;
+use bevy::math::{DVec2, IRect};
+use bevy::prelude::*;
+use bevy::window::{
+    CompositeAlphaMode, CursorEntered, CursorLeft, CursorMoved, PrimaryWindow, WindowResized,
+    WindowResolution,
+};
+use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, LayerConfig};
+use crate::layout::dock_layout;
+use crate::systems::{reveal_strip, update_dock_visibility, ContextMenu};
+use crate::types::{ClientIcon, DockSet, DockVisibility};
+
+/// Frames are paced to this rate on top of the compositor's vsync
+const FRAME_TIME: Duration = Duration::from_micros(16_667);
+/// Room around each icon that still takes input, for the hover lift and scale
+const ICON_INPUT_MARGIN: f32 = 1.4;
+/// Scroll distance of one wheel notch, turned into one line of `MouseWheel`
+const SCROLL_LINE: f64 = 10.0;
+
+/// Shows the dock on a `LayerSurface`, as the app's primary window
+///
+/// Use in place of `WinitPlugin`, with `WindowPlugin::primary_window` set to `None`.
+pub struct LayerShellPlugin {
+    surface: LayerSurface,
+    events: Mutex<Option<Receiver<PanelEvent>>>,
+}
+
+impl LayerShellPlugin {
+    pub fn new(surface: LayerSurface, events: Receiver<PanelEvent>) -> Self {
+        Self {
+            surface,
+            events: Mutex::new(Some(events)),
+        }
+    }
+}
+
+/// The panel the dock is shown on
+#[derive(Resource, Clone)]
+pub struct Panel(pub LayerSurface);
+
+impl Plugin for LayerShellPlugin {
+    fn build(&self, app: &mut App) {
+        let (width, height) = self.surface.size();
+        let window = app
+            .world
+            .spawn((
+                Window {
+                    title: "anny-dock".to_string(),
+                    resolution: WindowResolution::new(width as f32, height as f32),
+                    transparent: true,
+                    decorations: false,
+                    composite_alpha_mode: CompositeAlphaMode::PreMultiplied,
+                    ..default()
+                },
+                PrimaryWindow,
+                self.surface.raw_handle(),
+            ))
+            .id();
+
+        let events = self
+            .events
+            .lock()
+            .unwrap()
+            .take()
+            .expect("LayerShellPlugin can only be added once");
+
+        app.insert_resource(Panel(self.surface.clone()))
+            .add_systems(
+                Update,
+                (apply_layer_config, update_input_region).in_set(DockSet::Visuals),
+            )
+            .add_systems(
+                Update,
+                follow_window_visible
+                    .in_set(DockSet::Hover)
+                    .before(update_dock_visibility),
+            )
+            .set_runner(move |app| run_panel(app, window, events));
+    }
+}
+
+/// Like Bevy's `ScheduleRunnerPlugin`, feeding panel events in before every frame
+fn run_panel(mut app: App, window: Entity, events: Receiver<PanelEvent>) {
+    if app.plugins_state() != PluginsState::Cleaned {
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+    }
+
+    let mut exit_reader = ManualEventReader::<AppExit>::default();
+    loop {
+        let start = Instant::now();
+
+        for event in events.try_iter() {
+            apply_panel_event(&mut app.world, window, event);
+        }
+        app.update();
+
+        if let Some(exit_events) = app.world.get_resource::<Events<AppExit>>() {
+            if exit_reader.read(exit_events).last().is_some() {
+                break;
+            }
+        }
+
+        if let Some(rest) = FRAME_TIME.checked_sub(start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+}
+
+/// Turn one panel event into the Bevy events winit would have sent
+fn apply_panel_event(world: &mut World, window: Entity, event: PanelEvent) {
+    match event {
+        PanelEvent::Resized { width, height } => {
+            if let Some(mut primary) = world.get_mut::<Window>(window) {
+                primary.resolution.set(width as f32, height as f32);
+            }
+            world.send_event(WindowResized {
+                window,
+                width: width as f32,
+                height: height as f32,
+            });
+        }
+        PanelEvent::PointerEntered { x, y } => {
+            world.send_event(CursorEntered { window });
+            move_cursor(world, window, x, y);
+        }
+        PanelEvent::PointerMoved { x, y } => move_cursor(world, window, x, y),
+        PanelEvent::PointerLeft => {
+            if let Some(mut primary) = world.get_mut::<Window>(window) {
+                primary.set_physical_cursor_position(None);
+            }
+            world.send_event(CursorLeft { window });
+        }
+        PanelEvent::Button { button, pressed } => {
+            world.send_event(MouseButtonInput {
+                button: mouse_button(button),
+                state: button_state(pressed),
+                window,
+            });
+        }
+        PanelEvent::Scroll { x, y } => {
+            world.send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: (-x / SCROLL_LINE) as f32,
+                y: (-y / SCROLL_LINE) as f32,
+                window,
+            });
+        }
+        PanelEvent::Key { key, pressed } => {
+            world.send_event(KeyboardInput {
+                scan_code: key,
+                key_code: key_code(key),
+                state: button_state(pressed),
+                window,
+            });
+        }
+        PanelEvent::Closed => {
+            info!("Compositor closed the dock's panel");
+            world.send_event(AppExit);
+        }
+    }
+}
+
+fn move_cursor(world: &mut World, window: Entity, x: f64, y: f64) {
+    let Some(mut primary) = world.get_mut::<Window>(window) else {
+        return;
+    };
+    primary.set_physical_cursor_position(Some(DVec2::new(x, y)));
+    let position = Vec2::new(x as f32, y as f32);
+    world.send_event(CursorMoved { window, position });
+}
+
+fn button_state(pressed: bool) -> ButtonState {
+    if pressed {
+        ButtonState::Pressed
+    } else {
+        ButtonState::Released
+    }
+}
+
+/// Linux `BTN_*` codes from `input-event-codes.h`
+fn mouse_button(button: u32) -> MouseButton {
+    match button {
+        0x110 => MouseButton::Left,
+        0x111 => MouseButton::Right,
+        0x112 => MouseButton::Middle,
+        other => MouseButton::Other(other as u16),
+    }
+}
+
+/// Linux `KEY_*` codes from `input-event-codes.h`, by position on a US layout
+///
+/// Covers the keys the dock binds plus letters and digits; the panel doesn't
+/// load the compositor's keymap, so other layouts map by key position.
+fn key_code(key: u32) -> Option<KeyCode> {
+    const LETTERS: [(u32, KeyCode); 26] = [
+        (30, KeyCode::A),
+        (48, KeyCode::B),
+        (46, KeyCode::C),
+        (32, KeyCode::D),
+        (18, KeyCode::E),
+        (33, KeyCode::F),
+        (34, KeyCode::G),
+        (35, KeyCode::H),
+        (23, KeyCode::I),
+        (36, KeyCode::J),
+        (37, KeyCode::K),
+        (38, KeyCode::L),
+        (50, KeyCode::M),
+        (49, KeyCode::N),
+        (24, KeyCode::O),
+        (25, KeyCode::P),
+        (16, KeyCode::Q),
+        (19, KeyCode::R),
+        (31, KeyCode::S),
+        (20, KeyCode::T),
+        (22, KeyCode::U),
+        (47, KeyCode::V),
+        (17, KeyCode::W),
+        (45, KeyCode::X),
+        (21, KeyCode::Y),
+        (44, KeyCode::Z),
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+        KeyCode::Key0,
+    ];
+
+    match key {
+        1 => Some(KeyCode::Escape),
+        2..=11 => Some(DIGITS[(key - 2) as usize]),
+        14 => Some(KeyCode::Back),
+        15 => Some(KeyCode::Tab),
+        28 => Some(KeyCode::Return),
+        57 => Some(KeyCode::Space),
+        103 => Some(KeyCode::Up),
+        105 => Some(KeyCode::Left),
+        106 => Some(KeyCode::Right),
+        108 => Some(KeyCode::Down),
+        _ => LETTERS
+            .iter()
+            .find(|(code, _)| *code == key)
+            .map(|(_, key_code)| *key_code),
+    }
+}
+
+/// Re-apply edge, layer and the rest when `config.toml` changes them
+fn apply_layer_config(
+    config: Res<Config>,
+    panel: Res<Panel>,
+    mut applied: Local<Option<LayerConfig>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    if applied.as_ref() == Some(&config.layer) {
+        return;
+    }
+    if applied.is_some() {
+        panel.0.configure(&config.layer);
+    }
+    *applied = Some(config.layer.clone());
+}
+
+/// Hide the dock while `Window::visible` is false, where winit would unmap the window
+///
+/// The panel itself stays mapped, as the renderer keeps presenting to it; the
+/// icons slide out as with `anny-dock hide` instead, and come back once it is true.
+fn follow_window_visible(
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut visibility: ResMut<DockVisibility>,
+    mut shown: Local<Option<bool>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    if shown.unwrap_or(true) != window.visible {
+        visibility.hold_hidden(!window.visible);
+    }
+    *shown = Some(window.visible);
+}
+
+/// Limit pointer input to the icons, so the transparent rest of the panel is click-through
+///
+/// While the dock is hidden, the strip along its edge takes input too, so the
+/// pointer reaching the edge can bring it back, unless `anny-dock hide` hid it. An open context menu takes
+/// input over its whole area.
+fn update_input_region(
+    panel: Res<Panel>,
+    config: Res<Config>,
+    visibility: Res<DockVisibility>,
+    menu: Res<ContextMenu>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_icons: Query<&Transform, With<ClientIcon>>,
+    mut applied: Local<Vec<IRect>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width(), window.height());
+    let mut rects = icon_input_rects(window_size, config.icon_size, q_icons.iter());
+    if visibility.progress > 0.0 && !visibility.forced {
+        let edge = dock_layout(&config, window).edge();
+        rects.push(reveal_strip(edge, window_size, config.visibility.reveal_distance));
+    }
+    if menu.is_open() {
+        rects.push(surface_rect(window_size, menu.rect));
+    }
+    if rects != *applied {
+        panel.0.set_input_region(&rects);
+        *applied = rects;
+    }
+}
+
+/// Surface rects around each icon, from world space where the origin is the window center
+fn icon_input_rects<'a>(
+    window_size: Vec2,
+    icon_size: f32,
+    transforms: impl Iterator<Item = &'a Transform>,
+) -> Vec<IRect> {
+    let mut rects: Vec<IRect> = transforms
+        .map(|transform| {
+            let center = Vec2::new(
+                window_size.x / 2.0 + transform.translation.x,
+                window_size.y / 2.0 - transform.translation.y,
+            );
+            let half = icon_size * transform.scale.x * ICON_INPUT_MARGIN / 2.0;
+            IRect::from_corners(
+                (center - half).floor().as_ivec2(),
+                (center + half).ceil().as_ivec2(),
+            )
+        })
+        .collect();
+    rects.sort_by_key(|rect| (rect.min.x, rect.min.y));
+    rects
+}
+
+/// `rect` in world space, where the origin is the window center, as a surface rect
+fn surface_rect(window_size: Vec2, rect: Rect) -> IRect {
+    let min = Vec2::new(window_size.x / 2.0 + rect.min.x, window_size.y / 2.0 - rect.max.y);
+    let max = Vec2::new(window_size.x / 2.0 + rect.max.x, window_size.y / 2.0 - rect.min.y);
+    IRect::from_corners(min.floor().as_ivec2(), max.ceil().as_ivec2())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_input_rects_use_surface_coordinates() {
+        let transform = Transform::from_xyz(-100.0, -50.0, 0.0).with_scale(Vec3::splat(1.0));
+
+        let rects = icon_input_rects(Vec2::new(400.0, 200.0), 50.0, [transform].iter());
+
+        // Centro em (100, 150) na superfície, com 35px de cada lado
+        assert_eq!(rects, vec![IRect::new(65, 115, 135, 185)]);
+    }
+
+    #[test]
+    fn test_surface_rect_flips_y() {
+        let rect = Rect::new(-120.0, 20.0, 120.0, 80.0);
+
+        assert_eq!(
+            surface_rect(Vec2::new(400.0, 200.0), rect),
+            IRect::new(80, 20, 320, 80)
+        );
+    }
+
+    #[test]
+    fn test_window_visible_holds_the_dock_hidden() {
+        let mut app = App::new();
+        app.init_resource::<DockVisibility>()
+            .add_systems(Update, follow_window_visible);
+        let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+
+        app.update();
+        assert!(!app.world.resource::<DockVisibility>().forced);
+
+        app.world.get_mut::<Window>(window).unwrap().visible = false;
+        app.update();
+        let visibility = app.world.resource::<DockVisibility>();
+        assert!(visibility.forced && visibility.hidden);
+
+        // Outras mudanças na janela não mexem em um `hide` vindo de outro lugar
+        app.world.get_mut::<Window>(window).unwrap().visible = true;
+        app.update();
+        app.world.resource_mut::<DockVisibility>().hold_hidden(true);
+        app.world.get_mut::<Window>(window).unwrap().title = "moved".to_string();
+        app.update();
+        assert!(app.world.resource::<DockVisibility>().forced);
+    }
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(key_code(1), Some(KeyCode::Escape));
+        assert_eq!(key_code(2), Some(KeyCode::Key1));
+        assert_eq!(key_code(11), Some(KeyCode::Key0));
+        assert_eq!(key_code(16), Some(KeyCode::Q));
+        assert_eq!(key_code(20), Some(KeyCode::T));
+        assert_eq!(key_code(105), Some(KeyCode::Left));
+        assert_eq!(key_code(999), None);
+    }
+
+    #[test]
+    fn test_panel_events_become_bevy_input() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::input::InputPlugin,
+            bevy::window::WindowPlugin {
+                primary_window: None,
+                ..default()
+            },
+        ));
+        let window = app.world.spawn(Window::default()).id();
+
+        apply_panel_event(
+            &mut app.world,
+            window,
+            PanelEvent::PointerEntered { x: 10.0, y: 20.0 },
+        );
+        apply_panel_event(
+            &mut app.world,
+            window,
+            PanelEvent::Button {
+                button: 0x111,
+                pressed: true,
+            },
+        );
+        apply_panel_event(
+            &mut app.world,
+            window,
+            PanelEvent::Key {
+                key: 20,
+                pressed: true,
+            },
+        );
+        app.update();
+
+        let primary = app.world.get::<Window>(window).unwrap();
+        assert_eq!(primary.cursor_position(), Some(Vec2::new(10.0, 20.0)));
+        assert!(app
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Right));
+        assert!(app.world.resource::<Input<KeyCode>>().pressed(KeyCode::T));
+    }
+}
@@ -0,0 +1,547 @@
+use bevy::log::warn;
+use bevy::math::IRect;
+use bevy::window::RawHandleWrapper;
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+};
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use thiserror::Error;
+
+use wayland_client::globals::{registry_queue_init, GlobalList, GlobalListContents};
+use wayland_client::protocol::{
+    wl_compositor::WlCompositor,
+    wl_keyboard::{self, WlKeyboard},
+    wl_output::{self, WlOutput},
+    wl_pointer::{self, WlPointer},
+    wl_region::WlRegion,
+    wl_registry::WlRegistry,
+    wl_seat::{self, WlSeat},
+    wl_surface::WlSurface,
+};
+use wayland_client::{Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum};
+use wayland_protocols_wlr::layer_shell::v1::client::{
+    zwlr_layer_shell_v1::{self, ZwlrLayerShellV1},
+    zwlr_layer_surface_v1::{self, Anchor, ZwlrLayerSurfaceV1},
+};
+
+use crate::config::{Edge, KeyboardInteractivity, Layer, LayerConfig};
+
+/// Used when the compositor leaves a dimension up to the dock
+const FALLBACK_LENGTH: u32 = 1280;
+
+#[derive(Debug, Error)]
+pub enum LayerShellError {
+    #[error("no Wayland compositor: {0}")]
+    Connect(#[from] wayland_client::ConnectError),
+    #[error("compositor doesn't support {0}")]
+    Unsupported(&'static str),
+    #[error("Wayland protocol error: {0}")]
+    Protocol(String),
+    #[error("compositor closed the panel before showing it")]
+    Closed,
+}
+
+/// What happened to the panel, in surface coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum PanelEvent {
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    PointerEntered {
+        x: f64,
+        y: f64,
+    },
+    PointerMoved {
+        x: f64,
+        y: f64,
+    },
+    PointerLeft,
+    /// A Linux button code, e.g. `BTN_LEFT`
+    Button {
+        button: u32,
+        pressed: bool,
+    },
+    /// Scroll distance along each axis, positive towards the bottom right
+    Scroll {
+        x: f64,
+        y: f64,
+    },
+    /// A Linux key code, e.g. `KEY_ESC`
+    Key {
+        key: u32,
+        pressed: bool,
+    },
+    Closed,
+}
+
+/// Wayland event queue state, living on the dispatch thread
+struct PanelState {
+    sender: Sender<PanelEvent>,
+    size: Option<(u32, u32)>,
+    closed: bool,
+    pointer: Option<WlPointer>,
+    keyboard: Option<WlKeyboard>,
+    /// Keys held down, released when the panel loses keyboard focus
+    pressed_keys: HashSet<u32>,
+    /// Outputs with the connector name they announced
+    outputs: Vec<(WlOutput, String)>,
+}
+
+impl PanelState {
+    fn send(&self, event: PanelEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+/// The dock's `zwlr_layer_shell_v1` panel
+///
+/// Owns the `wl_surface` Bevy renders to; input on it comes back as
+/// `PanelEvent`s from a dispatch thread.
+#[derive(Clone)]
+pub struct LayerSurface {
+    connection: Connection,
+    compositor: WlCompositor,
+    surface: WlSurface,
+    layer_surface: ZwlrLayerSurfaceV1,
+    qh: QueueHandle<PanelState>,
+    size: (u32, u32),
+}
+
+impl LayerSurface {
+    /// Create the panel on `output`, by connector name, and wait until the compositor gives it a size
+    ///
+    /// With no `output`, or one the compositor doesn't know, it picks the
+    /// monitor itself, usually the focused one.
+    pub fn create(
+        config: &LayerConfig,
+        output: Option<&str>,
+    ) -> Result<(Self, Receiver<PanelEvent>), LayerShellError> {
+        let connection = Connection::connect_to_env()?;
+        let (globals, mut queue) = registry_queue_init::<PanelState>(&connection)
+            .map_err(|e| LayerShellError::Protocol(e.to_string()))?;
+        let qh = queue.handle();
+
+        let compositor: WlCompositor = globals
+            .bind(&qh, 1..=4, ())
+            .map_err(|_| LayerShellError::Unsupported("wl_compositor"))?;
+        let layer_shell: ZwlrLayerShellV1 = globals
+            .bind(&qh, 1..=4, ())
+            .map_err(|_| LayerShellError::Unsupported("wlr-layer-shell"))?;
+        // Sem seat o painel ainda aparece, só não recebe input
+        let seat: Option<WlSeat> = globals.bind(&qh, 1..=7, ()).ok();
+
+        let (sender, receiver) = channel();
+        let mut state = PanelState {
+            sender,
+            size: None,
+            closed: false,
+            pointer: None,
+            keyboard: None,
+            pressed_keys: HashSet::new(),
+            outputs: Vec::new(),
+        };
+
+        let wl_output = match output {
+            Some(name) => find_output(&globals, &mut queue, &mut state, name)?,
+            None => None,
+        };
+
+        let surface = compositor.create_surface(&qh, ());
+        let layer_surface = layer_shell.get_layer_surface(
+            &surface,
+            wl_output.as_ref(),
+            shell_layer(config.layer),
+            "anny-dock".to_string(),
+            &qh,
+            (),
+        );
+
+        let mut panel = Self {
+            connection,
+            compositor,
+            surface,
+            layer_surface,
+            qh,
+            size: (0, 0),
+        };
+        panel.configure(config);
+
+        while state.size.is_none() {
+            if state.closed {
+                return Err(LayerShellError::Closed);
+            }
+            queue
+                .blocking_dispatch(&mut state)
+                .map_err(|e| LayerShellError::Protocol(e.to_string()))?;
+        }
+        panel.size = state.size.unwrap_or_default();
+        // The size is known now, no need to report it again
+        while receiver.try_recv().is_ok() {}
+
+        std::thread::spawn(move || {
+            let _layer_shell = layer_shell;
+            let _seat = seat;
+            loop {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    warn!("Wayland connection closed: {}", e);
+                    state.send(PanelEvent::Closed);
+                    break;
+                }
+            }
+        });
+
+        Ok((panel, receiver))
+    }
+
+    /// Size given by the compositor on the first configure
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Handles Bevy's renderer needs to draw on the panel
+    pub fn raw_handle(&self) -> RawHandleWrapper {
+        let mut window = WaylandWindowHandle::empty();
+        window.surface = self.surface.id().as_ptr().cast();
+        let mut display = WaylandDisplayHandle::empty();
+        display.display = self.connection.backend().display_ptr().cast();
+
+        RawHandleWrapper {
+            window_handle: RawWindowHandle::Wayland(window),
+            display_handle: RawDisplayHandle::Wayland(display),
+        }
+    }
+
+    /// Apply edge, layer, size, exclusive zone and keyboard mode
+    pub fn configure(&self, config: &LayerConfig) {
+        let (anchor, width, height) = match config.edge {
+            Edge::Top => (Anchor::Top | Anchor::Left | Anchor::Right, 0, config.size),
+            Edge::Bottom => (
+                Anchor::Bottom | Anchor::Left | Anchor::Right,
+                0,
+                config.size,
+            ),
+            Edge::Left => (Anchor::Left | Anchor::Top | Anchor::Bottom, config.size, 0),
+            Edge::Right => (Anchor::Right | Anchor::Top | Anchor::Bottom, config.size, 0),
+        };
+
+        let version = self.layer_surface.version();
+        self.layer_surface.set_anchor(anchor);
+        self.layer_surface.set_size(width, height);
+        self.layer_surface.set_exclusive_zone(config.exclusive_zone);
+        if version >= 2 {
+            self.layer_surface.set_layer(shell_layer(config.layer));
+        }
+
+        let keyboard = match config.keyboard_interactivity {
+            KeyboardInteractivity::None => zwlr_layer_surface_v1::KeyboardInteractivity::None,
+            KeyboardInteractivity::Exclusive => {
+                zwlr_layer_surface_v1::KeyboardInteractivity::Exclusive
+            }
+            KeyboardInteractivity::OnDemand if version >= 4 => {
+                zwlr_layer_surface_v1::KeyboardInteractivity::OnDemand
+            }
+            KeyboardInteractivity::OnDemand => {
+                warn!("Compositor doesn't support on-demand keyboard focus, keybinds disabled");
+                zwlr_layer_surface_v1::KeyboardInteractivity::None
+            }
+        };
+        self.layer_surface.set_keyboard_interactivity(keyboard);
+
+        self.surface.commit();
+        self.flush();
+    }
+
+    /// Take pointer input only inside `rects`, letting clicks elsewhere reach the windows below
+    ///
+    /// Applied on the next frame Bevy presents.
+    pub fn set_input_region(&self, rects: &[IRect]) {
+        let region = self.compositor.create_region(&self.qh, ());
+        for rect in rects {
+            region.add(rect.min.x, rect.min.y, rect.width(), rect.height());
+        }
+        self.surface.set_input_region(Some(&region));
+        region.destroy();
+        self.flush();
+    }
+
+    fn flush(&self) {
+        if let Err(e) = self.connection.flush() {
+            warn!("Failed to flush Wayland requests: {}", e);
+        }
+    }
+}
+
+/// Bind every `wl_output` and return the one named `name`
+///
+/// Names arrive with `wl_output` version 4; older compositors get the dock
+/// wherever they put it.
+fn find_output(
+    globals: &GlobalList,
+    queue: &mut EventQueue<PanelState>,
+    state: &mut PanelState,
+    name: &str,
+) -> Result<Option<WlOutput>, LayerShellError> {
+    let qh = queue.handle();
+    globals.contents().with_list(|list| {
+        for global in list.iter().filter(|global| global.interface == WlOutput::interface().name) {
+            let version = global.version.min(4);
+            let _: WlOutput = globals.registry().bind(global.name, version, &qh, ());
+        }
+    });
+    queue
+        .roundtrip(state)
+        .map_err(|e| LayerShellError::Protocol(e.to_string()))?;
+
+    let found = state
+        .outputs
+        .iter()
+        .find(|(_, output_name)| output_name == name)
+        .map(|(output, _)| output.clone());
+    if found.is_none() {
+        warn!("Monitor {} not found, letting the compositor pick one", name);
+    }
+    Ok(found)
+}
+
+fn shell_layer(layer: Layer) -> zwlr_layer_shell_v1::Layer {
+    match layer {
+        Layer::Background => zwlr_layer_shell_v1::Layer::Background,
+        Layer::Bottom => zwlr_layer_shell_v1::Layer::Bottom,
+        Layer::Top => zwlr_layer_shell_v1::Layer::Top,
+        Layer::Overlay => zwlr_layer_shell_v1::Layer::Overlay,
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for PanelState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlCompositor, ()> for PanelState {
+    fn event(
+        _: &mut Self,
+        _: &WlCompositor,
+        _: <WlCompositor as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSurface, ()> for PanelState {
+    fn event(
+        _: &mut Self,
+        _: &WlSurface,
+        _: <WlSurface as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlRegion, ()> for PanelState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegion,
+        _: <WlRegion as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrLayerShellV1, ()> for PanelState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrLayerShellV1,
+        _: zwlr_layer_shell_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrLayerSurfaceV1, ()> for PanelState {
+    fn event(
+        state: &mut Self,
+        layer_surface: &ZwlrLayerSurfaceV1,
+        event: zwlr_layer_surface_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_layer_surface_v1::Event::Configure {
+                serial,
+                width,
+                height,
+            } => {
+                layer_surface.ack_configure(serial);
+                let or_fallback = |length: u32| if length == 0 { FALLBACK_LENGTH } else { length };
+                let size = (or_fallback(width), or_fallback(height));
+                if state.size != Some(size) {
+                    state.size = Some(size);
+                    state.send(PanelEvent::Resized {
+                        width: size.0,
+                        height: size.1,
+                    });
+                }
+            }
+            zwlr_layer_surface_v1::Event::Closed => {
+                state.closed = true;
+                state.send(PanelEvent::Closed);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for PanelState {
+    fn event(
+        state: &mut Self,
+        output: &WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.outputs.push((output.clone(), name));
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for PanelState {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        else {
+            return;
+        };
+
+        let has_pointer = capabilities.contains(wl_seat::Capability::Pointer);
+        if has_pointer && state.pointer.is_none() {
+            state.pointer = Some(seat.get_pointer(qh, ()));
+        } else if !has_pointer {
+            if let Some(pointer) = state.pointer.take() {
+                pointer.release();
+            }
+        }
+
+        let has_keyboard = capabilities.contains(wl_seat::Capability::Keyboard);
+        if has_keyboard && state.keyboard.is_none() {
+            state.keyboard = Some(seat.get_keyboard(qh, ()));
+        } else if !has_keyboard {
+            if let Some(keyboard) = state.keyboard.take() {
+                keyboard.release();
+            }
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for PanelState {
+    fn event(
+        state: &mut Self,
+        _: &WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let event = match event {
+            wl_pointer::Event::Enter {
+                surface_x,
+                surface_y,
+                ..
+            } => PanelEvent::PointerEntered {
+                x: surface_x,
+                y: surface_y,
+            },
+            wl_pointer::Event::Motion {
+                surface_x,
+                surface_y,
+                ..
+            } => PanelEvent::PointerMoved {
+                x: surface_x,
+                y: surface_y,
+            },
+            wl_pointer::Event::Leave { .. } => PanelEvent::PointerLeft,
+            wl_pointer::Event::Button {
+                button,
+                state: WEnum::Value(button_state),
+                ..
+            } => PanelEvent::Button {
+                button,
+                pressed: button_state == wl_pointer::ButtonState::Pressed,
+            },
+            wl_pointer::Event::Axis {
+                axis: WEnum::Value(axis),
+                value,
+                ..
+            } => match axis {
+                wl_pointer::Axis::VerticalScroll => PanelEvent::Scroll { x: 0.0, y: value },
+                wl_pointer::Axis::HorizontalScroll => PanelEvent::Scroll { x: value, y: 0.0 },
+                _ => return,
+            },
+            _ => return,
+        };
+        state.send(event);
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for PanelState {
+    fn event(
+        state: &mut Self,
+        _: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(key_state),
+                ..
+            } => {
+                let pressed = key_state == wl_keyboard::KeyState::Pressed;
+                if pressed {
+                    state.pressed_keys.insert(key);
+                } else {
+                    state.pressed_keys.remove(&key);
+                }
+                state.send(PanelEvent::Key { key, pressed });
+            }
+            wl_keyboard::Event::Leave { .. } => {
+                for key in std::mem::take(&mut state.pressed_keys) {
+                    state.send(PanelEvent::Key {
+                        key,
+                        pressed: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Where icons sit on screen
+//!
+//! Every system that places, lifts, drags or labels icons asks the `Layout`
+//! built by `dock_layout`, so all of them agree in every arrangement. Scroll
+//! distances are in pixels, `Config::spacing` per icon, as in `ScrollState`.
+
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::config::{Config, Edge, LayoutKind};
+
+/// Gap between an icon and its title
+const LABEL_GAP: f32 = 2.0;
+/// Angle the visible icons of the arc layout spread over
+const ARC_SPAN: f32 = 100.0 * std::f32::consts::PI / 180.0;
+
+/// Geometry of one dock arrangement
+pub trait Layout: Send + Sync {
+    /// Resting position and scale of the icon at `index` of `count`, scrolled by `scroll`
+    fn icon_transform(&self, index: usize, count: usize, scroll: f32) -> (Vec3, f32);
+
+    /// Scale the icon at `index` is drawn at when not hovered or dragged
+    fn icon_scale(&self, index: usize) -> f32;
+
+    /// Unit vector a hovered icon at `position` rises along, away from the screen edge
+    fn lift_direction(&self, position: Vec2) -> Vec2;
+
+    /// Offset of a title from its icon's center, for an icon `icon_extent` pixels wide
+    fn label_placement(&self, icon_extent: f32) -> (Vec2, Anchor);
+
+    /// How far along the dock `position` is, growing with the icon index
+    fn position_along(&self, position: Vec2) -> f32;
+
+    /// Unit vector along the dock at `position`, toward the following icons
+    fn tangent(&self, position: Vec2) -> Vec2;
+
+    /// Whether the front icon is drawn larger, handing over to the next one as the dock scrolls
+    fn emphasizes_front(&self) -> bool {
+        false
+    }
+
+    /// Screen edge the icons rise away from, and hide behind
+    fn edge(&self) -> Edge {
+        let outwards = -self.lift_direction(Vec2::ZERO);
+        if outwards.x.abs() > outwards.y.abs() {
+            if outwards.x > 0.0 {
+                Edge::Right
+            } else {
+                Edge::Left
+            }
+        } else if outwards.y > 0.0 {
+            Edge::Top
+        } else {
+            Edge::Bottom
+        }
+    }
+
+    /// Where the icon at `position` moves to be out of a `window_size` window,
+    /// sliding against its lift direction until it is `icon_extent` past the edge
+    fn hide_offset(&self, position: Vec2, icon_extent: f32, window_size: Vec2) -> Vec2 {
+        let direction = -self.lift_direction(position);
+        let bounds = window_size / 2.0 + icon_extent;
+        let distance = [
+            (direction.x, position.x, bounds.x),
+            (direction.y, position.y, bounds.y),
+        ]
+        .into_iter()
+        .filter(|(towards, _, _)| towards.abs() > 1e-3)
+        .map(|(towards, at, bound)| (bound - at * towards.signum()) / towards.abs())
+        .fold(f32::INFINITY, f32::min);
+
+        if distance.is_finite() {
+            direction * distance.max(0.0)
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+/// The layout `config.layout` asks for, in `window`
+pub fn dock_layout(config: &Config, window: &Window) -> Box<dyn Layout> {
+    let size = Vec2::new(window.width(), window.height());
+    match config.layout {
+        LayoutKind::Diagonal => Box::new(DiagonalLayout::new(config, size)),
+        LayoutKind::Horizontal => Box::new(LineLayout::horizontal(config, size)),
+        LayoutKind::VerticalLeft => Box::new(LineLayout::vertical(config, size, false)),
+        LayoutKind::VerticalRight => Box::new(LineLayout::vertical(config, size, true)),
+        LayoutKind::Arc => Box::new(ArcLayout::new(config, size)),
+    }
+}
+
+/// Distance between neighbouring icons in the flat layouts
+fn flat_step(config: &Config) -> f32 {
+    (config.icon_size + config.spacing * 0.25) * config.base_scale
+}
+
+/// Icons visible at once, which the flat layouts center on
+fn visible_count(count: usize, visible_items: usize) -> usize {
+    count.min(visible_items).max(1)
+}
+
+fn label_below(icon_extent: f32) -> (Vec2, Anchor) {
+    (
+        Vec2::new(0.0, -(icon_extent / 2.0 + LABEL_GAP)),
+        Anchor::Center,
+    )
+}
+
+/// The original perspective dock: a diagonal from the bottom-left corner toward
+/// `tilt_y`, each icon a little smaller and further back than the one before
+pub struct DiagonalLayout {
+    start: Vec2,
+    direction: Vec2,
+    spacing: f32,
+    base_scale: f32,
+    scale_factor: f32,
+    z_spacing: f32,
+}
+
+impl DiagonalLayout {
+    pub fn new(config: &Config, window_size: Vec2) -> Self {
+        let start = Vec2::new(
+            -window_size.x / 2.0 + config.margin_x,
+            -window_size.y / 2.0 + config.margin_y,
+        );
+        let center = Vec2::new(0.0, window_size.y * config.tilt_y);
+        Self {
+            start,
+            direction: (center - start).normalize_or_zero(),
+            spacing: config.spacing,
+            base_scale: config.base_scale,
+            scale_factor: config.scale_factor,
+            z_spacing: config.z_spacing,
+        }
+    }
+}
+
+impl Layout for DiagonalLayout {
+    fn icon_transform(&self, index: usize, _count: usize, scroll: f32) -> (Vec3, f32) {
+        let scale_dampening = 0.4;
+        let r = self.scale_factor + (1.0 - self.scale_factor) * scale_dampening;
+
+        let spacing_boost = 1.2;
+        let i = index as f32;
+
+        let total_spacing_multiplier = if (r - 1.0).abs() < f32::EPSILON {
+            i
+        } else {
+            (1.0 - r.powf(i)) / (1.0 - r)
+        };
+
+        let base_offset = self.direction
+            * (total_spacing_multiplier * self.spacing * spacing_boost * self.base_scale);
+        let position = self.start + base_offset - self.direction * scroll;
+        let z = -(index as f32 * self.z_spacing);
+
+        let scale = if scroll > 0.1 {
+            self.base_scale
+        } else {
+            self.base_scale * r.powi(index as i32)
+        };
+
+        (position.extend(z), scale)
+    }
+
+    fn icon_scale(&self, index: usize) -> f32 {
+        self.base_scale * self.scale_factor.powi(index as i32)
+    }
+
+    fn lift_direction(&self, _position: Vec2) -> Vec2 {
+        Vec2::Y
+    }
+
+    fn label_placement(&self, icon_extent: f32) -> (Vec2, Anchor) {
+        label_below(icon_extent)
+    }
+
+    fn position_along(&self, position: Vec2) -> f32 {
+        position.dot(self.direction)
+    }
+
+    fn tangent(&self, _position: Vec2) -> Vec2 {
+        self.direction
+    }
+
+    fn emphasizes_front(&self) -> bool {
+        true
+    }
+}
+
+/// Icons of the same size in a straight row or column, centered on a screen edge
+pub struct LineLayout {
+    /// Middle of the row, where the icons are centered
+    middle: Vec2,
+    /// Unit vector from one icon to the next
+    axis: Vec2,
+    lift: Vec2,
+    step: f32,
+    spacing: f32,
+    base_scale: f32,
+    z_spacing: f32,
+    visible_items: usize,
+    label_anchor: Option<Anchor>,
+}
+
+impl LineLayout {
+    /// A classic dock along the bottom edge, left to right
+    pub fn horizontal(config: &Config, window_size: Vec2) -> Self {
+        Self {
+            middle: Vec2::new(0.0, -window_size.y / 2.0 + config.margin_y),
+            axis: Vec2::X,
+            lift: Vec2::Y,
+            label_anchor: None,
+            ..Self::common(config)
+        }
+    }
+
+    /// A column along the left or right edge, top to bottom, titles toward the screen
+    pub fn vertical(config: &Config, window_size: Vec2, right: bool) -> Self {
+        let (side, label_anchor) = if right {
+            (1.0, Anchor::CenterRight)
+        } else {
+            (-1.0, Anchor::CenterLeft)
+        };
+        Self {
+            middle: Vec2::new(side * (window_size.x / 2.0 - config.margin_x), 0.0),
+            axis: Vec2::NEG_Y,
+            lift: Vec2::new(-side, 0.0),
+            label_anchor: Some(label_anchor),
+            ..Self::common(config)
+        }
+    }
+
+    fn common(config: &Config) -> Self {
+        Self {
+            middle: Vec2::ZERO,
+            axis: Vec2::X,
+            lift: Vec2::Y,
+            step: flat_step(config),
+            spacing: config.spacing,
+            base_scale: config.base_scale,
+            z_spacing: config.z_spacing,
+            visible_items: config.visible_items,
+            label_anchor: None,
+        }
+    }
+}
+
+impl Layout for LineLayout {
+    fn icon_transform(&self, index: usize, count: usize, scroll: f32) -> (Vec3, f32) {
+        let visible = visible_count(count, self.visible_items) as f32;
+        let first = self.middle - self.axis * (visible - 1.0) * self.step / 2.0;
+        let slot = index as f32 - scroll / self.spacing;
+        let position = first + self.axis * slot * self.step;
+
+        (
+            position.extend(-(index as f32 * self.z_spacing)),
+            self.base_scale,
+        )
+    }
+
+    fn icon_scale(&self, _index: usize) -> f32 {
+        self.base_scale
+    }
+
+    fn lift_direction(&self, _position: Vec2) -> Vec2 {
+        self.lift
+    }
+
+    fn label_placement(&self, icon_extent: f32) -> (Vec2, Anchor) {
+        match &self.label_anchor {
+            Some(anchor) => (self.lift * (icon_extent / 2.0 + LABEL_GAP), *anchor),
+            None => label_below(icon_extent),
+        }
+    }
+
+    fn position_along(&self, position: Vec2) -> f32 {
+        position.dot(self.axis)
+    }
+
+    fn tangent(&self, _position: Vec2) -> Vec2 {
+        self.axis
+    }
+}
+
+/// Icons fanned out along a circular arc rising from the bottom edge
+pub struct ArcLayout {
+    /// Center of the circle, below the bottom edge
+    pivot: Vec2,
+    radius: f32,
+    /// Angle between neighbouring icons
+    angle_step: f32,
+    spacing: f32,
+    base_scale: f32,
+    z_spacing: f32,
+    visible_items: usize,
+}
+
+impl ArcLayout {
+    pub fn new(config: &Config, window_size: Vec2) -> Self {
+        let step = flat_step(config);
+        let radius = (config.visible_items.saturating_sub(1) as f32 * step / ARC_SPAN).max(step);
+        // As ends of the arc rest on the margin, the middle rises above it
+        let base = -window_size.y / 2.0 + config.margin_y;
+        Self {
+            pivot: Vec2::new(0.0, base - radius * (ARC_SPAN / 2.0).cos()),
+            radius,
+            angle_step: step / radius,
+            spacing: config.spacing,
+            base_scale: config.base_scale,
+            z_spacing: config.z_spacing,
+            visible_items: config.visible_items,
+        }
+    }
+}
+
+impl Layout for ArcLayout {
+    fn icon_transform(&self, index: usize, count: usize, scroll: f32) -> (Vec3, f32) {
+        let visible = visible_count(count, self.visible_items) as f32;
+        let slot = index as f32 - scroll / self.spacing;
+        let angle = FRAC_PI_2 + ((visible - 1.0) / 2.0 - slot) * self.angle_step;
+        let position = self.pivot + Vec2::from_angle(angle) * self.radius;
+
+        (
+            position.extend(-(index as f32 * self.z_spacing)),
+            self.base_scale,
+        )
+    }
+
+    fn icon_scale(&self, _index: usize) -> f32 {
+        self.base_scale
+    }
+
+    fn lift_direction(&self, position: Vec2) -> Vec2 {
+        (position - self.pivot).try_normalize().unwrap_or(Vec2::Y)
+    }
+
+    fn label_placement(&self, icon_extent: f32) -> (Vec2, Anchor) {
+        label_below(icon_extent)
+    }
+
+    fn position_along(&self, position: Vec2) -> f32 {
+        position.x
+    }
+
+    fn tangent(&self, position: Vec2) -> Vec2 {
+        // Clockwise, as the icons go left to right over the top
+        -self.lift_direction(position).perp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Vec2 = Vec2::new(1280.0, 720.0);
+
+    fn config(layout: LayoutKind) -> Config {
+        Config {
+            layout,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_diagonal_starts_at_the_margin_and_shrinks() {
+        let config = config(LayoutKind::Diagonal);
+        let layout = DiagonalLayout::new(&config, WINDOW);
+
+        let (first, first_scale) = layout.icon_transform(0, 5, 0.0);
+        let (second, second_scale) = layout.icon_transform(1, 5, 0.0);
+        assert_eq!(
+            first.truncate(),
+            Vec2::new(-640.0 + config.margin_x, -360.0 + config.margin_y)
+        );
+        assert_eq!(first_scale, config.base_scale);
+        assert!(second_scale < first_scale);
+        assert!(layout.position_along(second.truncate()) > layout.position_along(first.truncate()));
+
+        // Rolar desliza os ícones ao longo da diagonal
+        let (scrolled, scrolled_scale) = layout.icon_transform(1, 5, config.spacing);
+        assert!((scrolled.truncate().distance(second.truncate()) - config.spacing).abs() < 1e-3);
+        assert_eq!(scrolled_scale, config.base_scale);
+    }
+
+    #[test]
+    fn test_horizontal_row_is_centered() {
+        let config = config(LayoutKind::Horizontal);
+        let layout = LineLayout::horizontal(&config, WINDOW);
+
+        let (first, _) = layout.icon_transform(0, 3, 0.0);
+        let (middle, _) = layout.icon_transform(1, 3, 0.0);
+        let (last, scale) = layout.icon_transform(2, 3, 0.0);
+        assert_eq!(middle.x, 0.0);
+        assert_eq!(first.x, -last.x);
+        assert_eq!(first.y, last.y);
+        assert_eq!(scale, config.base_scale);
+        assert_eq!(layout.lift_direction(middle.truncate()), Vec2::Y);
+
+        let (scrolled, _) = layout.icon_transform(1, 3, config.spacing);
+        assert_eq!(scrolled.x, first.x);
+    }
+
+    #[test]
+    fn test_vertical_column_runs_down_its_edge() {
+        let config = config(LayoutKind::VerticalRight);
+        let layout = LineLayout::vertical(&config, WINDOW, true);
+
+        let (first, _) = layout.icon_transform(0, 2, 0.0);
+        let (second, _) = layout.icon_transform(1, 2, 0.0);
+        assert_eq!(first.x, 640.0 - config.margin_x);
+        assert!(second.y < first.y);
+        assert!(layout.position_along(second.truncate()) > layout.position_along(first.truncate()));
+        assert_eq!(layout.lift_direction(first.truncate()), Vec2::NEG_X);
+
+        let (offset, anchor) = layout.label_placement(50.0);
+        assert!(offset.x < 0.0);
+        assert!(matches!(anchor, Anchor::CenterRight));
+    }
+
+    #[test]
+    fn test_arc_is_symmetric_and_lifts_outwards() {
+        let config = config(LayoutKind::Arc);
+        let layout = ArcLayout::new(&config, WINDOW);
+
+        let (left, _) = layout.icon_transform(0, 3, 0.0);
+        let (top, _) = layout.icon_transform(1, 3, 0.0);
+        let (right, _) = layout.icon_transform(2, 3, 0.0);
+        assert!(top.x.abs() < 1e-3);
+        assert!((left.x + right.x).abs() < 1e-3);
+        assert!(top.y > left.y);
+        assert!(layout
+            .lift_direction(top.truncate())
+            .abs_diff_eq(Vec2::Y, 1e-5));
+        assert!(layout.lift_direction(left.truncate()).x < 0.0);
+        assert!(layout.tangent(top.truncate()).abs_diff_eq(Vec2::X, 1e-5));
+    }
+
+    #[test]
+    fn test_icons_hide_behind_their_own_edge() {
+        let diagonal = DiagonalLayout::new(&config(LayoutKind::Diagonal), WINDOW);
+        let left = LineLayout::vertical(&config(LayoutKind::VerticalLeft), WINDOW, false);
+        let arc = ArcLayout::new(&config(LayoutKind::Arc), WINDOW);
+        assert_eq!(diagonal.edge(), Edge::Bottom);
+        assert_eq!(left.edge(), Edge::Left);
+        assert_eq!(arc.edge(), Edge::Bottom);
+
+        let (first, _) = diagonal.icon_transform(0, 3, 0.0);
+        let hidden = first.truncate() + diagonal.hide_offset(first.truncate(), 50.0, WINDOW);
+        assert_eq!(hidden, Vec2::new(first.x, -360.0 - 50.0));
+
+        let (top, _) = left.icon_transform(0, 3, 0.0);
+        let hidden = top.truncate() + left.hide_offset(top.truncate(), 50.0, WINDOW);
+        assert_eq!(hidden, Vec2::new(-640.0 - 50.0, top.y));
+
+        // Os ícones do arco saem em direção ao centro do círculo, abaixo da borda
+        let (side, _) = arc.icon_transform(0, 3, 0.0);
+        let hidden = side.truncate() + arc.hide_offset(side.truncate(), 50.0, WINDOW);
+        assert!(hidden.y <= -360.0 - 50.0 + 1e-3 || hidden.x <= -640.0 - 50.0 + 1e-3);
+    }
+}
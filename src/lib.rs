@@ -0,0 +1,22 @@
+//! A macOS-style dock for Wayland compositors, built on Bevy
+//!
+//! Add `AnnyDockPlugin` to an app that has a window to get the whole dock,
+//! and order your own systems around the stages in `DockSet`.
+
+pub mod backend;
+pub mod components;
+pub mod config;
+pub mod control;
+pub mod desktop;
+pub mod icon;
+pub mod layer_shell;
+pub mod layout;
+pub mod plugin;
+pub mod systems;
+#[cfg(test)]
+mod testing;
+pub mod types;
+pub mod utils;
+
+pub use plugin::AnnyDockPlugin;
+pub use types::{DockSet, ReorderIcons, StateUpdate};
@@ -1,860 +1,99 @@
-mod components;
-mod systems;
-mod types;
-mod utils;
-mod config;
+mod cli;
+mod supervisor;
 
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
-use bevy::render::texture::Image;
-use bevy::window::{PrimaryWindow, Window, WindowPlugin};
+use bevy::window::{Window, WindowPlugin};
+use bevy::winit::WinitPlugin;
 use bevy_embedded_assets::EmbeddedAssetPlugin;
 use bevy_svg::SvgPlugin;
 
-use components::{
-    add_client_address, add_favorite, add_icon_text, spawn_icon_entity, Favorite, Favorites, FavoritePin,
-};
-use std::collections::HashSet;
-use std::process::Command;
-use types::*;
-use utils::hover::{hover_animation_system, hover_system};
-use utils::{
-    calculate_icon_transform, launch_application, load_clients, load_favorites, save_favorites,
-    update_sprite_alpha, IconAnimationState,
-};
-use config::{load_config, Config};
-
-use std::env;
-use std::io::{BufRead, BufReader};
-use std::os::unix::net::UnixStream;
-use std::sync::{mpsc::channel, Arc, Mutex};
-
-use crate::systems::*;
-use systems::animation::ScrollAnimationState;
+use anny_dock::backend;
+use anny_dock::config::{load_config, MonitorMode};
+use anny_dock::layer_shell::{LayerShellError, LayerShellPlugin, LayerSurface};
+use anny_dock::AnnyDockPlugin;
 
 fn main() {
-    let config = load_config();
-    let client_list = load_clients();
-    let favorites = load_favorites();
-
-    App::new()
-        
-        .insert_resource(Msaa::Sample4)
-        .add_plugins(EmbeddedAssetPlugin::default())
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        transparent: true,
-                        decorations: false,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .set(ImagePlugin {
-                    default_sampler: bevy::render::texture::ImageSamplerDescriptor::linear(),
-                    ..default()
-                }),
-        )
-        .add_plugins(SvgPlugin)
-        .insert_resource(ClearColor(Color::NONE))
-        .insert_resource(ClientList(client_list))
-        .insert_resource(IconPositions::default())
-        .insert_resource(ShowTitles(false))
-        .insert_resource(UiState::default())
-        .insert_resource(favorites)
-        .insert_resource(ReorderTrigger::default())
-        .insert_resource(DockOrder::default())
-        .insert_resource(ScrollState::default())
-        .insert_resource(config)
-        .insert_resource(IconAnimationState::default())
-        .insert_resource(ScrollAnimationState::default())
-        .add_event::<IconRemovedEvent>()
-        .add_systems(Startup, setup)
-        .add_systems(Startup, setup_hyprland_monitor)
-        
-        .add_systems(Update, cleanup_duplicate_cameras)
-        .add_systems(
-            Update,
-            (
-                scroll_system,
-                scroll_with_arrows,
-                hover_system,
-                hover_animation_system,
-                icon_scale_animation_system,
-                collect_icon_data.before(update_text_positions),
-                update_text_positions,
-                icon_click_system,
-                toggle_favorite_system.in_set(StateUpdate),
-                toggle_titles,
-                drag_register_click_system,
-                drag_check_system,
-                drag_update_system,
-                drag_end_system.in_set(StateUpdate),
-                reset_positions_system,
-                reorder_icons_system.in_set(ReorderIcons),
-                process_hyprland_events,
-                exit_on_esc_or_q,
-                keybind_launch_visible_icons,
-            )
-                .chain(),
-        )
-        .run();
-}
-
-fn setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut images: ResMut<Assets<Image>>,
-    client_list: Res<ClientList>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    show_titles: Res<ShowTitles>,
-    favorites: Res<Favorites>,
-    config: Res<Config>,
-) {
-    commands
-        .spawn(Camera2dBundle {
-            transform: Transform {
-                translation: Vec3::new(0.0, 0.0, 100.0),
-                ..default()
-            },
-            ..default()
-        })
-        .insert(MainCamera);
-
-    let window = windows.single();
-    let window_width = window.width();
-    let window_height = window.height();
-
-    let start_x = -window_width / 2.0 + config.margin_x;
-    let start_y = -window_height / 2.0 + config.margin_y;
-    let start_pos = Vec2::new(start_x, start_y);
-    let center = Vec2::new(0.0, window_height * config.tilt_y);
-
-    let mut all_apps: Vec<(String, Option<Client>, bool)> = Vec::new();
-    let mut initial_order = Vec::new();
-    let mut processed_classes = HashSet::new();
-
-    // Primeiro, adiciona os favoritos na ordem salva
-    for fav_class in &favorites.0 {
-        if processed_classes.contains(fav_class) {
-            continue;
-        }
-
-        let client = client_list.0.iter().find(|c| &c.class == fav_class).cloned();
-        all_apps.push((fav_class.clone(), client.clone(), true));
-
-        if let Some(client) = client {
-            initial_order.push(client.address.clone());
-        } else {
-            initial_order.push(format!("pinned:{}", fav_class));
-        }
-        processed_classes.insert(fav_class.clone());
-    }
-
-    // Depois, adiciona as aplicações não-favoritas que estão abertas
-    for client in &client_list.0 {
-        if !processed_classes.contains(&client.class) {
-            all_apps.push((client.class.clone(), Some(client.clone()), false));
-            initial_order.push(client.address.clone());
-            processed_classes.insert(client.class.clone());
-        }
-    }
-
-    commands.insert_resource(DockOrder(initial_order));
-
-    let direction = (center - start_pos).normalize_or_zero();
-
-    for (index, (class, client_opt, is_favorite)) in all_apps.iter().enumerate() {
-        let (translation, scale) =
-            calculate_icon_transform(index, start_pos, direction, &config, Vec2::ZERO);
-        let transform = Transform {
-            translation,
-            scale: Vec3::splat(scale),
-            ..default()
-        };
-        let alpha = if *is_favorite && client_opt.is_none() {
-            0.5
-        } else {
-            1.0
-        };
-
-        let icon_entity = spawn_icon_entity(
-            &mut commands,
-            &mut images,
-            &asset_server,
-            class,
-            transform,
-            scale,
-            alpha,
-            index,
-        );
-
-        if let Some(client) = client_opt {
-            add_client_address(&mut commands, icon_entity, client.address.clone());
-            commands
-                .entity(icon_entity)
-                .insert(ClientAddress(client.address.clone()));
-        } else if *is_favorite {
-            let placeholder_address = format!("pinned:{}", class);
-            add_client_address(&mut commands, icon_entity, placeholder_address.clone());
-            commands
-                .entity(icon_entity)
-                .insert(ClientAddress(placeholder_address));
-        }
-
-        if *is_favorite {
-            add_favorite(&mut commands, icon_entity, &mut images, &config);
-        }
-        if show_titles.0 {
-            add_icon_text(
-                &mut commands,
-                icon_entity,
-                class,
-                transform,
-                scale,
-                &asset_server,
-                &config,
-            );
-        }
-    }
-}
-
-fn toggle_favorite_system(
-    mut commands: Commands,
-    mut images: ResMut<Assets<Image>>,
-    mut favorites: ResMut<Favorites>,
-    mut reorder_trigger: ResMut<ReorderTrigger>,
-    mut dock_order: ResMut<DockOrder>,
-    mut q_icons: Query<(
-        Entity,
-        &ClientClass,
-        Option<&mut Sprite>,
-        Option<&ClientAddress>,
-        Option<&Favorite>,
-        &HoverTarget,
-        &Transform,
-        Option<&Children>,
-    )>,
-    config: Res<Config>,
-    q_pins: Query<Entity, With<FavoritePin>>,
-    mouse_button: Res<Input<MouseButton>>,
-) {
-    if mouse_button.just_released(MouseButton::Right) {
-        if let Some((entity, class, mut sprite_opt, address_opt, favorite_opt, _hover, _transform, children)) =
-            q_icons.iter_mut().find(|(_, _, _, _, _, hover, _, _)| hover.is_hovered)
-        {
-            toggle_favorite(
-                &mut commands,
-                &mut images,
-                &mut favorites,
-                &mut reorder_trigger,
-                &mut dock_order,
-                entity,
-                &class.0,
-                favorite_opt.is_some(),
-                sprite_opt.as_deref_mut().unwrap(),
-                address_opt,
-                &config,
-                children,
-                &q_pins,
-            );
-        }
-    }
-}
-
-fn toggle_favorite(
-    commands: &mut Commands,
-    images: &mut Assets<Image>,
-    favorites: &mut ResMut<Favorites>,
-    reorder_trigger: &mut ResMut<ReorderTrigger>,
-    dock_order: &mut ResMut<DockOrder>,
-    entity: Entity,
-    app_class: &str,
-    is_favorite: bool,
-    sprite: &mut Sprite,
-    q_address: Option<&ClientAddress>,
-    config: &Res<Config>,
-    children: Option<&Children>,
-    q_pins: &Query<Entity, With<FavoritePin>>,
-) {
-    if is_favorite {
-        info!("Removing favorite: {}", app_class);
-        favorites.0.retain(|f| f != app_class);
-
-        let is_running = q_address.map_or(false, |addr| !addr.0.starts_with("pinned:"));
-
-        if is_running {
-            // App is running, just remove favorite status and pin
-            commands.entity(entity).remove::<Favorite>();
-            if let Some(children) = children {
-                for &child in children.iter() {
-                    if q_pins.get(child).is_ok() {
-                        commands.entity(child).despawn();
-                    }
-                }
-            }
-            // Reorder will be triggered and will move this to non-favorites section
-        } else {
-            // App is not running, it only exists because it was a favorite.
-            // Despawn the whole thing.
-            let pinned_addr = format!("pinned:{}", app_class);
-            dock_order.0.retain(|a| a != &pinned_addr);
-            commands.entity(entity).despawn_recursive();
-        }
-
-        reorder_trigger.0 = true;
-    } else {
-        info!("Adding favorite: {}", app_class);
-        if !favorites.0.contains(&app_class.to_string()) {
-            // Add favorite to the list
-            favorites.0.push(app_class.to_string());
-        }
-        add_favorite(commands, entity, images, config);
-        
-        // Trigger full reorder to place favorites correctly
-        reorder_trigger.0 = true;
-    }
-    save_favorites(favorites);
-
-    if commands.get_entity(entity).is_some() {
-        let is_running =
-            q_address.is_some() && q_address.map(|a| !a.0.starts_with("pinned:")).unwrap_or(false);
-        update_sprite_alpha(sprite, !is_favorite, is_running);
-    }
-}
-
-fn icon_click_system(
-    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    q_icons: Query<(
-        Entity,
-        &ClientAddress,
-        &ClientClass,
-        &HoverTarget,
-        &Transform,
-    )>,
-    mouse_button: Res<Input<MouseButton>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    ui_state: Res<UiState>,
-    config: Res<Config>,
-) {
-    if mouse_button.just_released(MouseButton::Left) && ui_state.dragging.is_none() {
-        let window = windows.single();
-        if let Some(cursor_pos) = window.cursor_position() {
-            if let Ok((camera, camera_transform)) = q_camera.get_single() {
-                if let Some(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-                    for (_entity, address, client_class, hover, transform) in q_icons.iter() {
-                        let icon_position = transform.translation.truncate();
-                        let size = Vec2::splat(config.icon_size);
-                        let rect = Rect::from_center_size(icon_position, size);
-                        if rect.contains(world_pos) && hover.is_hovered {
-                            if address.0.starts_with("pinned:") {
-                                launch_application(&client_class.0);
-                            } else {
-                                focus_client(&address.0);
-                            }
-                            break;
-                        }
-                    }
-                }
+    let matches = cli::command().get_matches();
+    if let Some(code) = cli::run(&matches) {
+        std::process::exit(code);
+    }
+    let options = cli::Options::from_matches(&matches);
+    let config = load_config(options.config_path.as_deref());
+
+    if config.monitors.mode == MonitorMode::All && options.monitor.is_none() {
+        let window_manager = backend::connect(options.backend.unwrap_or(config.backend));
+        if let Some(code) = supervisor::run(&options, &window_manager) {
+            std::process::exit(code);
+        }
+    }
+
+    // Painel do layer-shell quando o compositor suporta, senão janela comum do winit
+    let monitor = options
+        .monitor
+        .clone()
+        .or_else(|| Some(config.monitors.output.clone()).filter(|output| !output.is_empty()));
+    let panel = if config.layer.enabled {
+        match LayerSurface::create(&config.layer, monitor.as_deref()) {
+            Ok(panel) => Some(panel),
+            Err(LayerShellError::Connect(_)) => None,
+            Err(e) => {
+                eprintln!("Layer shell unavailable, using a regular window: {}", e);
+                None
             }
         }
-    }
-}
-
-fn focus_client(address: &str) {
-    let full_address = if address.starts_with("address:") {
-        address.to_string()
     } else {
-        format!("address:{}", address)
+        None
     };
-    let output = Command::new("hyprctl")
-        .args(["dispatch", "focuswindow", &full_address])
-        .output();
-    match output {
-        Ok(result) => {
-            if !result.status.success() {
-                warn!(
-                    "Failed to focus window: {:?}",
-                    String::from_utf8_lossy(&result.stderr)
-                );
-            }
-        }
-        Err(e) => error!("Error executing hyprctl: {:?}", e),
-    }
-}
-
-#[allow(dead_code)]
-fn update_client_list_system(
-    mut client_list: ResMut<ClientList>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    config: Res<Config>,
-    favorites: Res<Favorites>,
-    show_titles: Res<ShowTitles>,
-    mut q_entities: Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    mut images: ResMut<Assets<Image>>,
-    mut dock_order: ResMut<DockOrder>,
-    mut reorder_trigger: ResMut<ReorderTrigger>,
-) {
-    match crate::utils::loader::get_current_clients() {
-        Ok(current_windows) => {
-            let current_addresses: HashSet<String> =
-                current_windows.iter().map(|c| c.address.clone()).collect();
-            let old_addresses: HashSet<String> =
-                client_list.0.iter().map(|c| c.address.clone()).collect();
 
-            let new_windows: Vec<Client> = current_windows
-                .iter()
-                .filter(|c| !old_addresses.contains(&c.address))
-                .cloned()
-                .collect();
-            let closed_windows: Vec<String> = old_addresses
-                .difference(&current_addresses)
-                .cloned()
-                .collect();
-
-            if !new_windows.is_empty() || !closed_windows.is_empty() {
-                client_list.0 = current_windows.clone();
-
-                process_closed_windows(
-                    &closed_windows,
-                    &favorites,
-                    &mut q_entities,
-                    &mut dock_order,
-                    &mut commands,
-                );
-
-                process_new_windows(
-                    &new_windows,
-                    &mut q_entities,
-                    &mut commands,
-                    &mut images,
-                    &asset_server,
-                    &windows,
-                    &config,
-                    &show_titles,
-                    &mut dock_order,
-                    &mut client_list,
-                    &mut reorder_trigger,
-                );
-            }
-        }
-        Err(e) => {
-            error!("Failed to get current windows: {:?}", e);
-        }
-    }
-}
-
-#[allow(dead_code)]
-fn process_closed_windows(
-    closed_windows: &[String],
-    favorites: &Favorites,
-    q_entities: &mut Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    dock_order: &mut DockOrder,
-    commands: &mut Commands,
-) {
-    for address in closed_windows {
-        if let Some((entity, addr_opt, class_opt, Some(mut sprite))) =
-            q_entities.iter_mut().find(|(_, addr, _, sprite)| {
-                addr.as_ref().map(|a| a.0 == *address).unwrap_or(false) && sprite.is_some()
-            })
-        {
-            if let (Some(_addr), Some(class)) = (addr_opt, class_opt) {
-                if favorites.0.contains(&class.0) {
-                    handle_close_pinned_window(
-                        entity,
-                        address,
-                        &class.0,
-                        &mut sprite,
-                        dock_order,
-                        commands,
-                    );
-                } else {
-                    commands.entity(entity).despawn();
-                    dock_order.0.retain(|a| a != address);
-                }
-            }
-        }
-    }
-}
-
-#[allow(dead_code)]
-fn process_new_windows(
-    new_windows: &[Client],
-    q_entities: &mut Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    commands: &mut Commands,
-    images: &mut ResMut<Assets<Image>>,
-    asset_server: &Res<AssetServer>,
-    windows: &Query<&Window, With<PrimaryWindow>>,
-    config: &Res<Config>,
-    show_titles: &Res<ShowTitles>,
-    dock_order: &mut DockOrder,
-    client_list: &mut ResMut<ClientList>,
-    reorder_trigger: &mut ResMut<ReorderTrigger>,
-) {
-
-    for (_index, client) in new_windows.iter().enumerate() {
-        // Verifica se já existe um ícone para este endereço
-        if q_entities.iter().any(|(_, addr_opt, _, _)| {
-            addr_opt.map_or(false, |a| a.0 == client.address)
-        }) {
-            info!("Window already exists in dock: {}", client.address);
-            continue;
-        }
-
-        // Verifica se existe um ícone pinned para esta classe
-        if let Some((entity, _, _, Some(mut sprite))) = q_entities.iter_mut().find(|(_, addr_opt, class_opt, _)| {
-            addr_opt.map_or(false, |a| a.0.starts_with("pinned:"))
-                && class_opt.map_or(false, |c| c.0 == client.class)
-        }) {
-            commands
-                .entity(entity)
-                .insert(ClientAddress(client.address.clone()));
-            handle_open_pinned_window(entity, client, &mut sprite, dock_order);
-            client_list.0.push(client.clone());
-            reorder_trigger.0 = true;
-            continue;
-        }
-
-        client_list.0.push(client.clone());
-        dock_order.0.push(client.address.clone());
-        reorder_trigger.0 = true;
-        let window = windows.single();
-        let start_x = -window.width() / 2.0 + config.margin_x;
-        let start_y = -window.height() / 2.0 + config.margin_y;
-        let start_pos = Vec2::new(start_x, start_y);
-        let center = Vec2::new(0.0, window.height() * config.tilt_y);
-        let direction = (center - start_pos).normalize_or_zero();
-
-        let (translation, scale) =
-            calculate_icon_transform(dock_order.0.len() - 1, start_pos, direction, config, Vec2::ZERO);
-        let transform = Transform {
-            translation,
-            scale: Vec3::splat(scale),
+    let default_plugins = DefaultPlugins
+        .set(ImagePlugin {
+            default_sampler: bevy::render::texture::ImageSamplerDescriptor::linear(),
             ..default()
-        };
-
-        let icon_entity = spawn_icon_entity(
-            commands,
-            images,
-            asset_server,
-            &client.class,
-            transform,
-            scale,
-            1.0,
-            0,
-        );
-
-        add_client_address(commands, icon_entity, client.address.clone());
-        commands
-            .entity(icon_entity)
-            .insert(ClientAddress(client.address.clone()));
-
-        if show_titles.0 {
-            add_icon_text(
-                commands,
-                icon_entity,
-                &client.class,
-                transform,
-                scale,
-                asset_server,
-                config,
-            );
-        }
-    }
-}
-
-fn setup_hyprland_monitor(mut commands: Commands) {
-    let (event_sender, event_receiver) = channel();
-    let event_receiver = Arc::new(Mutex::new(event_receiver));
-
-    std::thread::spawn(move || {
-        let xdg_runtime_dir = match env::var("XDG_RUNTIME_DIR") {
-            Ok(val) => val,
-            Err(_) => return,
-        };
-        let hyprland_instance_signature = match env::var("HYPRLAND_INSTANCE_SIGNATURE") {
-            Ok(val) => val,
-            Err(_) => return,
-        };
-        let socket_path = format!(
-            "{}/hypr/{}/.socket2.sock",
-            xdg_runtime_dir, hyprland_instance_signature
-        );
-        let stream = match UnixStream::connect(socket_path) {
-            Ok(s) => s,
-            Err(_) => return,
-        };
-        let reader = BufReader::new(stream);
+        })
+        .set(LogPlugin {
+            level: options.log_level,
+            ..default()
+        });
 
-        for line in reader.lines().flatten() {
-            if let Some(rest) = line.strip_prefix("openwindow>>") {
-                let mut parts = rest.splitn(4, ',');
-                if let (Some(address), Some(workspace), Some(class), Some(title)) =
-                    (parts.next(), parts.next(), parts.next(), parts.next())
-                {
-                    let _ = event_sender.send(HyprIpcEvent::OpenWindow {
-                        address: address.to_string(),
-                        workspace: workspace.to_string(),
-                        class: class.to_string(),
-                        title: title.to_string(),
-                    });
-                }
-            } else if let Some(rest) = line.strip_prefix("closewindow>>") {
-                let address = rest.trim().to_string();
-                let _ = event_sender.send(HyprIpcEvent::CloseWindow { address });
-            } else {
-                let _ = event_sender.send(HyprIpcEvent::Other);
-            }
+    let mut app = App::new();
+    app.insert_resource(Msaa::Sample4)
+        .insert_resource(config)
+        .add_plugins(EmbeddedAssetPlugin::default());
+
+    match panel {
+        Some((surface, events)) => {
+            app.add_plugins(
+                default_plugins
+                    .set(WindowPlugin {
+                        primary_window: None,
+                        ..default()
+                    })
+                    .disable::<WinitPlugin>(),
+            )
+            .add_plugins(LayerShellPlugin::new(surface, events));
         }
-    });
-
-    commands.insert_resource(HyprlandEventReceiver(event_receiver));
-}
-
-fn process_hyprland_events(
-    mut commands: Commands,
-    mut client_list: ResMut<ClientList>,
-    mut dock_order: ResMut<DockOrder>,
-    mut reorder_trigger: ResMut<ReorderTrigger>,
-    event_receiver: Res<HyprlandEventReceiver>,
-    windows: Query<&Window, With<PrimaryWindow>>,
-    config: Res<Config>,
-    favorites: Res<Favorites>,
-    show_titles: Res<ShowTitles>,
-    asset_server: Res<AssetServer>,
-    mut q_entities: Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    mut images: ResMut<Assets<Image>>,
-) {
-    let event_receiver = event_receiver.0.lock().unwrap();
-    while let Ok(event) = event_receiver.try_recv() {
-        match event {
-            HyprIpcEvent::OpenWindow {
-                address,
-                workspace: _,
-                class,
-                title,
-            } => {
-                handle_hypr_open_window(
-                    &mut commands,
-                    &mut client_list,
-                    &mut dock_order,
-                    &mut reorder_trigger,
-                    &asset_server,
-                    &windows,
-                    &config,
-                    &favorites,
-                    &show_titles,
-                    &mut q_entities,
-                    &mut images,
-                    address,
-                    class,
-                    title,
-                );
-            }
-            HyprIpcEvent::CloseWindow { address } => {
-                handle_hypr_close_window(
-                    &mut commands,
-                    &mut client_list,
-                    &mut dock_order,
-                    &mut reorder_trigger,
-                    &favorites,
-                    &mut q_entities,
-                    address,
-                );
-            }
-            HyprIpcEvent::Other => {}
+        None => {
+            app.add_plugins(default_plugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    transparent: true,
+                    decorations: false,
+                    ..default()
+                }),
+                ..default()
+            }));
         }
     }
-}
-
-fn handle_hypr_open_window(
-    commands: &mut Commands,
-    client_list: &mut ResMut<ClientList>,
-    dock_order: &mut ResMut<DockOrder>,
-    reorder_trigger: &mut ResMut<ReorderTrigger>,
-    asset_server: &Res<AssetServer>,
-    windows: &Query<&Window, With<PrimaryWindow>>,
-    config: &Res<Config>,
-    _favorites: &Res<Favorites>,
-    show_titles: &Res<ShowTitles>,
-    q_entities: &mut Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    images: &mut ResMut<Assets<Image>>,
-    address: String,
-    class: String,
-    title: String,
-) {
-    // Verifica se já existe um ícone para este endereço
-    if q_entities.iter().any(|(_, addr_opt, _, _)| {
-        addr_opt.map_or(false, |a| a.0 == address)
-    }) {
-        info!("Window already exists in dock: {}", address);
-        return;
-    }
-
-    let client = Client {
-        address: address.clone(),
-        class: class.clone(),
-        _name: Some(title.clone()),
-    };
-    let pinned_addr = format!("pinned:{}", client.class);
-    if let Some((entity, _, _, Some(mut sprite))) = q_entities.iter_mut().find(|(_, addr_opt, class_opt, _)| {
-        addr_opt.map_or(false, |a| a.0 == pinned_addr)
-            && class_opt.map_or(false, |c| c.0 == client.class)
-    }) {
-        commands
-            .entity(entity)
-            .insert(ClientAddress(client.address.clone()));
-        handle_open_pinned_window(entity, &client, &mut sprite, dock_order);
-        client_list.0.push(client.clone());
-        reorder_trigger.0 = true;
-        return;
-    }
-
-    client_list.0.push(client.clone());
-    dock_order.0.push(address.clone());
-    reorder_trigger.0 = true;
-    let window = windows.single();
-    let start_x = -window.width() / 2.0 + config.margin_x;
-    let start_y = -window.height() / 2.0 + config.margin_y;
-    let start_pos = Vec2::new(start_x, start_y);
-    let center = Vec2::new(0.0, window.height() * config.tilt_y);
-    let direction = (center - start_pos).normalize_or_zero();
-
-    let (translation, scale) =
-        calculate_icon_transform(dock_order.0.len() - 1, start_pos, direction, config, Vec2::ZERO);
-    let transform = Transform {
-        translation,
-        scale: Vec3::splat(scale),
-        ..default()
-    };
-
-    let icon_entity = spawn_icon_entity(
-        commands,
-        images,
-        asset_server,
-        &client.class,
-        transform,
-        scale,
-        1.0,
-        0,
-    );
-
-    add_client_address(commands, icon_entity, client.address.clone());
-    commands
-        .entity(icon_entity)
-        .insert(ClientAddress(client.address.clone()));
-
-    if show_titles.0 {
-        add_icon_text(
-            commands,
-            icon_entity,
-            &client.class,
-            transform,
-            scale,
-            asset_server,
-            config,
-        );
-    }
-}
 
-fn handle_hypr_close_window(
-    commands: &mut Commands,
-    client_list: &mut ResMut<ClientList>,
-    dock_order: &mut ResMut<DockOrder>,
-    reorder_trigger: &mut ResMut<ReorderTrigger>,
-    favorites: &Res<Favorites>,
-    q_entities: &mut Query<(
-        Entity,
-        Option<&ClientAddress>,
-        Option<&ClientClass>,
-        Option<&mut Sprite>,
-    )>,
-    address: String,
-) {
-    if let Some((entity, addr_opt, class_opt, Some(mut sprite))) =
-        q_entities.iter_mut().find(|(_, addr, _, sprite)| {
-            addr.as_ref().map(|a| a.0 == address).unwrap_or(false) && sprite.is_some()
+    let control_socket = options.socket_path();
+    app.add_plugins(SvgPlugin)
+        .insert_resource(ClearColor(Color::NONE))
+        .add_plugins(AnnyDockPlugin {
+            config_path: options.config_path,
+            favorites_path: options.favorites_path,
+            backend: options.backend,
+            monitor: options.monitor,
+            control_socket: Some(control_socket),
         })
-    {
-        if let (Some(_addr), Some(class)) = (addr_opt, class_opt) {
-            if favorites.0.contains(&class.0) {
-                handle_close_pinned_window(
-                    entity,
-                    &address,
-                    &class.0,
-                    &mut sprite,
-                    dock_order,
-                    commands,
-                );
-            } else {
-                commands.entity(entity).despawn();
-                dock_order.0.retain(|a| a != &address);
-            }
-        }
-    }
-    client_list.0.retain(|c| c.address != address);
-    reorder_trigger.0 = true;
-}
-
-fn handle_open_pinned_window(
-    _entity: Entity,
-    client: &Client,
-    sprite: &mut Sprite,
-    dock_order: &mut DockOrder,
-) {
-    sprite.color.set_a(1.0);
-
-    let pinned_addr = format!("pinned:{}", client.class.clone());
-    if let Some(index) = dock_order.0.iter().position(|a| a == &pinned_addr) {
-        dock_order.0[index] = client.address.clone();
-    }
-}
-
-fn handle_close_pinned_window(
-    entity: Entity,
-    address: &str,
-    class: &str,
-    sprite: &mut Sprite,
-    dock_order: &mut DockOrder,
-    commands: &mut Commands,
-) {
-    let pinned_addr = format!("pinned:{}", class);
-    commands
-        .entity(entity)
-        .insert(ClientAddress(pinned_addr.clone()));
-    update_sprite_alpha(sprite, true, false);
-
-    if let Some(index) = dock_order.0.iter().position(|a| a == address) {
-        dock_order.0[index] = pinned_addr;
-    }
+        .run();
 }
-
-
-
@@ -0,0 +1,344 @@
+//! Bevy plugins that make up the dock
+//!
+//! `AnnyDockPlugin` is all an app needs; the sub-plugins are added by it and
+//! exist so each part of the dock registers its own systems in its `DockSet`.
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+use crate::backend::{self, BackendKind, WindowManager};
+use crate::components::Favorites;
+use crate::config::{get_config_path, load_config, Config, ConfigPath};
+use crate::control::{self, ControlSocketPath};
+use crate::desktop::DesktopEntryIndex;
+use crate::icon::{IconCache, IconResolver};
+use crate::systems::animation::ScrollAnimationState;
+use crate::systems::*;
+use crate::types::*;
+use crate::utils::hover::{hover_animation_system, hover_system};
+use crate::utils::{default_favorites_path, load_favorites, FavoritesPath, IconAnimationState};
+
+/// The whole dock: resources, events, system sets and every sub-plugin
+///
+/// Doesn't add `DefaultPlugins` or set up the window, so it can run inside
+/// another app. `Config`, `Favorites`, `WindowManager`, `ClientList` and
+/// `DesktopEntryIndex` already in the app are kept; the rest are loaded from
+/// the paths below or, for the index, the XDG data directories.
+/// Windows outside the dock's monitor or workspace, as `[monitors]` in the
+/// config asks, are left out of `ClientList`.
+#[derive(Debug, Clone)]
+pub struct AnnyDockPlugin {
+    /// Config file to load and watch, `None` to use the defaults
+    pub config_path: Option<PathBuf>,
+    /// Where pinned applications are saved, `None` to keep them in memory
+    pub favorites_path: Option<PathBuf>,
+    /// Overrides `backend` from the config
+    pub backend: Option<BackendKind>,
+    /// Monitor to open the dock on, by connector name, overriding `monitors.output`
+    pub monitor: Option<String>,
+    /// Control socket to listen on, `None` to disable scripting
+    pub control_socket: Option<PathBuf>,
+}
+
+impl Default for AnnyDockPlugin {
+    fn default() -> Self {
+        Self {
+            config_path: get_config_path(),
+            favorites_path: default_favorites_path(),
+            backend: None,
+            monitor: None,
+            control_socket: Some(control::socket_path()),
+        }
+    }
+}
+
+impl Plugin for AnnyDockPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Config>() {
+            app.insert_resource(load_config(self.config_path.as_deref()));
+        }
+        if !app.world.contains_resource::<WindowManager>() {
+            let kind = self.backend.unwrap_or(app.world.resource::<Config>().backend);
+            app.insert_resource(backend::connect(kind));
+        }
+        let window_manager = app.world.resource::<WindowManager>().clone();
+        let client_list = match app.world.remove_resource::<ClientList>() {
+            Some(client_list) => client_list.0,
+            None => window_manager.list_windows().unwrap_or_else(|e| {
+                warn!("Failed to list windows: {}", e);
+                Vec::new()
+            }),
+        };
+        let monitors = window_manager.monitors().unwrap_or_else(|e| {
+            warn!("Failed to list monitors: {}", e);
+            Vec::new()
+        });
+        let config = app.world.resource::<Config>();
+        let target_monitor = self
+            .monitor
+            .clone()
+            .or_else(|| Some(config.monitors.output.clone()).filter(|output| !output.is_empty()));
+        let mut monitor_filter =
+            MonitorFilter::new(config.monitors.windows, target_monitor.clone(), &monitors);
+        app.insert_resource(ClientList(monitor_filter.resync(client_list)));
+        let favorites_path = FavoritesPath(self.favorites_path.clone());
+        if !app.world.contains_resource::<Favorites>() {
+            app.insert_resource(load_favorites(&favorites_path));
+        }
+        if !app.world.contains_resource::<DesktopEntryIndex>() {
+            app.insert_resource(DesktopEntryIndex::scan());
+        }
+
+        app.insert_resource(favorites_path)
+            .insert_resource(ConfigPath(self.config_path.clone()))
+            .insert_resource(ControlSocketPath(self.control_socket.clone()))
+            .insert_resource(TargetMonitor(target_monitor))
+            .insert_resource(monitor_filter)
+            .insert_resource(ShowTitles(false))
+            .insert_resource(IconResolver::with_default_strategies())
+            .insert_resource(IconCache::new())
+            .init_resource::<IconPositions>()
+            .init_resource::<UiState>()
+            .init_resource::<ReorderTrigger>()
+            .init_resource::<DockOrder>()
+            .init_resource::<ScrollState>()
+            .init_resource::<WindowManagerStatus>()
+            .init_resource::<FocusedWindow>()
+            .init_resource::<DockVisibility>()
+            .init_resource::<ContextMenu>()
+            .init_resource::<IconAnimationState>()
+            .init_resource::<ScrollAnimationState>()
+            .add_event::<IconRemovedEvent>()
+            .add_event::<ContextMenuAction>()
+            .add_event::<WindowFocused>()
+            .add_event::<WindowTitleChanged>()
+            .add_event::<WindowWorkspaceChanged>()
+            .add_event::<WorkspaceChanged>()
+            .add_event::<MonitorFocused>()
+            .add_event::<FullscreenChanged>()
+            .add_event::<WindowUrgent>()
+            .add_event::<FloatingModeChanged>()
+            .add_event::<MonitorAdded>()
+            .add_event::<MonitorRemoved>()
+            .add_event::<WindowManagerConfigReloaded>()
+            .configure_sets(
+                Update,
+                (
+                    DockSet::Input,
+                    DockSet::Hover,
+                    DockSet::Activate,
+                    DockSet::Favorites,
+                    DockSet::Drag,
+                    DockSet::Layout,
+                    DockSet::WindowManager,
+                    DockSet::Visuals,
+                )
+                    .chain(),
+            )
+            .add_plugins((
+                InputPlugin,
+                HoverPlugin,
+                FavoritesPlugin,
+                DragPlugin,
+                LayoutPlugin,
+                HyprlandPlugin,
+                WorkspacePlugin,
+                VisibilityPlugin,
+            ))
+            .add_systems(
+                Startup,
+                (
+                    setup_indicator_textures,
+                    setup_config_watcher,
+                    setup_desktop_entry_watcher,
+                    setup_control_socket,
+                ),
+            )
+            // Icons pinned through the socket must exist before the reorder
+            .add_systems(
+                Update,
+                (
+                    reload_config_system,
+                    refresh_desktop_entries,
+                    process_control_requests,
+                    apply_deferred,
+                )
+                    .chain()
+                    .in_set(DockSet::Input),
+            )
+            .add_systems(
+                Update,
+                (start_attention_animations, attention_animation_system)
+                    .chain()
+                    .in_set(DockSet::Hover)
+                    .before(hover_animation_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_window_indicators,
+                    update_window_count_indicators,
+                    update_connection_banner,
+                    move_to_target_monitor,
+                )
+                    .in_set(DockSet::Visuals),
+            );
+    }
+}
+
+/// Scrolling, keybinds and clicking icons
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                scroll_system,
+                scroll_with_arrows.run_if(not(context_menu_open)),
+                toggle_titles,
+                exit_on_esc_or_q,
+            )
+                .chain()
+                .in_set(DockSet::Input),
+        )
+        .add_systems(
+            Update,
+            (icon_click_system, keybind_launch_visible_icons)
+                .chain()
+                .run_if(not(context_menu_open))
+                .in_set(DockSet::Activate),
+        );
+    }
+}
+
+/// Hover detection, icon animations and the titles that follow them
+pub struct HoverPlugin;
+
+impl Plugin for HoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                hover_system,
+                hover_animation_system,
+                icon_scale_animation_system,
+                collect_icon_data,
+                update_text_positions,
+            )
+                .chain()
+                .in_set(DockSet::Hover),
+        );
+    }
+}
+
+/// The right-click menu, with pinning, unpinning and window actions
+pub struct FavoritesPlugin;
+
+impl Plugin for FavoritesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                context_menu_input,
+                open_context_menu,
+                run_context_menu_actions.in_set(StateUpdate),
+                draw_context_menu,
+            )
+                .chain()
+                .in_set(DockSet::Favorites),
+        );
+    }
+}
+
+/// Dragging icons to reorder the dock
+pub struct DragPlugin;
+
+impl Plugin for DragPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                drag_register_click_system.run_if(not(context_menu_open)),
+                drag_check_system,
+                drag_update_system,
+                drag_end_system.in_set(StateUpdate),
+                reset_positions_system,
+            )
+                .chain()
+                .in_set(DockSet::Drag),
+        );
+    }
+}
+
+/// The camera, the first icons and keeping icons in `DockOrder`
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (cleanup_duplicate_cameras, reorder_icons_system.in_set(ReorderIcons))
+                .chain()
+                .in_set(DockSet::Layout),
+        );
+    }
+}
+
+/// Window manager events, from Hyprland or whichever backend `WindowManager` holds
+pub struct HyprlandPlugin;
+
+impl Plugin for HyprlandPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_window_manager_events).add_systems(
+            Update,
+            (process_hyprland_events, track_focused_window)
+                .chain()
+                .in_set(DockSet::WindowManager),
+        );
+    }
+}
+
+/// Separators between workspaces and the workspace switcher strip
+pub struct WorkspacePlugin;
+
+impl Plugin for WorkspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            workspace_strip_click
+                .run_if(not(context_menu_open))
+                .in_set(DockSet::Activate),
+        )
+            .add_systems(
+                Update,
+                (update_workspace_separators, update_workspace_strip).in_set(DockSet::Visuals),
+            );
+    }
+}
+
+/// Auto-hide and intellihide, sliding the icons off screen and back
+pub struct VisibilityPlugin;
+
+impl Plugin for VisibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_geometry_watcher)
+            .add_systems(
+                Update,
+                update_dock_visibility
+                    .in_set(DockSet::Hover)
+                    .after(hover_system)
+                    .before(hover_animation_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    (request_desktop_geometry, receive_desktop_geometry).chain(),
+                    hide_workspace_widgets
+                        .after(update_workspace_separators)
+                        .after(update_workspace_strip),
+                )
+                    .in_set(DockSet::Visuals),
+            );
+    }
+}
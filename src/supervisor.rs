@@ -0,0 +1,304 @@
+//! One dock per monitor, each in its own process
+//!
+//! The docks are this binary started again with `--monitor` and `--socket`.
+//! The supervisor starts and stops them as monitors come and go, and listens
+//! on the usual control socket (or `--socket`) so `anny-dock pin` and friends
+//! reach them all.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::Duration;
+
+use anny_dock::backend::WindowManager;
+use anny_dock::control::{self, ControlError, ControlReply, ControlRequest};
+use anny_dock::types::HyprIpcEvent;
+use clap::ValueEnum;
+
+use crate::cli::Options;
+
+/// How often monitor events and exited docks are checked for between requests
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Dock {
+    process: Child,
+    socket: PathBuf,
+}
+
+struct Supervisor {
+    exe: PathBuf,
+    options: Options,
+    docks: BTreeMap<String, Dock>,
+    focused_monitor: Option<String>,
+}
+
+/// Run a dock on every monitor until the last one exits, returning the exit code
+///
+/// Returns `None` without starting anything when the window manager doesn't
+/// list monitors, so a single dock can run instead.
+pub fn run(options: &Options, window_manager: &WindowManager) -> Option<i32> {
+    let monitors = match window_manager.monitors() {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        Ok(_) => {
+            eprintln!(
+                "{} doesn't list monitors, starting a single dock",
+                window_manager.name()
+            );
+            return None;
+        }
+        Err(e) => {
+            eprintln!("Failed to list monitors, starting a single dock: {}", e);
+            return None;
+        }
+    };
+
+    let requests = match control::listen(&options.socket_path()) {
+        Ok(requests) => requests,
+        Err(e) => {
+            eprintln!("anny-dock: {}", e);
+            return Some(1);
+        }
+    };
+    let (sender, events) = channel();
+    if let Err(e) = window_manager.subscribe(sender) {
+        eprintln!(
+            "Failed to follow monitors, docks won't be added or removed: {}",
+            e
+        );
+    }
+
+    let mut supervisor = Supervisor {
+        exe: std::env::current_exe().unwrap_or_else(|_| PathBuf::from("anny-dock")),
+        options: options.clone(),
+        docks: BTreeMap::new(),
+        focused_monitor: monitors.iter().find(|m| m.focused).map(|m| m.name.clone()),
+    };
+    for monitor in &monitors {
+        supervisor.start(&monitor.name);
+    }
+
+    let requests = requests.0.lock().unwrap();
+    loop {
+        for event in events.try_iter() {
+            supervisor.handle_event(event);
+        }
+
+        match requests.recv_timeout(POLL_INTERVAL) {
+            Ok((request, reply)) => {
+                let quit = request == ControlRequest::Quit;
+                let _ = reply.send(supervisor.forward(&request));
+                if quit {
+                    supervisor.wait_all();
+                    return Some(0);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Some(1),
+        }
+
+        // Docks closed by the user don't come back; the last one ends the session
+        if supervisor.reap() && supervisor.docks.is_empty() {
+            return Some(0);
+        }
+    }
+}
+
+impl Supervisor {
+    fn start(&mut self, monitor: &str) {
+        if self.docks.contains_key(monitor) {
+            return;
+        }
+
+        let socket = control::monitor_socket_path(monitor);
+        match dock_command(&self.exe, &self.options, monitor, &socket).spawn() {
+            Ok(process) => {
+                self.docks
+                    .insert(monitor.to_string(), Dock { process, socket });
+            }
+            Err(e) => eprintln!("Failed to start the dock on {}: {}", monitor, e),
+        }
+    }
+
+    fn stop(&mut self, monitor: &str) {
+        if let Some(mut dock) = self.docks.remove(monitor) {
+            let _ = dock.process.kill();
+            let _ = dock.process.wait();
+        }
+    }
+
+    fn handle_event(&mut self, event: HyprIpcEvent) {
+        match event {
+            HyprIpcEvent::MonitorAdded { name } => self.start(&name),
+            HyprIpcEvent::MonitorRemoved { name } => self.stop(&name),
+            HyprIpcEvent::FocusedMonitor { monitor, .. } => self.focused_monitor = Some(monitor),
+            _ => {}
+        }
+    }
+
+    /// Pass `request` on to the docks it concerns, answering with the first reply
+    fn forward(&self, request: &ControlRequest) -> ControlReply {
+        let sockets: Vec<&Path> = if is_broadcast(request) {
+            self.docks
+                .values()
+                .map(|dock| dock.socket.as_path())
+                .collect()
+        } else {
+            self.focused_dock()
+                .map(|dock| dock.socket.as_path())
+                .into_iter()
+                .collect()
+        };
+
+        let replies: Vec<ControlReply> = sockets
+            .into_iter()
+            .map(|socket| match control::send(socket, request) {
+                Ok(reply) => reply,
+                Err(ControlError::Rejected(message)) => ControlReply::error(message),
+                Err(e) => ControlReply::error(e.to_string()),
+            })
+            .collect();
+        replies
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| ControlReply::error("no dock is running"))
+    }
+
+    /// The dock on the focused monitor, or any dock when that isn't known
+    fn focused_dock(&self) -> Option<&Dock> {
+        self.focused_monitor
+            .as_ref()
+            .and_then(|monitor| self.docks.get(monitor))
+            .or_else(|| self.docks.values().next())
+    }
+
+    /// Forget docks that exited, returning whether there were any
+    fn reap(&mut self) -> bool {
+        let exited: Vec<String> = self
+            .docks
+            .iter_mut()
+            .filter_map(|(monitor, dock)| {
+                (!matches!(dock.process.try_wait(), Ok(None))).then(|| monitor.clone())
+            })
+            .collect();
+
+        for monitor in &exited {
+            self.docks.remove(monitor);
+        }
+        !exited.is_empty()
+    }
+
+    fn wait_all(&mut self) {
+        for dock in self.docks.values_mut() {
+            let _ = dock.process.wait();
+        }
+    }
+}
+
+/// Whether every dock should get `request`, rather than only the focused one
+///
+/// Indexes and listings refer to a single dock's icons, and launching twice
+/// would open two windows.
+fn is_broadcast(request: &ControlRequest) -> bool {
+    match request {
+        ControlRequest::Pin { .. }
+        | ControlRequest::Unpin { .. }
+        | ControlRequest::ToggleTitles
+        | ControlRequest::Show
+        | ControlRequest::Hide
+        | ControlRequest::ReloadConfig
+        | ControlRequest::Quit => true,
+        ControlRequest::Focus { .. }
+        | ControlRequest::Launch { .. }
+        | ControlRequest::Reorder { .. }
+        | ControlRequest::Scroll { .. }
+        | ControlRequest::List
+        | ControlRequest::DumpState => false,
+    }
+}
+
+/// This binary, started as the dock on `monitor` with the supervisor's options
+fn dock_command(exe: &Path, options: &Options, monitor: &str, socket: &Path) -> Command {
+    let mut command = Command::new(exe);
+    if let Some(path) = &options.config_path {
+        command.arg("--config").arg(path);
+    }
+    if let Some(path) = &options.favorites_path {
+        command.arg("--favorites").arg(path);
+    }
+    if let Some(value) = options
+        .backend
+        .and_then(|backend| backend.to_possible_value())
+    {
+        command.arg("--backend").arg(value.get_name());
+    }
+    command
+        .arg("--log-level")
+        .arg(options.log_level.as_str().to_lowercase())
+        .arg("--monitor")
+        .arg(monitor)
+        .arg("--socket")
+        .arg(socket);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anny_dock::backend::BackendKind;
+    use bevy::log::Level;
+
+    #[test]
+    fn test_dock_command_passes_options_on() {
+        let options = Options {
+            config_path: Some(PathBuf::from("/tmp/dock.toml")),
+            favorites_path: None,
+            backend: Some(BackendKind::Sway),
+            log_level: Level::DEBUG,
+            monitor: None,
+            socket: None,
+        };
+
+        let command = dock_command(
+            Path::new("/usr/bin/anny-dock"),
+            &options,
+            "DP-1",
+            Path::new("/run/anny-dock-DP-1.sock"),
+        );
+        let args: Vec<&str> = command
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect();
+
+        assert_eq!(
+            args,
+            vec![
+                "--config",
+                "/tmp/dock.toml",
+                "--backend",
+                "sway",
+                "--log-level",
+                "debug",
+                "--monitor",
+                "DP-1",
+                "--socket",
+                "/run/anny-dock-DP-1.sock",
+            ]
+        );
+        assert!(crate::cli::command()
+            .try_get_matches_from(std::iter::once("anny-dock").chain(args))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_only_dock_wide_requests_are_broadcast() {
+        assert!(is_broadcast(&ControlRequest::Pin {
+            class: "kitty".to_string()
+        }));
+        assert!(is_broadcast(&ControlRequest::Quit));
+        assert!(!is_broadcast(&ControlRequest::Focus { index: 0 }));
+        assert!(!is_broadcast(&ControlRequest::Launch {
+            class: "kitty".to_string()
+        }));
+    }
+}
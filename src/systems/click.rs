@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::backend::WindowManager;
+use crate::components::Favorites;
+use crate::desktop::DesktopEntryIndex;
+use crate::config::Config;
+use crate::types::*;
+use crate::utils::launch_application;
+
+type ClickableIcon<'a> = (
+    &'a ClientAddress,
+    &'a ClientClass,
+    &'a HoverTarget,
+    &'a Transform,
+    Option<&'a mut WindowGroup>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn icon_click_system(
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut q_icons: Query<ClickableIcon>,
+    mouse_button: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_state: Res<UiState>,
+    config: Res<Config>,
+    window_manager: Res<WindowManager>,
+    favorites: Res<Favorites>,
+    desktop_entries: Res<DesktopEntryIndex>,
+) {
+    if mouse_button.just_released(MouseButton::Left) && ui_state.dragging.is_none() {
+        let window = windows.single();
+        if let Some(cursor_pos) = window.cursor_position() {
+            if let Ok((camera, camera_transform)) = q_camera.get_single() {
+                if let Some(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
+                    for (address, client_class, hover, transform, group) in q_icons.iter_mut() {
+                        let icon_position = transform.translation.truncate();
+                        let size = Vec2::splat(config.icon_size);
+                        let rect = Rect::from_center_size(icon_position, size);
+                        if rect.contains(world_pos) && hover.is_hovered {
+                            if address.0.starts_with("pinned:") {
+                                launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, &client_class.0);
+                            } else {
+                                focus_group(&window_manager, &address.0, group);
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Focuses the next window of the icon's group, or the icon's own address if it has no group
+pub fn focus_group(window_manager: &WindowManager, address: &str, group: Option<Mut<WindowGroup>>) {
+    match group.and_then(|mut group| group.next_address()) {
+        Some(next) => focus_client(window_manager, &next),
+        None => focus_client(window_manager, address),
+    }
+}
+
+pub fn focus_client(window_manager: &WindowManager, address: &str) {
+    if let Err(e) = window_manager.focus(address) {
+        warn!("Failed to focus window: {}", e);
+    }
+}
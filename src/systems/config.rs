@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use crate::types::*;
+use crate::config::{watch_config, Config, ConfigPath, ConfigReloadReceiver};
+
+pub fn setup_config_watcher(mut commands: Commands, config_path: Res<ConfigPath>) {
+    let Some(path) = config_path.0.clone() else {
+        return;
+    };
+
+    match watch_config(path) {
+        Ok(receiver) => commands.insert_resource(receiver),
+        Err(e) => warn!("Failed to watch config file, hot reload disabled: {}", e),
+    }
+}
+
+/// Applies re-parsed configs, keeping the last good one when a reload fails
+pub fn reload_config_system(
+    receiver: Option<Res<ConfigReloadReceiver>>,
+    mut config: ResMut<Config>,
+    mut reorder_trigger: ResMut<ReorderTrigger>,
+) {
+    let Some(receiver) = receiver else {
+        return;
+    };
+
+    let receiver = receiver.0.lock().unwrap();
+    while let Ok(result) = receiver.try_recv() {
+        match result {
+            Ok(new_config) => apply_config(&mut config, &mut reorder_trigger, new_config),
+            Err(e) => error!("Config not reloaded, keeping the last good one: {}", e),
+        }
+    }
+}
+
+/// Replace the running config with `new_config`, relaying out icons if anything changed
+pub fn apply_config(config: &mut Config, reorder_trigger: &mut ReorderTrigger, new_config: Config) {
+    if new_config == *config {
+        return;
+    }
+    if new_config.backend != config.backend {
+        warn!("Changing `backend` only takes effect after a restart");
+    }
+    info!("Config reloaded");
+    *config = new_config;
+    reorder_trigger.0 = true;
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::types::*;
+use crate::config::Config;
+
+const BANNER_TEXT: &str = "Disconnected - reconnecting...";
+const BANNER_FONT_SIZE: f32 = 14.0;
+const DISCONNECTED_TINT: f32 = 0.45;
+
+/// Shows a banner and greys out the icons while the window manager is unreachable
+pub fn update_connection_banner(
+    mut commands: Commands,
+    status: Res<WindowManagerStatus>,
+    q_banner: Query<Entity, With<DisconnectedBanner>>,
+    mut q_icons: Query<&mut Sprite, With<ClientClass>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    config: Res<Config>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+
+    let disconnected = *status == WindowManagerStatus::Disconnected;
+    let tint = if disconnected { DISCONNECTED_TINT } else { 1.0 };
+    for mut sprite in q_icons.iter_mut() {
+        let alpha = sprite.color.a();
+        sprite.color = Color::rgba(tint, tint, tint, alpha);
+    }
+
+    match (disconnected, q_banner.get_single()) {
+        (true, Err(_)) => {
+            let window = windows.single();
+            let x = -window.width() / 2.0 + config.margin_x;
+            let y = -window.height() / 2.0 + config.margin_y + config.icon_size;
+
+            commands
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        BANNER_TEXT,
+                        TextStyle {
+                            font: TextStyle::default().font,
+                            font_size: BANNER_FONT_SIZE,
+                            color: Color::rgb(1.0, 0.6, 0.4),
+                        },
+                    )
+                    .with_alignment(TextAlignment::Left),
+                    text_anchor: bevy::sprite::Anchor::CenterLeft,
+                    transform: Transform::from_xyz(x, y, 50.0),
+                    ..default()
+                })
+                .insert(DisconnectedBanner);
+        }
+        (false, Ok(banner)) => {
+            commands.entity(banner).despawn_recursive();
+        }
+        _ => {}
+    }
+}
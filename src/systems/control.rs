@@ -0,0 +1,497 @@
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::backend::WindowManager;
+use crate::components::{
+    add_client_address, add_favorite, add_icon_text, apply_pin_icon, Favorite, FavoritePin,
+    Favorites, IconSpawner,
+};
+use crate::desktop::DesktopEntryIndex;
+use crate::config::{read_config, Config, ConfigPath};
+use crate::control::{
+    self, ControlReceiver, ControlReply, ControlRequest, ControlSocketPath, DockItem, DockState,
+};
+use crate::icon::IconContext;
+use crate::layout::dock_layout;
+use crate::systems::{apply_config, max_scroll};
+use crate::types::*;
+use crate::utils::{launch_application, save_favorites, FavoritesChange, FavoritesPath};
+use crate::systems::{focus_group, toggle_favorite, PinnedIcon, Pinning};
+
+type ControlIconQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static ClientClass,
+        Option<&'static mut Sprite>,
+        Option<&'static ClientAddress>,
+        Option<&'static Favorite>,
+        Option<&'static Children>,
+        Option<&'static mut WindowGroup>,
+    ),
+>;
+
+/// Dock state scripts can look at and change, other than its icons
+#[derive(SystemParam)]
+pub struct DockView<'w, 's> {
+    scroll_state: ResMut<'w, ScrollState>,
+    show_titles: ResMut<'w, ShowTitles>,
+    focused: Res<'w, FocusedWindow>,
+    status: Res<'w, WindowManagerStatus>,
+    visibility: ResMut<'w, DockVisibility>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+}
+
+impl DockView<'_, '_> {
+    /// Whether any of the dock is on screen, rather than slid all the way out
+    fn is_visible(&self) -> bool {
+        self.visibility.progress < 1.0
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.visibility.hold_hidden(!visible);
+    }
+}
+
+impl IconSpawner<'_> {
+    /// Spawn a dimmed icon for a pinned application with no open window
+    fn spawn_pinned(
+        &mut self,
+        commands: &mut Commands,
+        view: &DockView,
+        config: &Config,
+        favorites: &Favorites,
+        dock_order: &mut DockOrder,
+        class: &str,
+    ) {
+        let pinned_address = format!("pinned:{}", class);
+        dock_order.0.push(pinned_address.clone());
+
+        let Ok(window) = view.windows.get_single() else {
+            return;
+        };
+        let index = dock_order.0.len() - 1;
+        let (translation, scale) =
+            dock_layout(config, window).icon_transform(index, dock_order.0.len(), 0.0);
+        let transform = Transform {
+            translation,
+            scale: Vec3::splat(scale),
+            ..default()
+        };
+
+        let entity = self.spawn(commands, &IconContext::new(class.to_string()), transform, 0.5, index);
+        add_client_address(commands, entity, pinned_address);
+        commands.entity(entity).insert(WindowGroup::default());
+        add_favorite(commands, entity, &mut self.images, config);
+        if let Some(pin) = favorites.get(class) {
+            apply_pin_icon(commands, &mut self.images, entity, pin);
+        }
+        if view.show_titles.0 {
+            add_icon_text(
+                commands,
+                entity,
+                favorites.label(class),
+                transform,
+                scale,
+                &self.asset_server,
+                config,
+            );
+        }
+    }
+}
+
+pub fn setup_control_socket(mut commands: Commands, socket_path: Res<ControlSocketPath>) {
+    let Some(path) = &socket_path.0 else {
+        return;
+    };
+
+    match control::listen(path) {
+        Ok(receiver) => commands.insert_resource(receiver),
+        Err(e) => warn!("Failed to open control socket, scripting disabled: {}", e),
+    }
+}
+
+/// Icons in dock order, as reported by `list` and `dump-state`
+fn dock_items(dock_order: &DockOrder, q_icons: &ControlIconQuery) -> Vec<DockItem> {
+    dock_order
+        .0
+        .iter()
+        .filter_map(|address| {
+            q_icons
+                .iter()
+                .find(|(_, _, _, icon_address, ..)| icon_address.is_some_and(|a| &a.0 == address))
+                .map(|(_, class, _, _, favorite, _, group)| DockItem {
+                    class: class.0.clone(),
+                    pinned: favorite.is_some(),
+                    windows: group.map(|g| g.addresses.clone()).unwrap_or_default(),
+                })
+        })
+        .collect()
+}
+
+/// Answers requests from `anny-dock <command>` and other scripts
+///
+/// Every request is answered in the frame it arrives, doing what the mouse
+/// and keyboard handlers do for the same action.
+#[allow(clippy::too_many_arguments)]
+pub fn process_control_requests(
+    mut commands: Commands,
+    receiver: Option<Res<ControlReceiver>>,
+    mut favorites: ResMut<Favorites>,
+    favorites_path: Res<FavoritesPath>,
+    mut dock_order: ResMut<DockOrder>,
+    mut reorder_trigger: ResMut<ReorderTrigger>,
+    mut config: ResMut<Config>,
+    config_path: Res<ConfigPath>,
+    mut q_icons: ControlIconQuery,
+    q_pins: Query<Entity, With<FavoritePin>>,
+    mut spawner: IconSpawner,
+    mut view: DockView,
+    window_manager: Res<WindowManager>,
+    desktop_entries: Res<DesktopEntryIndex>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(receiver) = receiver else {
+        return;
+    };
+
+    let receiver = receiver.0.lock().unwrap();
+    while let Ok((request, reply_sender)) = receiver.try_recv() {
+        let reply = match request {
+            ControlRequest::Focus { index } => match dock_order.0.get(index) {
+                Some(address) => {
+                    let icon = q_icons.iter_mut().find(|(_, _, _, icon_address, ..)| {
+                        icon_address.is_some_and(|a| &a.0 == address)
+                    });
+                    if let Some((_, class, _, _, _, _, group)) = icon {
+                        if address.starts_with("pinned:") {
+                            launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, &class.0);
+                        } else {
+                            focus_group(&window_manager, address, group);
+                        }
+                    }
+                    ControlReply::ok()
+                }
+                None => ControlReply::error(format!("no icon at index {}", index)),
+            },
+            ControlRequest::Launch { ref class } => {
+                launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, class);
+                ControlReply::ok()
+            }
+            ControlRequest::Pin { ref class } | ControlRequest::Unpin { ref class } => {
+                let pin = matches!(request, ControlRequest::Pin { .. });
+                if favorites.contains(class) == pin {
+                    let state = if pin { "already" } else { "not" };
+                    ControlReply::error(format!("{} is {} pinned", class, state))
+                } else {
+                    match q_icons.iter_mut().find(|(_, icon_class, ..)| &icon_class.0 == class) {
+                        Some((entity, _, Some(mut sprite), address, favorite, children, _)) => {
+                            toggle_favorite(
+                                &mut commands,
+                                &mut spawner.images,
+                                &config,
+                                &q_pins,
+                                Pinning {
+                                    favorites: &mut favorites,
+                                    favorites_path: &favorites_path,
+                                    reorder_trigger: &mut reorder_trigger,
+                                    dock_order: &mut dock_order,
+                                },
+                                PinnedIcon {
+                                    entity,
+                                    class,
+                                    is_favorite: favorite.is_some(),
+                                    sprite: &mut sprite,
+                                    address,
+                                    children,
+                                },
+                            );
+                        }
+                        _ if pin => {
+                            info!("Adding favorite: {}", class);
+                            favorites.add(class);
+                            save_favorites(&favorites_path, &favorites, FavoritesChange::Pin(class.clone()));
+                            spawner.spawn_pinned(
+                                &mut commands,
+                                &view,
+                                &config,
+                                &favorites,
+                                &mut dock_order,
+                                class,
+                            );
+                            reorder_trigger.0 = true;
+                        }
+                        _ => {
+                            favorites.remove(class);
+                            save_favorites(&favorites_path, &favorites, FavoritesChange::Unpin(class.clone()));
+                        }
+                    }
+                    ControlReply::ok()
+                }
+            }
+            ControlRequest::Reorder { from, to } => {
+                let len = dock_order.0.len();
+                if from >= len || to >= len {
+                    ControlReply::error(format!("index out of range, the dock has {} icons", len))
+                } else {
+                    let address = dock_order.0.remove(from);
+                    dock_order.0.insert(to, address);
+
+                    // Pinned icons are laid out in favorites order, so it has to follow
+                    let pinned_classes: Vec<String> = dock_items(&dock_order, &q_icons)
+                        .into_iter()
+                        .filter(|item| item.pinned)
+                        .map(|item| item.class)
+                        .collect();
+                    if favorites.classes().ne(pinned_classes.iter().map(String::as_str)) {
+                        favorites.reorder(&pinned_classes);
+                        save_favorites(&favorites_path, &favorites, FavoritesChange::Reorder(pinned_classes));
+                    }
+                    ControlReply::ok()
+                }
+            }
+            ControlRequest::Scroll { delta } => {
+                let max = max_scroll(dock_order.0.len(), &config);
+                let distance = view.scroll_state.total_scroll_distance + delta * config.spacing;
+                view.scroll_state.total_scroll_distance = distance.clamp(0.0, max);
+                ControlReply::ok()
+            }
+            ControlRequest::ToggleTitles => {
+                view.show_titles.0 = !view.show_titles.0;
+                ControlReply::ok()
+            }
+            ControlRequest::Show | ControlRequest::Hide => {
+                view.set_visible(request == ControlRequest::Show);
+                ControlReply::ok()
+            }
+            ControlRequest::List => ControlReply::data(dock_items(&dock_order, &q_icons)),
+            ControlRequest::ReloadConfig => match &config_path.0 {
+                Some(path) => match read_config(path) {
+                    Ok(new_config) => {
+                        apply_config(&mut config, &mut reorder_trigger, new_config);
+                        ControlReply::ok()
+                    }
+                    Err(e) => ControlReply::error(e.to_string()),
+                },
+                None => ControlReply::error("no config file"),
+            },
+            ControlRequest::DumpState => ControlReply::data(DockState {
+                items: dock_items(&dock_order, &q_icons),
+                focused: view.focused.0.clone(),
+                scroll: view.scroll_state.total_scroll_distance / config.spacing,
+                show_titles: view.show_titles.0,
+                visible: view.is_visible(),
+                connected: *view.status == WindowManagerStatus::Connected,
+            }),
+            ControlRequest::Quit => {
+                exit.send(AppExit);
+                ControlReply::ok()
+            }
+        };
+        let _ = reply_sender.send(reply);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{mock_client, MockCall};
+    use crate::components::Pin;
+    use crate::control::ControlError;
+    use crate::testing::DockHarness;
+
+    /// A dock showing pinned `firefox` with two windows, then `kitty`
+    fn dock() -> DockHarness {
+        DockHarness::with_favorites(
+            vec![
+                mock_client("0x1", "firefox", "1"),
+                mock_client("0x2", "firefox", "1"),
+                mock_client("0x3", "kitty", "1"),
+            ],
+            Favorites(vec![Pin {
+                command: Some("firefox --new-window".to_string()),
+                ..Pin::new("firefox")
+            }]),
+        )
+    }
+
+    fn classes(items: &[DockItem]) -> Vec<&str> {
+        items.iter().map(|item| item.class.as_str()).collect()
+    }
+
+    #[test]
+    fn test_focus_cycles_through_group() {
+        let mut dock = dock();
+
+        dock.send(ControlRequest::Focus { index: 0 }).unwrap();
+        dock.send(ControlRequest::Focus { index: 0 }).unwrap();
+        dock.send(ControlRequest::Focus { index: 1 }).unwrap();
+
+        let focused = ["0x1", "0x2", "0x3"].map(|a| MockCall::Focus(a.to_string()));
+        assert_eq!(dock.backend.calls(), focused);
+        assert!(matches!(
+            dock.send(ControlRequest::Focus { index: 5 }),
+            Err(ControlError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_launch_uses_pinned_command() {
+        let mut dock = dock();
+
+        dock.send(ControlRequest::Launch {
+            class: "firefox".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            dock.backend.calls(),
+            vec![MockCall::Launch("firefox --new-window".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_list_and_dump_state() {
+        let mut dock = dock();
+        dock.send(ControlRequest::Focus { index: 1 }).unwrap();
+
+        let reply = dock.send(ControlRequest::List).unwrap();
+        let items: Vec<DockItem> = serde_json::from_value(reply.data.unwrap()).unwrap();
+        assert_eq!(classes(&items), vec!["firefox", "kitty"]);
+        assert!(items[0].pinned);
+        assert_eq!(items[0].windows, vec!["0x1", "0x2"]);
+
+        let reply = dock.send(ControlRequest::DumpState).unwrap();
+        let state: DockState = serde_json::from_value(reply.data.unwrap()).unwrap();
+        assert_eq!(state.items, items);
+        assert_eq!(state.focused.as_deref(), Some("0x3"));
+        assert!(state.connected);
+        assert!(!state.show_titles);
+    }
+
+    #[test]
+    fn test_pin_and_unpin_save_favorites() {
+        let mut dock = dock();
+        let favorites_path = dock.dir().path().join("favorites.json");
+
+        dock.send(ControlRequest::Unpin {
+            class: "firefox".to_string(),
+        })
+        .unwrap();
+        assert!(!std::fs::read_to_string(&favorites_path).unwrap().contains("firefox"));
+
+        dock.send(ControlRequest::Pin {
+            class: "steam".to_string(),
+        })
+        .unwrap();
+        dock.app.update();
+        assert!(dock.icon("steam").is_some());
+        assert!(dock.dock_order().contains(&"pinned:steam".to_string()));
+        assert!(std::fs::read_to_string(&favorites_path).unwrap().contains("steam"));
+
+        let reply = dock.send(ControlRequest::Unpin {
+            class: "firefox".to_string(),
+        });
+        assert!(matches!(reply, Err(ControlError::Rejected(message)) if message == "firefox is not pinned"));
+    }
+
+    #[test]
+    fn test_reorder_moves_pins_too() {
+        let mut dock = dock();
+        dock.send(ControlRequest::Pin {
+            class: "kitty".to_string(),
+        })
+        .unwrap();
+
+        dock.send(ControlRequest::Reorder { from: 1, to: 0 }).unwrap();
+
+        assert_eq!(dock.dock_order(), vec!["0x3", "0x1"]);
+        let favorites = dock.app.world.resource::<Favorites>();
+        assert_eq!(favorites.classes().collect::<Vec<_>>(), vec!["kitty", "firefox"]);
+        assert!(matches!(
+            dock.send(ControlRequest::Reorder { from: 0, to: 2 }),
+            Err(ControlError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_view_commands() {
+        let mut dock = dock();
+        // Fewer visible items than icons, so there is something to scroll
+        dock.app.world.resource_mut::<Config>().visible_items = 1;
+
+        dock.send(ControlRequest::ToggleTitles).unwrap();
+        dock.send(ControlRequest::Scroll { delta: 1.0 }).unwrap();
+
+        assert!(dock.app.world.resource::<ShowTitles>().0);
+        let spacing = dock.app.world.resource::<Config>().spacing;
+        assert_eq!(
+            dock.app.world.resource::<ScrollState>().total_scroll_distance,
+            spacing
+        );
+        let reply = dock.send(ControlRequest::DumpState).unwrap();
+        let state: DockState = serde_json::from_value(reply.data.unwrap()).unwrap();
+        assert!(state.visible);
+    }
+
+    fn is_visible(dock: &mut DockHarness) -> bool {
+        let reply = dock.send(ControlRequest::DumpState).unwrap();
+        serde_json::from_value::<DockState>(reply.data.unwrap()).unwrap().visible
+    }
+
+    #[test]
+    fn test_hide_slides_the_icons_out_until_shown() {
+        let mut dock = dock();
+        dock.app.world.resource_mut::<Config>().visibility.duration = 0.05;
+
+        dock.send(ControlRequest::Hide).unwrap();
+        dock.slide_to(1.0);
+
+        assert!(!is_visible(&mut dock));
+        let distances = dock.distances_from_hidden();
+        assert!(distances.iter().all(|distance| *distance < 0.5), "{:?}", distances);
+
+        // Passar o mouse por cima não traz de volta um dock escondido por comando
+        dock.hover("kitty");
+        dock.app.update();
+        assert_eq!(dock.app.world.resource::<DockVisibility>().progress, 1.0);
+
+        dock.send(ControlRequest::Show).unwrap();
+        dock.slide_to(0.0);
+
+        assert!(is_visible(&mut dock));
+    }
+
+    #[test]
+    fn test_reload_config_reports_errors() {
+        let mut dock = dock();
+        let path = dock.app.world.resource::<ConfigPath>().0.clone().unwrap();
+
+        std::fs::write(&path, "visible_items = 3\n").unwrap();
+        dock.send(ControlRequest::ReloadConfig).unwrap();
+        assert_eq!(dock.app.world.resource::<Config>().visible_items, 3);
+
+        std::fs::write(&path, "visible_items = 0\n").unwrap();
+        assert!(dock.send(ControlRequest::ReloadConfig).is_err());
+        assert_eq!(dock.app.world.resource::<Config>().visible_items, 3);
+    }
+
+    #[test]
+    fn test_quit_and_bad_requests() {
+        let mut dock = dock();
+
+        let mut stream = std::os::unix::net::UnixStream::connect(dock.socket()).unwrap();
+        std::io::Write::write_all(&mut stream, b"{\"command\":\"explode\"}\n").unwrap();
+        let mut line = String::new();
+        std::io::BufRead::read_line(&mut std::io::BufReader::new(stream), &mut line).unwrap();
+        let reply: ControlReply = serde_json::from_str(&line).unwrap();
+        assert!(!reply.ok);
+
+        dock.send(ControlRequest::Quit).unwrap();
+        let exits = dock.app.world.resource::<Events<AppExit>>();
+        assert!(!exits.is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+use crate::desktop::{watch_applications, DesktopEntryIndex, DesktopEntryReceiver};
+
+pub fn setup_desktop_entry_watcher(mut commands: Commands, index: Res<DesktopEntryIndex>) {
+    if index.dirs().is_empty() {
+        return;
+    }
+
+    match watch_applications(&index) {
+        Ok(receiver) => commands.insert_resource(receiver),
+        Err(e) => warn!("Failed to watch application directories: {}", e),
+    }
+}
+
+/// Swaps in the index the watcher rebuilt after applications were installed or removed
+pub fn refresh_desktop_entries(
+    receiver: Option<Res<DesktopEntryReceiver>>,
+    mut index: ResMut<DesktopEntryIndex>,
+) {
+    let Some(receiver) = receiver else {
+        return;
+    };
+
+    let receiver = receiver.0.lock().unwrap();
+    while let Ok(fresh) = receiver.try_recv() {
+        *index = fresh;
+    }
+}
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use crate::types::*;
 use crate::config::Config;
+use crate::layout::dock_layout;
 
 pub fn drag_register_click_system(
     windows: Query<&Window, With<PrimaryWindow>>,
@@ -82,7 +83,7 @@ pub fn drag_update_system(
                             transform.translation =
                                 Vec3::new(new_pos.x, new_pos.y, hover.original_z + 10.0);
 
-                            let base_scale = config.base_scale * config.scale_factor.powi(hover.index as i32);
+                            let base_scale = dock_layout(&config, window).icon_scale(hover.index);
                             transform.scale = Vec3::splat(base_scale * 1.1);
                         }
                     }
@@ -92,6 +93,7 @@ pub fn drag_update_system(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn drag_end_system(
     mut commands: Commands,
     mouse_button: Res<Input<MouseButton>>,
@@ -99,28 +101,36 @@ pub fn drag_end_system(
     mut dock_order: ResMut<DockOrder>,
     q_icons: Query<(Entity, &Transform, &ClientAddress)>,
     mut reorder_trigger: ResMut<ReorderTrigger>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    config: Res<Config>,
 ) {
     if mouse_button.just_released(MouseButton::Left) && ui_state.dragging.is_some() {
         if let Some(dragged_entity) = ui_state.dragging {
             commands.entity(dragged_entity).remove::<Dragging>();
             ui_state.dragging = None;
 
-            let (dragged_x, dragged_address) = q_icons
+            let Ok(window) = windows.get_single() else {
+                return;
+            };
+            let layout = dock_layout(&config, window);
+            let along =
+                |transform: &Transform| layout.position_along(transform.translation.truncate());
+            let (dragged_along, dragged_address) = q_icons
                 .get(dragged_entity)
-                .map(|(_, transform, address)| (transform.translation.x, address.0.clone()))
+                .map(|(_, transform, address)| (along(transform), address.0.clone()))
                 .unwrap_or((0.0, String::new()));
 
             let mut other_icons: Vec<(String, f32)> = q_icons
                 .iter()
                 .filter(|(e, _, _)| *e != dragged_entity)
-                .map(|(_, transform, address)| (address.0.clone(), transform.translation.x))
+                .map(|(_, transform, address)| (address.0.clone(), along(transform)))
                 .collect();
 
             other_icons.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
 
             let new_index = other_icons
                 .iter()
-                .position(|(_, x)| *x > dragged_x)
+                .position(|(_, position)| *position > dragged_along)
                 .unwrap_or(other_icons.len());
 
             let mut new_order = Vec::new();
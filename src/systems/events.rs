@@ -0,0 +1,68 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::types::*;
+
+/// Writers for every window manager event other systems can react to
+#[derive(SystemParam)]
+pub struct WindowManagerEvents<'w> {
+    focused: EventWriter<'w, WindowFocused>,
+    title_changed: EventWriter<'w, WindowTitleChanged>,
+    moved: EventWriter<'w, WindowWorkspaceChanged>,
+    workspace_changed: EventWriter<'w, WorkspaceChanged>,
+    monitor_focused: EventWriter<'w, MonitorFocused>,
+    fullscreen_changed: EventWriter<'w, FullscreenChanged>,
+    urgent: EventWriter<'w, WindowUrgent>,
+    floating_changed: EventWriter<'w, FloatingModeChanged>,
+    monitor_added: EventWriter<'w, MonitorAdded>,
+    monitor_removed: EventWriter<'w, MonitorRemoved>,
+    config_reloaded: EventWriter<'w, WindowManagerConfigReloaded>,
+}
+
+impl WindowManagerEvents<'_> {
+    /// Emit the Bevy event matching `event`
+    ///
+    /// Window open/close and connection changes are handled by `process_hyprland_events`
+    /// itself, and workspace moves by `MonitorFilter`; they are ignored here.
+    pub fn send(&mut self, event: HyprIpcEvent) {
+        match event {
+            HyprIpcEvent::ActiveWindow { address } => self.focused.send(WindowFocused { address }),
+            HyprIpcEvent::WindowTitle { address, title } => {
+                self.title_changed.send(WindowTitleChanged { address, title })
+            }
+            HyprIpcEvent::MoveWindow {
+                address,
+                workspace_id,
+                workspace,
+            } => self.moved.send(WindowWorkspaceChanged {
+                address,
+                workspace_id,
+                workspace,
+            }),
+            HyprIpcEvent::Workspace { id, name } => {
+                self.workspace_changed.send(WorkspaceChanged { id, name })
+            }
+            HyprIpcEvent::FocusedMonitor { monitor, workspace } => self
+                .monitor_focused
+                .send(MonitorFocused { monitor, workspace }),
+            HyprIpcEvent::Fullscreen { enabled } => {
+                self.fullscreen_changed.send(FullscreenChanged { enabled })
+            }
+            HyprIpcEvent::Urgent { address } => self.urgent.send(WindowUrgent { address }),
+            HyprIpcEvent::ChangeFloatingMode { address, floating } => self
+                .floating_changed
+                .send(FloatingModeChanged { address, floating }),
+            HyprIpcEvent::MonitorAdded { name } => self.monitor_added.send(MonitorAdded { name }),
+            HyprIpcEvent::MonitorRemoved { name } => {
+                self.monitor_removed.send(MonitorRemoved { name })
+            }
+            HyprIpcEvent::ConfigReloaded => self.config_reloaded.send(WindowManagerConfigReloaded),
+            HyprIpcEvent::OpenWindow { .. }
+            | HyprIpcEvent::CloseWindow { .. }
+            | HyprIpcEvent::Disconnected
+            | HyprIpcEvent::Resync { .. }
+            | HyprIpcEvent::WorkspaceMoved { .. }
+            | HyprIpcEvent::Other => {}
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy::render::texture::Image;
+
+use crate::components::{add_favorite, Favorite, FavoritePin, Favorites};
+use crate::config::Config;
+use crate::types::*;
+use crate::utils::{save_favorites, update_sprite_alpha, FavoritesChange, FavoritesPath};
+
+/// The dock state pinning or unpinning an icon changes
+pub struct Pinning<'a> {
+    pub favorites: &'a mut Favorites,
+    pub favorites_path: &'a FavoritesPath,
+    pub reorder_trigger: &'a mut ReorderTrigger,
+    pub dock_order: &'a mut DockOrder,
+}
+
+/// The icon being pinned or unpinned
+pub struct PinnedIcon<'a> {
+    pub entity: Entity,
+    pub class: &'a str,
+    pub is_favorite: bool,
+    pub sprite: &'a mut Sprite,
+    pub address: Option<&'a ClientAddress>,
+    pub children: Option<&'a Children>,
+}
+
+pub fn toggle_favorite(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    config: &Config,
+    q_pins: &Query<Entity, With<FavoritePin>>,
+    pinning: Pinning,
+    icon: PinnedIcon,
+) {
+    let Pinning { favorites, favorites_path, reorder_trigger, dock_order } = pinning;
+    let PinnedIcon { entity, class: app_class, is_favorite, sprite, address: q_address, children } = icon;
+
+    if is_favorite {
+        info!("Removing favorite: {}", app_class);
+        favorites.remove(app_class);
+
+        let is_running = q_address.is_some_and(|addr| !addr.0.starts_with("pinned:"));
+
+        if is_running {
+            // App is running, just remove favorite status and pin
+            commands.entity(entity).remove::<Favorite>();
+            if let Some(children) = children {
+                for &child in children.iter() {
+                    if q_pins.get(child).is_ok() {
+                        commands.entity(child).despawn();
+                    }
+                }
+            }
+            // Reorder will be triggered and will move this to non-favorites section
+        } else {
+            // App is not running, it only exists because it was a favorite.
+            // Despawn the whole thing.
+            let pinned_addr = format!("pinned:{}", app_class);
+            dock_order.0.retain(|a| a != &pinned_addr);
+            commands.entity(entity).despawn_recursive();
+        }
+
+        reorder_trigger.0 = true;
+    } else {
+        info!("Adding favorite: {}", app_class);
+        favorites.add(app_class);
+        add_favorite(commands, entity, images, config);
+        
+        // Trigger full reorder to place favorites correctly
+        reorder_trigger.0 = true;
+    }
+    let change = if is_favorite {
+        FavoritesChange::Unpin(app_class.to_string())
+    } else {
+        FavoritesChange::Pin(app_class.to_string())
+    };
+    save_favorites(favorites_path, favorites, change);
+
+    if commands.get_entity(entity).is_some() {
+        let is_running = q_address.is_some_and(|a| !a.0.starts_with("pinned:"));
+        update_sprite_alpha(sprite, !is_favorite, is_running);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::mock_client;
+    use crate::testing::DockHarness;
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "kitty", "2"),
+        ]
+    }
+
+    #[test]
+    fn test_context_menu_pins_and_unpins() {
+        let mut dock = DockHarness::new(clients(), &["steam"]);
+
+        dock.right_click("kitty");
+        dock.choose("Pin");
+        dock.app.update();
+
+        let kitty = dock.icon("kitty").unwrap();
+        assert!(dock.get::<Favorite>(kitty).is_some());
+        let favorites = dock.app.world.resource::<Favorites>();
+        assert_eq!(favorites.classes().collect::<Vec<_>>(), vec!["steam", "kitty"]);
+        assert_eq!(dock.dock_order(), vec!["pinned:steam", "0x2", "0x1"]);
+        let saved = std::fs::read_to_string(dock.dir().path().join("favorites.json")).unwrap();
+        assert!(saved.contains("kitty"));
+
+        dock.right_click("steam");
+        dock.choose("Unpin");
+
+        assert!(dock.icon("steam").is_none());
+        assert_eq!(dock.dock_order(), vec!["0x2", "0x1"]);
+    }
+}
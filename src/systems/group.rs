@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use crate::types::*;
+use crate::config::Config;
+
+const INDICATOR_FONT_SIZE: f32 = 14.0;
+
+pub fn update_window_count_indicators(
+    mut commands: Commands,
+    q_groups: Query<(Entity, &WindowGroup, Option<&Children>), Changed<WindowGroup>>,
+    mut q_indicators: Query<&mut Text, With<WindowCountIndicator>>,
+    config: Res<Config>,
+) {
+    for (entity, group, children) in q_groups.iter() {
+        let count = group.addresses.len();
+        let indicator = children
+            .and_then(|children| children.iter().copied().find(|c| q_indicators.contains(*c)));
+
+        match indicator {
+            Some(indicator) if count > 1 => {
+                if let Ok(mut text) = q_indicators.get_mut(indicator) {
+                    text.sections[0].value = count.to_string();
+                }
+            }
+            Some(indicator) => {
+                commands.entity(indicator).despawn_recursive();
+            }
+            None if count > 1 => {
+                commands.entity(entity).with_children(|parent| {
+                    parent
+                        .spawn(Text2dBundle {
+                            text: Text::from_section(
+                                count.to_string(),
+                                TextStyle {
+                                    font: TextStyle::default().font,
+                                    font_size: INDICATOR_FONT_SIZE,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_alignment(TextAlignment::Center),
+                            transform: Transform::from_xyz(
+                                config.icon_size / 3.0,
+                                -config.icon_size / 3.0,
+                                0.2,
+                            ),
+                            ..default()
+                        })
+                        .insert(WindowCountIndicator);
+                });
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{mpsc::channel, Arc, Mutex};
+
+use crate::backend::WindowManager;
+use crate::components::{add_client_address, add_icon_text, Favorites, IconSpawner};
+use crate::config::Config;
+use crate::layout::dock_layout;
+use crate::systems::{FilteredWindowEvents, WindowManagerEvents};
+use crate::types::*;
+use crate::utils::update_sprite_alpha;
+
+type WindowIconQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Option<&'static ClientAddress>,
+        Option<&'static ClientClass>,
+        Option<&'static mut Sprite>,
+        Option<&'static mut WindowGroup>,
+    ),
+>;
+
+/// Icons spawned or taken over from a pin during one `process_hyprland_events` run, by class
+///
+/// Their new `ClientAddress` and `WindowGroup` only land when the commands are
+/// applied, so the icon query can't see them yet.
+type SpawnedIcons = HashMap<String, Entity>;
+
+/// Icons and dock state that opening and closing windows touch
+#[derive(SystemParam)]
+pub struct WindowIcons<'w, 's> {
+    commands: Commands<'w, 's>,
+    client_list: ResMut<'w, ClientList>,
+    dock_order: ResMut<'w, DockOrder>,
+    reorder_trigger: ResMut<'w, ReorderTrigger>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    config: Res<'w, Config>,
+    favorites: Res<'w, Favorites>,
+    show_titles: Res<'w, ShowTitles>,
+    spawner: IconSpawner<'w>,
+    icons: WindowIconQuery<'w, 's>,
+}
+
+pub fn setup_window_manager_events(mut commands: Commands, window_manager: Res<WindowManager>) {
+    let (event_sender, event_receiver) = channel();
+    let event_receiver = Arc::new(Mutex::new(event_receiver));
+
+    if let Err(e) = window_manager.subscribe(event_sender) {
+        warn!("Failed to subscribe to {} events: {}", window_manager.name(), e);
+    }
+
+    commands.insert_resource(HyprlandEventReceiver(event_receiver));
+}
+
+pub fn process_hyprland_events(
+    mut icons: WindowIcons,
+    mut incoming: FilteredWindowEvents,
+    mut window_events: WindowManagerEvents,
+    mut status: ResMut<WindowManagerStatus>,
+) {
+    let mut spawned = SpawnedIcons::new();
+    for event in incoming.drain() {
+        match event {
+            HyprIpcEvent::OpenWindow {
+                address,
+                workspace,
+                class,
+                title,
+            } => {
+                let client = Client {
+                    address,
+                    class,
+                    title,
+                    pid: 0,
+                    workspace: ClientWorkspace { name: workspace },
+                };
+                icons.open_window(&mut spawned, client);
+            }
+            HyprIpcEvent::CloseWindow { address } => icons.close_window(address),
+            HyprIpcEvent::WindowTitle { ref address, ref title } => {
+                if let Some(client) = icons.client_list.0.iter_mut().find(|c| &c.address == address) {
+                    client.title = title.clone();
+                }
+                window_events.send(event);
+            }
+            HyprIpcEvent::MoveWindow { ref address, ref workspace, .. } => {
+                if let Some(client) = icons.client_list.0.iter_mut().find(|c| &c.address == address) {
+                    client.workspace.name = workspace.clone();
+                }
+                // Icons grouped by workspace may need to move
+                icons.reorder_trigger.0 = true;
+                window_events.send(event);
+            }
+            HyprIpcEvent::Disconnected => {
+                status.set_if_neq(WindowManagerStatus::Disconnected);
+            }
+            HyprIpcEvent::Resync { clients } => {
+                status.set_if_neq(WindowManagerStatus::Connected);
+                icons.resync(&mut spawned, clients);
+            }
+            event => window_events.send(event),
+        }
+    }
+}
+
+impl WindowIcons<'_, '_> {
+    /// Brings the dock in line with a fresh window listing taken after (re)connecting
+    ///
+    /// Windows missing from the listing are closed, unknown ones are opened. Both
+    /// `ClientList` and the running entries of `DockOrder` count as known, so
+    /// icons left behind while disconnected are cleaned up too.
+    fn resync(&mut self, spawned: &mut SpawnedIcons, current_windows: Vec<Client>) {
+        let current_addresses: HashSet<String> =
+            current_windows.iter().map(|c| c.address.clone()).collect();
+        let old_addresses: HashSet<String> = self
+            .client_list
+            .0
+            .iter()
+            .map(|c| c.address.clone())
+            .chain(
+                self.dock_order
+                    .0
+                    .iter()
+                    .filter(|a| !a.starts_with("pinned:"))
+                    .cloned(),
+            )
+            .collect();
+
+        let closed_windows: Vec<String> = old_addresses
+            .difference(&current_addresses)
+            .cloned()
+            .collect();
+        let new_windows: Vec<Client> = current_windows
+            .into_iter()
+            .filter(|c| !old_addresses.contains(&c.address))
+            .collect();
+
+        if !closed_windows.is_empty() || !new_windows.is_empty() {
+            info!(
+                "Resync: {} window(s) closed, {} opened while disconnected",
+                closed_windows.len(),
+                new_windows.len()
+            );
+        }
+
+        for address in closed_windows {
+            self.close_window(address);
+        }
+        for client in new_windows {
+            self.open_window(spawned, client);
+        }
+    }
+
+    fn open_window(&mut self, spawned: &mut SpawnedIcons, client: Client) {
+        // Verifica se já existe um ícone para este endereço
+        if self.client_list.0.iter().any(|c| c.address == client.address)
+            || self.icons.iter().any(|(_, addr_opt, _, _, group_opt)| {
+                addr_opt.is_some_and(|a| a.0 == client.address)
+                    || group_opt.is_some_and(|g| g.contains(&client.address))
+            })
+        {
+            info!("Window already exists in dock: {}", client.address);
+            return;
+        }
+
+        // Um ícone criado neste mesmo frame ainda não aparece na query
+        if let Some(&entity) = spawned.get(&client.class) {
+            let address = client.address.clone();
+            match self.icons.get_mut(entity) {
+                Ok((_, _, _, _, Some(mut group))) => group.add(address),
+                _ => self.commands.add(move |world: &mut World| {
+                    if let Some(mut group) = world.get_mut::<WindowGroup>(entity) {
+                        group.add(address);
+                    }
+                }),
+            }
+            self.client_list.0.push(client);
+            return;
+        }
+
+        // Outra janela de uma classe que já está aberta entra no grupo existente
+        if let Some((_, _, _, _, Some(mut group))) =
+            self.icons.iter_mut().find(|(_, addr_opt, class_opt, _, group_opt)| {
+                group_opt.is_some()
+                    && class_opt.is_some_and(|c| c.0 == client.class)
+                    && addr_opt.is_some_and(|a| !a.0.starts_with("pinned:"))
+            })
+        {
+            group.add(client.address.clone());
+            self.client_list.0.push(client);
+            return;
+        }
+
+        let pinned_addr = format!("pinned:{}", client.class);
+        if let Some((entity, _, _, Some(mut sprite), group_opt)) =
+            self.icons.iter_mut().find(|(_, addr_opt, class_opt, _, _)| {
+                addr_opt.is_some_and(|a| a.0 == pinned_addr)
+                    && class_opt.is_some_and(|c| c.0 == client.class)
+            })
+        {
+            self.commands
+                .entity(entity)
+                .insert(ClientAddress(client.address.clone()));
+            match group_opt {
+                Some(mut group) => group.add(client.address.clone()),
+                None => {
+                    self.commands
+                        .entity(entity)
+                        .insert(WindowGroup::new(vec![client.address.clone()]));
+                }
+            }
+            handle_open_pinned_window(entity, &client, &mut sprite, &mut self.dock_order);
+            spawned.insert(client.class.clone(), entity);
+            self.client_list.0.push(client);
+            self.reorder_trigger.0 = true;
+            return;
+        }
+
+        self.dock_order.0.push(client.address.clone());
+        self.reorder_trigger.0 = true;
+        let count = self.dock_order.0.len();
+        let (translation, scale) =
+            dock_layout(&self.config, self.windows.single()).icon_transform(count - 1, count, 0.0);
+        let transform = Transform {
+            translation,
+            scale: Vec3::splat(scale),
+            ..default()
+        };
+
+        let icon_entity = self
+            .spawner
+            .spawn(&mut self.commands, &client.icon_context(), transform, 1.0, 0);
+
+        add_client_address(&mut self.commands, icon_entity, client.address.clone());
+        self.commands
+            .entity(icon_entity)
+            .insert(WindowGroup::new(vec![client.address.clone()]));
+
+        if self.show_titles.0 {
+            add_icon_text(
+                &mut self.commands,
+                icon_entity,
+                self.favorites.label(&client.class),
+                transform,
+                scale,
+                &self.spawner.asset_server,
+                &self.config,
+            );
+        }
+        spawned.insert(client.class.clone(), icon_entity);
+        self.client_list.0.push(client);
+    }
+
+    fn close_window(&mut self, address: String) {
+        if let Some((entity, addr_opt, class_opt, Some(mut sprite), group_opt)) =
+            self.icons.iter_mut().find(|(_, addr, _, sprite, group_opt)| {
+                sprite.is_some()
+                    && (addr.is_some_and(|a| a.0 == address)
+                        || group_opt.as_ref().is_some_and(|g| g.contains(&address)))
+            })
+        {
+            let remaining = group_opt.and_then(|mut group| {
+                group.remove(&address);
+                group.addresses.first().cloned()
+            });
+
+            if let (Some(addr), Some(class)) = (addr_opt, class_opt) {
+                if let Some(next_address) = remaining {
+                    // O ícone continua enquanto houver outras janelas no grupo
+                    if addr.0 == address {
+                        self.commands
+                            .entity(entity)
+                            .insert(ClientAddress(next_address.clone()));
+                        if let Some(index) = self.dock_order.0.iter().position(|a| a == &address) {
+                            self.dock_order.0[index] = next_address;
+                        }
+                    }
+                } else if self.favorites.contains(&class.0) {
+                    handle_close_pinned_window(
+                        entity,
+                        &address,
+                        &class.0,
+                        &mut sprite,
+                        &mut self.dock_order,
+                        &mut self.commands,
+                    );
+                } else {
+                    self.commands.entity(entity).despawn_recursive();
+                    self.dock_order.0.retain(|a| a != &address);
+                }
+            }
+        }
+        self.client_list.0.retain(|c| c.address != address);
+        self.reorder_trigger.0 = true;
+    }
+}
+
+fn handle_open_pinned_window(
+    _entity: Entity,
+    client: &Client,
+    sprite: &mut Sprite,
+    dock_order: &mut DockOrder,
+) {
+    sprite.color.set_a(1.0);
+
+    let pinned_addr = format!("pinned:{}", client.class.clone());
+    if let Some(index) = dock_order.0.iter().position(|a| a == &pinned_addr) {
+        dock_order.0[index] = client.address.clone();
+    }
+}
+
+fn handle_close_pinned_window(
+    entity: Entity,
+    address: &str,
+    class: &str,
+    sprite: &mut Sprite,
+    dock_order: &mut DockOrder,
+    commands: &mut Commands,
+) {
+    let pinned_addr = format!("pinned:{}", class);
+    commands
+        .entity(entity)
+        .insert(ClientAddress(pinned_addr.clone()));
+    update_sprite_alpha(sprite, true, false);
+
+    if let Some(index) = dock_order.0.iter().position(|a| a == address) {
+        dock_order.0[index] = pinned_addr;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{mock_client, open_event};
+    use crate::backend::WindowManagerBackend;
+    use crate::testing::DockHarness;
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "kitty", "2"),
+        ]
+    }
+
+    fn close_event(address: &str) -> HyprIpcEvent {
+        HyprIpcEvent::CloseWindow {
+            address: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_open_window_adds_icon_or_joins_group() {
+        let mut dock = DockHarness::new(clients(), &["steam"]);
+
+        dock.emit(open_event("0x4", "code", "1"));
+        dock.emit(open_event("0x5", "firefox", "2"));
+        dock.emit(open_event("0x6", "steam", "3"));
+
+        assert_eq!(dock.dock_order(), vec!["0x6", "0x1", "0x2", "0x4"]);
+        assert_eq!(dock.icon_classes(), vec!["code", "firefox", "kitty", "steam"]);
+        let firefox = dock.icon("firefox").unwrap();
+        assert_eq!(dock.get::<WindowGroup>(firefox).unwrap().addresses, vec!["0x1", "0x5"]);
+        let steam = dock.icon("steam").unwrap();
+        assert_eq!(dock.get::<ClientAddress>(steam).unwrap().0, "0x6");
+        assert_eq!(dock.get::<Sprite>(steam).unwrap().color.a(), 1.0);
+        assert_eq!(dock.client_addresses(), vec!["0x1", "0x2", "0x3", "0x4", "0x5", "0x6"]);
+    }
+
+    #[test]
+    fn test_close_window_keeps_pinned_and_grouped_icons() {
+        let mut dock = DockHarness::new(clients(), &["firefox"]);
+
+        dock.emit(close_event("0x2"));
+        let kitty = dock.icon("kitty").unwrap();
+        assert_eq!(dock.get::<ClientAddress>(kitty).unwrap().0, "0x3");
+        assert_eq!(dock.dock_order(), vec!["0x1", "0x3"]);
+
+        dock.emit(close_event("0x3"));
+        dock.emit(close_event("0x1"));
+
+        assert_eq!(dock.icon_classes(), vec!["firefox"]);
+        assert_eq!(dock.dock_order(), vec!["pinned:firefox"]);
+        assert!(dock.client_addresses().is_empty());
+    }
+
+    #[test]
+    fn test_resync_reconciles_windows_missed_while_disconnected() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.emit(HyprIpcEvent::Disconnected);
+        assert_eq!(
+            *dock.app.world.resource::<WindowManagerStatus>(),
+            WindowManagerStatus::Disconnected
+        );
+
+        dock.emit(HyprIpcEvent::Resync {
+            clients: vec![mock_client("0x2", "kitty", "1"), mock_client("0x7", "code", "1")],
+        });
+
+        assert_eq!(
+            *dock.app.world.resource::<WindowManagerStatus>(),
+            WindowManagerStatus::Connected
+        );
+        assert_eq!(dock.icon_classes(), vec!["code", "kitty"]);
+        assert_eq!(dock.dock_order(), vec!["0x2", "0x7"]);
+    }
+
+    #[test]
+    fn test_resync_groups_windows_of_a_new_class_in_one_frame() {
+        let mut dock = DockHarness::new(clients(), &["steam"]);
+
+        dock.emit(HyprIpcEvent::Resync {
+            clients: vec![
+                mock_client("0x1", "firefox", "1"),
+                mock_client("0x7", "code", "1"),
+                mock_client("0x8", "code", "2"),
+                mock_client("0x9", "steam", "1"),
+                mock_client("0xa", "steam", "3"),
+            ],
+        });
+
+        assert_eq!(dock.icon_classes(), vec!["code", "firefox", "steam"]);
+        let code = dock.icon("code").unwrap();
+        assert_eq!(dock.get::<WindowGroup>(code).unwrap().addresses, vec!["0x7", "0x8"]);
+        let steam = dock.icon("steam").unwrap();
+        assert_eq!(dock.get::<ClientAddress>(steam).unwrap().0, "0x9");
+        assert_eq!(dock.get::<WindowGroup>(steam).unwrap().addresses, vec!["0x9", "0xa"]);
+        assert_eq!(dock.dock_order(), vec!["0x9", "0x1", "0x7"]);
+    }
+
+    #[test]
+    fn test_open_events_in_one_frame_share_an_icon() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.emit_all(vec![
+            open_event("0x4", "code", "1"),
+            open_event("0x5", "code", "2"),
+            open_event("0x5", "code", "2"),
+        ]);
+
+        assert_eq!(dock.icon_classes(), vec!["code", "firefox", "kitty"]);
+        let code = dock.icon("code").unwrap();
+        assert_eq!(dock.get::<WindowGroup>(code).unwrap().addresses, vec!["0x4", "0x5"]);
+        assert_eq!(dock.dock_order(), vec!["0x1", "0x2", "0x4"]);
+        assert_eq!(dock.client_addresses(), vec!["0x1", "0x2", "0x3", "0x4", "0x5"]);
+    }
+
+    #[test]
+    fn test_title_and_workspace_changes_update_client_list() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.emit(HyprIpcEvent::WindowTitle {
+            address: "0x1".to_string(),
+            title: "Docs".to_string(),
+        });
+        dock.emit(HyprIpcEvent::MoveWindow {
+            address: "0x1".to_string(),
+            workspace_id: 4,
+            workspace: "4".to_string(),
+        });
+
+        let client = dock.app.world.resource::<ClientList>().0[0].clone();
+        assert_eq!(client.title, "Docs");
+        assert_eq!(client.workspace.name, "4");
+    }
+
+    #[test]
+    fn test_focus_event_from_backend_is_tracked() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.backend.focus("0x3").unwrap();
+        dock.app.update();
+
+        assert_eq!(
+            dock.app.world.resource::<FocusedWindow>().0.as_deref(),
+            Some("0x3")
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use crate::types::*;
 use crate::config::Config;
-use crate::utils::calculate_icon_transform;
-use crate::{IconText, Favorite, Favorites};
+use crate::layout::dock_layout;
+use crate::systems::{workspace_of, workspace_sort_key};
+use crate::components::{Favorite, Favorites};
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
 use bevy::window::PrimaryWindow;
 
 pub fn collect_icon_data(
@@ -19,21 +21,29 @@ pub fn collect_icon_data(
 
 pub fn update_text_positions(
     icon_query: Query<(&Transform, &ClientClass), Without<IconText>>,
-    mut text_query: Query<(&mut Transform, &IconText)>,
+    mut text_query: Query<(&mut Transform, &mut Anchor, &IconText)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
     config: Res<Config>,
 ) {
-    for (mut text_transform, icon_text) in text_query.iter_mut() {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let layout = dock_layout(&config, window);
+
+    for (mut text_transform, mut anchor, icon_text) in text_query.iter_mut() {
         if let Ok((icon_transform, _)) = icon_query.get(icon_text.0) {
             let scale = icon_transform.scale.y;
-            text_transform.translation.x = icon_transform.translation.x;
-            text_transform.translation.y =
-                icon_transform.translation.y - (config.icon_size * scale / 2.0) - 2.0;
+            let (offset, label_anchor) = layout.label_placement(config.icon_size * scale);
+            text_transform.translation.x = icon_transform.translation.x + offset.x;
+            text_transform.translation.y = icon_transform.translation.y + offset.y;
             text_transform.translation.z = icon_transform.translation.z - 0.01;
+            *anchor = label_anchor;
             text_transform.scale = Vec3::splat(scale);
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn reorder_icons_system(
     mut q_icons: Query<(Entity, &ClientAddress, &ClientClass, &mut Transform, &mut HoverTarget, Option<&Favorite>)>,
     mut dock_order: ResMut<DockOrder>,
@@ -41,7 +51,9 @@ pub fn reorder_icons_system(
     windows: Query<&Window, With<PrimaryWindow>>,
     scroll_state: Res<ScrollState>,
     config: Res<Config>,
+    client_list: Res<ClientList>,
     mut reorder_trigger: ResMut<ReorderTrigger>,
+    visibility: Res<DockVisibility>,
 ) {
     if reorder_trigger.0 {
         // Rebuild dock_order to ensure favorites are first
@@ -55,9 +67,9 @@ pub fn reorder_icons_system(
         }
         
         // First, add favorites in order
-        for fav_class in &favorites.0 {
+        for fav_class in favorites.classes() {
             // Find the address for this favorite class
-            if let Some((_, addr, _, _, _, _)) = q_icons.iter().find(|(_, _, class, _, _, _)| &class.0 == fav_class) {
+            if let Some((_, addr, _, _, _, _)) = q_icons.iter().find(|(_, _, class, _, _, _)| class.0 == fav_class) {
                 new_order.push(addr.0.clone());
             }
         }
@@ -68,26 +80,36 @@ pub fn reorder_icons_system(
                 non_favorite_addresses.push(addr.0.clone());
             }
         }
+        // Keeping the order they had, as query order changes with components added
+        non_favorite_addresses.sort_by_key(|addr| {
+            dock_order.0.iter().position(|a| a == addr).unwrap_or(usize::MAX)
+        });
+        if config.workspaces.grouped {
+            // Estável: dentro de um workspace a ordem acima fica
+            non_favorite_addresses
+                .sort_by_cached_key(|addr| workspace_sort_key(workspace_of(&client_list, addr)));
+        }
         
         new_order.extend(non_favorite_addresses);
         dock_order.0 = new_order;
     }
     
     let window = windows.single();
-    let start_x = -window.width() / 2.0 + config.margin_x;
-    let start_y = -window.height() / 2.0 + config.margin_y;
-    let start_pos = Vec2::new(start_x, start_y);
-    let center = Vec2::new(0.0, window.height() * config.tilt_y);
-    let direction = (center - start_pos).normalize_or_zero();
+    let layout = dock_layout(&config, window);
+    let count = dock_order.0.len();
+    let window_size = Vec2::new(window.width(), window.height());
+    let extent = config.icon_size * config.base_scale;
 
     for (index, address) in dock_order.0.iter().enumerate() {
         let (translation, scale) =
-            calculate_icon_transform(index, start_pos, direction, &config, scroll_state.offset);
+            layout.icon_transform(index, count, scroll_state.total_scroll_distance);
 
         for (_entity, icon_address, _, mut transform, mut hover, _) in q_icons.iter_mut() {
             if icon_address.0 == *address {
                 let current_pos = transform.translation;
-                let target_pos = translation;
+                // Ícones escondidos ficam fora da tela, não voltam 20% a cada frame
+                let hide = visibility.offset(layout.as_ref(), translation.truncate(), extent, window_size);
+                let target_pos = translation + hide.extend(0.0);
 
                 transform.translation = Vec3::new(
                     lerp(current_pos.x, target_pos.x, 0.2),
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use crate::types::*;
+use crate::config::{Config, IndicatorStyle};
+
+/// More windows than this still draw this many markers
+const MAX_INDICATORS: usize = 4;
+const INDICATOR_OFFSET_Y: f32 = 6.0;
+const RUNNING_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.8);
+const ACTIVE_COLOR: Color = Color::rgb(0.35, 0.75, 1.0);
+const TEXTURE_SIZE: u32 = 32;
+
+#[derive(Resource)]
+pub struct IndicatorTextures {
+    dot: Handle<Image>,
+    glow: Handle<Image>,
+}
+
+/// Builds a white disc of `TEXTURE_SIZE` pixels whose alpha follows `falloff(distance)`,
+/// with distance normalized to 0 at the center and 1 at the edge
+fn radial_image(falloff: impl Fn(f32) -> f32) -> Image {
+    let radius = TEXTURE_SIZE as f32 / 2.0;
+    let mut data = Vec::with_capacity((TEXTURE_SIZE * TEXTURE_SIZE * 4) as usize);
+
+    for y in 0..TEXTURE_SIZE {
+        for x in 0..TEXTURE_SIZE {
+            let dx = x as f32 + 0.5 - radius;
+            let dy = y as f32 + 0.5 - radius;
+            let distance = (dx * dx + dy * dy).sqrt() / radius;
+            let alpha = falloff(distance).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: TEXTURE_SIZE,
+            height: TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+pub fn setup_indicator_textures(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let edge = 1.0 / TEXTURE_SIZE as f32 * 2.0;
+    let dot = radial_image(|d| (1.0 - d) / edge);
+    let glow = radial_image(|d| (1.0 - d).powi(2));
+
+    commands.insert_resource(IndicatorTextures {
+        dot: images.add(dot),
+        glow: images.add(glow),
+    });
+}
+
+pub fn track_focused_window(
+    mut events: EventReader<WindowFocused>,
+    mut focused: ResMut<FocusedWindow>,
+) {
+    if let Some(event) = events.read().last() {
+        focused.set_if_neq(FocusedWindow(event.address.clone()));
+    }
+}
+
+/// An icon as `update_window_indicators` sees it, with change detection on its windows
+type IndicatedIcon<'a> = (Entity, Ref<'a, WindowGroup>, Option<&'a Children>);
+
+/// Redraws the running/focused markers of icons whose windows or focus changed
+pub fn update_window_indicators(
+    mut commands: Commands,
+    q_icons: Query<IndicatedIcon, With<ClientIcon>>,
+    q_indicators: Query<(), With<WindowIndicator>>,
+    focused: Res<FocusedWindow>,
+    textures: Res<IndicatorTextures>,
+    config: Res<Config>,
+) {
+    let redraw_all = focused.is_changed() || config.is_changed();
+
+    for (entity, group, children) in q_icons.iter() {
+        if !redraw_all && !group.is_changed() {
+            continue;
+        }
+
+        if let Some(children) = children {
+            for &child in children.iter().filter(|c| q_indicators.contains(**c)) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        let count = group.addresses.len().min(MAX_INDICATORS);
+        if count == 0 {
+            continue;
+        }
+
+        let active = focused
+            .0
+            .as_ref()
+            .and_then(|address| group.addresses.iter().position(|a| a == address))
+            .map(|index| index.min(count - 1));
+
+        let markers = indicator_sprites(config.indicator_style, count, active, &textures, &config);
+        commands.entity(entity).with_children(|parent| {
+            for marker in markers {
+                parent.spawn(marker).insert(WindowIndicator);
+            }
+        });
+    }
+}
+
+fn indicator_sprites(
+    style: IndicatorStyle,
+    count: usize,
+    active: Option<usize>,
+    textures: &IndicatorTextures,
+    config: &Config,
+) -> Vec<SpriteBundle> {
+    let (spacing, size, active_size, texture) = match style {
+        IndicatorStyle::Bar => (
+            12.0,
+            Vec2::new(8.0, 3.0),
+            Vec2::new(14.0, 3.0),
+            Handle::default(),
+        ),
+        IndicatorStyle::Dot | IndicatorStyle::Glow => (
+            8.0,
+            Vec2::splat(5.0),
+            Vec2::splat(7.0),
+            textures.dot.clone(),
+        ),
+    };
+    let y = -config.icon_size / 2.0 - INDICATOR_OFFSET_Y;
+    let first_x = -(count as f32 - 1.0) * spacing / 2.0;
+
+    let mut sprites: Vec<SpriteBundle> = (0..count)
+        .map(|i| {
+            let is_active = active == Some(i);
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite {
+                    color: if is_active { ACTIVE_COLOR } else { RUNNING_COLOR },
+                    custom_size: Some(if is_active { active_size } else { size }),
+                    ..default()
+                },
+                transform: Transform::from_xyz(first_x + i as f32 * spacing, y, 0.1),
+                ..default()
+            }
+        })
+        .collect();
+
+    if style == IndicatorStyle::Glow && active.is_some() {
+        sprites.push(SpriteBundle {
+            texture: textures.glow.clone(),
+            sprite: Sprite {
+                color: ACTIVE_COLOR.with_a(0.6),
+                custom_size: Some(Vec2::splat(config.icon_size * 1.6)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, -0.1),
+            ..default()
+        });
+    }
+
+    sprites
+}
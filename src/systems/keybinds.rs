@@ -2,8 +2,11 @@ use bevy::app::AppExit;
 use bevy::input::keyboard::KeyboardInput;
 use bevy::input::ButtonState;
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
-use crate::{focus_client, types::*};
+use crate::backend::WindowManager;
+use crate::components::Favorites;
+use crate::systems::{focus_group, max_scroll, ContextMenu};
+use crate::types::*;
+use crate::desktop::DesktopEntryIndex;
 use crate::config::Config;
 use crate::utils::launch_application;
 
@@ -12,7 +15,6 @@ pub fn scroll_with_arrows(
     mut scroll_state: ResMut<ScrollState>,
     q_icons: Query<&HoverTarget>,
     config: Res<Config>,
-    windows: Query<&Window, With<PrimaryWindow>>,
 ) {
     let total_items = q_icons.iter().count();
     if total_items <= config.visible_items {
@@ -28,26 +30,18 @@ pub fn scroll_with_arrows(
         scroll_state.total_scroll_distance += scroll_amount;
     }
 
-    let max_scroll = ((total_items as f32 - config.visible_items as f32).max(0.0)) * config.spacing;
-    scroll_state.total_scroll_distance = scroll_state.total_scroll_distance.clamp(0.0, max_scroll);
-
-    let window = windows.single();
-    let window_width = window.width();
-    let window_height = window.height();
-    let start_x = -window_width / 2.0 + config.margin_x;
-    let start_y = -window_height / 2.0 + config.margin_y;
-    let start_pos = Vec2::new(start_x, start_y);
-    let center = Vec2::new(0.0, window_height * config.tilt_y);
-    let direction = (center - start_pos).normalize_or_zero();
-
-    scroll_state.offset = direction * scroll_state.total_scroll_distance;
+    scroll_state.total_scroll_distance =
+        scroll_state.total_scroll_distance.clamp(0.0, max_scroll(total_items, &config));
 }
 
 pub fn keybind_launch_visible_icons(
     keyboard: Res<Input<KeyCode>>,
-    icons: Query<(&ClientClass, &HoverTarget, Option<&ClientAddress>)>,
+    mut icons: Query<(&ClientClass, &HoverTarget, Option<&ClientAddress>, Option<&mut WindowGroup>)>,
     scroll_state: Res<ScrollState>,
     config: Res<Config>,
+    window_manager: Res<WindowManager>,
+    favorites: Res<Favorites>,
+    desktop_entries: Res<DesktopEntryIndex>,
 ) {
     let keycodes = [
         KeyCode::Key1,
@@ -67,22 +61,31 @@ pub fn keybind_launch_visible_icons(
     for (i, &key) in keycodes.iter().enumerate().take(config.visible_items) {
         if keyboard.just_pressed(key) {
             let target_index = first_visible_index + i;
-            if let Some((class, _, address)) = icons.iter().find(|(_, hover, _)| hover.index == target_index) {
+            if let Some((class, _, address, group)) = icons.iter_mut().find(|(_, hover, _, _)| hover.index == target_index) {
                 if let Some(addr) = address {
                     if addr.0.starts_with("pinned:") {
-                        launch_application(&class.0);
+                        launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, &class.0);
                     } else {
-                        focus_client(&addr.0);
+                        focus_group(&window_manager, &addr.0, group);
                     }
                 } else {
-                    launch_application(&class.0);
+                    launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, &class.0);
                 }
             }
         }
     }
 }
 
-pub fn exit_on_esc_or_q(mut keys: EventReader<KeyboardInput>, mut exit: EventWriter<AppExit>) {
+pub fn exit_on_esc_or_q(
+    mut keys: EventReader<KeyboardInput>,
+    mut exit: EventWriter<AppExit>,
+    menu: Res<ContextMenu>,
+) {
+    // Com o menu aberto, Escape só fecha o menu
+    if menu.is_open() {
+        keys.clear();
+        return;
+    }
     for key_event in keys.read() {
         if let Some(key_code) = key_event.key_code {
             if key_event.state == ButtonState::Pressed
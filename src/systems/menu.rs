@@ -0,0 +1,631 @@
+use bevy::prelude::*;
+use bevy::render::texture::Image;
+use bevy::sprite::Anchor;
+use bevy::window::PrimaryWindow;
+
+use crate::backend::WindowManager;
+use crate::components::{Favorite, FavoritePin, Favorites};
+use crate::desktop::DesktopEntryIndex;
+use crate::config::Config;
+use crate::layout::dock_layout;
+use crate::systems::{
+    focus_client, is_hidden_workspace, sort_workspaces, toggle_favorite, workspace_of, MonitorFilter,
+    PinnedIcon, Pinning,
+};
+use crate::types::*;
+use crate::utils::{launch_application, FavoritesPath};
+
+const MENU_WIDTH: f32 = 240.0;
+const ROW_HEIGHT: f32 = 26.0;
+const MENU_PADDING: f32 = 6.0;
+/// Space between the icon and the menu
+const MENU_GAP: f32 = 8.0;
+const MENU_FONT_SIZE: f32 = 14.0;
+const MENU_COLOR: Color = Color::rgba(0.12, 0.12, 0.15, 0.95);
+const SELECTED_COLOR: Color = Color::rgba(0.35, 0.55, 0.95, 0.9);
+/// Window titles longer than this are cut short with an ellipsis
+const MAX_TITLE_CHARS: usize = 30;
+
+/// An icon as `open_context_menu` sees it
+type MenuIcon<'a> = (
+    Entity,
+    &'a ClientClass,
+    &'a HoverTarget,
+    &'a Transform,
+    Option<&'a ClientAddress>,
+    Option<&'a WindowGroup>,
+    Option<&'a Favorite>,
+);
+
+/// What pinning and unpinning an icon changes on it
+type PinnableIcon<'a> = (
+    &'a mut Sprite,
+    Option<&'a ClientAddress>,
+    Option<&'a Favorite>,
+    Option<&'a Children>,
+);
+
+/// What choosing a context menu entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuAction {
+    /// Focus one window of the group
+    Focus(String),
+    /// Start another instance of the application
+    NewWindow,
+    TogglePin,
+    Close(String),
+    CloseAll(Vec<String>),
+    /// List the workspaces a window can be moved to
+    ChooseWorkspace(String),
+    MoveToWorkspace(String, String),
+    ToggleFloating(String),
+    ToggleFullscreen(String),
+    /// Go back from the workspace list to the first page
+    Back,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuEntry {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+impl MenuEntry {
+    fn new(label: impl Into<String>, action: MenuAction) -> Self {
+        Self {
+            label: label.into(),
+            action,
+        }
+    }
+}
+
+/// The right-click menu of an icon, while one is open
+#[derive(Resource, Debug, Default)]
+pub struct ContextMenu {
+    /// Icon the menu was opened on, `None` while closed
+    pub icon: Option<Entity>,
+    pub class: String,
+    /// Entries on the page being shown, top to bottom
+    pub entries: Vec<MenuEntry>,
+    /// Entry under the pointer or picked with the arrow keys
+    pub selected: usize,
+    /// Where the menu is drawn, in world space
+    pub rect: Rect,
+    /// The first page, restored by `MenuAction::Back`
+    main: Vec<MenuEntry>,
+    /// Point next to the icon the menu grows from, and the direction it grows in
+    anchor: Vec2,
+    direction: Vec2,
+    window_size: Vec2,
+}
+
+impl ContextMenu {
+    pub fn is_open(&self) -> bool {
+        self.icon.is_some()
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Shows `entries` instead of the current page, selecting the first one
+    pub fn show(&mut self, entries: Vec<MenuEntry>) {
+        self.entries = entries;
+        self.selected = 0;
+        self.rect = place_menu(
+            self.anchor,
+            self.direction,
+            menu_size(self.entries.len()),
+            self.window_size,
+        );
+    }
+
+    /// Index of the entry at `point`, in world space
+    pub fn entry_at(&self, point: Vec2) -> Option<usize> {
+        (0..self.entries.len()).find(|&index| self.entry_rect(index).contains(point))
+    }
+
+    fn entry_rect(&self, index: usize) -> Rect {
+        let top = self.rect.max.y - MENU_PADDING - index as f32 * ROW_HEIGHT;
+        Rect::new(
+            self.rect.min.x + MENU_PADDING,
+            top - ROW_HEIGHT,
+            self.rect.max.x - MENU_PADDING,
+            top,
+        )
+    }
+}
+
+/// Sent when an entry of the context menu is chosen, with a click or `Return`
+#[derive(Event, Debug, Clone)]
+pub struct ContextMenuAction {
+    pub icon: Entity,
+    pub class: String,
+    pub action: MenuAction,
+}
+
+/// Background of the open context menu, with its entries as children
+#[derive(Component)]
+pub struct ContextMenuPanel;
+
+/// Run condition for systems that shouldn't see clicks and keys meant for the menu
+pub fn context_menu_open(menu: Res<ContextMenu>) -> bool {
+    menu.is_open()
+}
+
+/// The first page: the group's windows, then actions on the app and on one of its windows
+///
+/// Per-window actions act on `target`, the group's focused window or its first one.
+pub fn main_entries(windows: &[&Client], pinned: bool, target: Option<&str>) -> Vec<MenuEntry> {
+    let mut entries: Vec<MenuEntry> = windows
+        .iter()
+        .map(|client| {
+            let title = if client.title.is_empty() {
+                &client.class
+            } else {
+                &client.title
+            };
+            MenuEntry::new(shorten(title), MenuAction::Focus(client.address.clone()))
+        })
+        .collect();
+
+    entries.push(MenuEntry::new("New window", MenuAction::NewWindow));
+    entries.push(MenuEntry::new(
+        if pinned { "Unpin" } else { "Pin" },
+        MenuAction::TogglePin,
+    ));
+    if let Some(target) = target {
+        let target = target.to_string();
+        entries.push(MenuEntry::new("Close window", MenuAction::Close(target.clone())));
+        if windows.len() > 1 {
+            let addresses = windows.iter().map(|client| client.address.clone()).collect();
+            entries.push(MenuEntry::new("Close all", MenuAction::CloseAll(addresses)));
+        }
+        entries.push(MenuEntry::new(
+            "Move to workspace…",
+            MenuAction::ChooseWorkspace(target.clone()),
+        ));
+        entries.push(MenuEntry::new(
+            "Toggle floating",
+            MenuAction::ToggleFloating(target.clone()),
+        ));
+        entries.push(MenuEntry::new("Fullscreen", MenuAction::ToggleFullscreen(target)));
+    }
+    entries
+}
+
+/// Workspaces `address` can move to: those in use, plus the next free number
+///
+/// The workspace the window is on already is left out.
+pub fn workspace_entries(address: &str, current: &str, mut workspaces: Vec<String>) -> Vec<MenuEntry> {
+    let next_free = workspaces
+        .iter()
+        .map(String::as_str)
+        .chain([current])
+        .filter_map(|workspace| workspace.parse::<i64>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    workspaces.push(next_free.to_string());
+    sort_workspaces(&mut workspaces);
+
+    let mut entries: Vec<MenuEntry> = workspaces
+        .into_iter()
+        .filter(|workspace| workspace != current && !is_hidden_workspace(workspace))
+        .map(|workspace| {
+            MenuEntry::new(
+                format!("Workspace {}", workspace),
+                MenuAction::MoveToWorkspace(address.to_string(), workspace),
+            )
+        })
+        .collect();
+    entries.push(MenuEntry::new("Back", MenuAction::Back));
+    entries
+}
+
+fn shorten(title: &str) -> String {
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        title.to_string()
+    } else {
+        let cut: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+fn menu_size(entries: usize) -> Vec2 {
+    Vec2::new(MENU_WIDTH, entries as f32 * ROW_HEIGHT + MENU_PADDING * 2.0)
+}
+
+/// Puts the menu beside `anchor`, on the `direction` side, kept inside the window
+fn place_menu(anchor: Vec2, direction: Vec2, size: Vec2, window_size: Vec2) -> Rect {
+    let center = anchor + direction * size / 2.0;
+    let half_room = ((window_size - size) / 2.0).max(Vec2::ZERO);
+    Rect::from_center_size(center.clamp(-half_room, half_room), size)
+}
+
+/// Opens the menu of the hovered icon on a right click
+pub fn open_context_menu(
+    mouse_button: Res<Input<MouseButton>>,
+    mut menu: ResMut<ContextMenu>,
+    q_icons: Query<MenuIcon>,
+    client_list: Res<ClientList>,
+    focused: Res<FocusedWindow>,
+    config: Res<Config>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !mouse_button.just_released(MouseButton::Right) {
+        return;
+    }
+    let Some((entity, class, _, transform, address, group, favorite)) =
+        q_icons.iter().find(|(_, _, hover, ..)| hover.is_hovered)
+    else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let addresses: Vec<String> = match (group, address) {
+        (Some(group), _) => group.addresses.clone(),
+        (None, Some(address)) if !address.0.starts_with("pinned:") => vec![address.0.clone()],
+        _ => Vec::new(),
+    };
+    let clients: Vec<&Client> = addresses
+        .iter()
+        .filter_map(|address| client_list.0.iter().find(|client| &client.address == address))
+        .collect();
+    let target = focused
+        .0
+        .as_ref()
+        .filter(|address| addresses.contains(address))
+        .or(addresses.first());
+
+    let position = transform.translation.truncate();
+    let direction = dock_layout(&config, window).lift_direction(position);
+    let icon_extent = config.icon_size * transform.scale.x;
+    let main = main_entries(&clients, favorite.is_some(), target.map(String::as_str));
+
+    *menu = ContextMenu {
+        icon: Some(entity),
+        class: class.0.clone(),
+        main: main.clone(),
+        anchor: position + direction * (icon_extent / 2.0 + MENU_GAP),
+        direction,
+        window_size: Vec2::new(window.width(), window.height()),
+        ..default()
+    };
+    menu.show(main);
+}
+
+/// Arrow keys, `Return` and the pointer pick entries; `Escape` or a click elsewhere closes the menu
+pub fn context_menu_input(
+    mut menu: ResMut<ContextMenu>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse_button: Res<Input<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut chosen: EventWriter<ContextMenuAction>,
+) {
+    let Some(icon) = menu.icon else {
+        return;
+    };
+    if menu.entries.is_empty() || keyboard.just_pressed(KeyCode::Escape) {
+        menu.close();
+        return;
+    }
+
+    let count = menu.entries.len();
+    if keyboard.just_pressed(KeyCode::Up) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if keyboard.just_pressed(KeyCode::Down) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    let mut activate = keyboard.any_just_pressed([KeyCode::Return, KeyCode::Space]);
+
+    let pointer = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(q_camera.get_single().ok())
+        .and_then(|(cursor, (camera, transform))| camera.viewport_to_world_2d(transform, cursor));
+    if let Some(pointer) = pointer {
+        let under_pointer = menu.entry_at(pointer);
+        if let Some(index) = under_pointer.filter(|&index| index != menu.selected) {
+            menu.selected = index;
+        }
+        if mouse_button.just_released(MouseButton::Left) {
+            if under_pointer.is_some() {
+                activate = true;
+            } else if !menu.rect.contains(pointer) {
+                menu.close();
+                return;
+            }
+        }
+    }
+    // Outro clique direito fecha; se for num ícone, open_context_menu abre o menu dele
+    if mouse_button.just_released(MouseButton::Right) {
+        menu.close();
+        return;
+    }
+
+    if activate {
+        chosen.send(ContextMenuAction {
+            icon,
+            class: menu.class.clone(),
+            action: menu.entries[menu.selected].action.clone(),
+        });
+    }
+}
+
+/// Carries out chosen menu entries, closing the menu unless the entry opens another page
+#[allow(clippy::too_many_arguments)]
+pub fn run_context_menu_actions(
+    mut chosen: EventReader<ContextMenuAction>,
+    mut menu: ResMut<ContextMenu>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut favorites: ResMut<Favorites>,
+    mut reorder_trigger: ResMut<ReorderTrigger>,
+    mut dock_order: ResMut<DockOrder>,
+    mut q_icons: Query<PinnableIcon>,
+    q_pins: Query<Entity, With<FavoritePin>>,
+    config: Res<Config>,
+    favorites_path: Res<FavoritesPath>,
+    window_manager: Res<WindowManager>,
+    filter: Res<MonitorFilter>,
+    client_list: Res<ClientList>,
+    desktop_entries: Res<DesktopEntryIndex>,
+) {
+    for chosen in chosen.read() {
+        let result = match &chosen.action {
+            MenuAction::Focus(address) => {
+                focus_client(&window_manager, address);
+                Ok(())
+            }
+            MenuAction::NewWindow => {
+                launch_application(&window_manager, &favorites, &desktop_entries, &config.terminal, &chosen.class);
+                Ok(())
+            }
+            MenuAction::TogglePin => {
+                if let Ok((mut sprite, address, favorite, children)) = q_icons.get_mut(chosen.icon) {
+                    toggle_favorite(
+                        &mut commands,
+                        &mut images,
+                        &config,
+                        &q_pins,
+                        Pinning {
+                            favorites: &mut favorites,
+                            favorites_path: &favorites_path,
+                            reorder_trigger: &mut reorder_trigger,
+                            dock_order: &mut dock_order,
+                        },
+                        PinnedIcon {
+                            entity: chosen.icon,
+                            class: &chosen.class,
+                            is_favorite: favorite.is_some(),
+                            sprite: &mut sprite,
+                            address,
+                            children,
+                        },
+                    );
+                }
+                Ok(())
+            }
+            MenuAction::Close(address) => window_manager.close(address),
+            MenuAction::CloseAll(addresses) => addresses
+                .iter()
+                .try_for_each(|address| window_manager.close(address)),
+            MenuAction::ChooseWorkspace(address) => {
+                let mut workspaces = filter.occupied_workspaces();
+                workspaces.extend(filter.active_workspace().map(str::to_string));
+                let current = workspace_of(&client_list, address);
+                menu.show(workspace_entries(address, current, workspaces));
+                continue;
+            }
+            MenuAction::MoveToWorkspace(address, workspace) => {
+                window_manager.move_to_workspace(address, workspace)
+            }
+            MenuAction::ToggleFloating(address) => window_manager.toggle_floating(address),
+            MenuAction::ToggleFullscreen(address) => window_manager.toggle_fullscreen(address),
+            MenuAction::Back => {
+                let main = menu.main.clone();
+                menu.show(main);
+                continue;
+            }
+        };
+        if let Err(e) = result {
+            warn!("Context menu action {:?} failed: {}", chosen.action, e);
+        }
+        menu.close();
+    }
+}
+
+/// Redraws the menu whenever it opens, closes, changes page or selection
+pub fn draw_context_menu(
+    mut commands: Commands,
+    menu: Res<ContextMenu>,
+    q_panels: Query<Entity, With<ContextMenuPanel>>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+    for entity in q_panels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !menu.is_open() {
+        return;
+    }
+
+    let center = menu.rect.center();
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: MENU_COLOR,
+                    custom_size: Some(menu.rect.size()),
+                    ..default()
+                },
+                transform: Transform::from_xyz(center.x, center.y, 60.0),
+                ..default()
+            },
+            ContextMenuPanel,
+        ))
+        .with_children(|parent| {
+            for (index, entry) in menu.entries.iter().enumerate() {
+                let row = menu.entry_rect(index);
+                let row_center = row.center() - center;
+                if index == menu.selected {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: SELECTED_COLOR,
+                            custom_size: Some(row.size()),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(row_center.x, row_center.y, 0.1),
+                        ..default()
+                    });
+                }
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        entry.label.clone(),
+                        TextStyle {
+                            font: TextStyle::default().font,
+                            font_size: MENU_FONT_SIZE,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_alignment(TextAlignment::Left),
+                    text_anchor: Anchor::CenterLeft,
+                    transform: Transform::from_xyz(
+                        row.min.x - center.x + MENU_PADDING,
+                        row_center.y,
+                        0.2,
+                    ),
+                    ..default()
+                });
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{mock_client, MockCall};
+    use crate::testing::DockHarness;
+    use bevy::app::AppExit;
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "kitty", "2"),
+        ]
+    }
+
+    fn labels(entries: &[MenuEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_main_entries() {
+        let mut kitty = mock_client("0x2", "kitty", "1");
+        kitty.title = "a very long title that goes on and on forever".to_string();
+        let other = mock_client("0x3", "kitty", "2");
+
+        let entries = main_entries(&[&kitty, &other], false, Some("0x3"));
+
+        assert_eq!(
+            labels(&entries),
+            vec![
+                "a very long title that goes o…",
+                "kitty",
+                "New window",
+                "Pin",
+                "Close window",
+                "Close all",
+                "Move to workspace…",
+                "Toggle floating",
+                "Fullscreen",
+            ]
+        );
+        assert_eq!(entries[4].action, MenuAction::Close("0x3".to_string()));
+
+        // Ícone só fixado: nada de ações de janela
+        let entries = main_entries(&[], true, None);
+        assert_eq!(labels(&entries), vec!["New window", "Unpin"]);
+    }
+
+    #[test]
+    fn test_workspace_entries_skip_current_and_offer_a_free_one() {
+        let workspaces = vec!["web".to_string(), "1".to_string(), "special:scratch".to_string()];
+
+        let entries = workspace_entries("0x2", "1", workspaces);
+
+        assert_eq!(
+            labels(&entries),
+            vec!["Workspace 2", "Workspace web", "Back"]
+        );
+        assert_eq!(
+            entries[0].action,
+            MenuAction::MoveToWorkspace("0x2".to_string(), "2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_menu_stays_inside_the_window() {
+        let size = Vec2::new(240.0, 100.0);
+
+        let rect = place_menu(Vec2::new(-390.0, -100.0), Vec2::Y, size, Vec2::new(800.0, 360.0));
+
+        assert_eq!(rect, Rect::new(-400.0, -100.0, -160.0, 0.0));
+    }
+
+    #[test]
+    fn test_menu_actions_reach_the_backend() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.right_click("kitty");
+        assert!(dock.app.world.resource::<ContextMenu>().is_open());
+        dock.choose("Close window");
+
+        assert!(!dock.app.world.resource::<ContextMenu>().is_open());
+        assert!(dock.backend.calls().contains(&MockCall::Close("0x2".to_string())));
+
+        dock.right_click("firefox");
+        dock.choose("Move to workspace…");
+        assert!(dock.app.world.resource::<ContextMenu>().is_open());
+        dock.choose("Workspace 2");
+
+        assert!(dock
+            .backend
+            .calls()
+            .contains(&MockCall::MoveToWorkspace("0x1".to_string(), "2".to_string())));
+    }
+
+    #[test]
+    fn test_escape_closes_the_menu_without_quitting() {
+        let mut dock = DockHarness::new(clients(), &[]);
+
+        dock.right_click("firefox");
+        dock.press(KeyCode::Escape);
+
+        assert!(!dock.app.world.resource::<ContextMenu>().is_open());
+        assert!(dock.app.world.resource::<Events<AppExit>>().is_empty());
+    }
+
+    #[test]
+    fn test_arrows_leave_the_dock_alone_while_the_menu_is_open() {
+        let mut dock = DockHarness::new(clients(), &[]);
+        // Menos itens visíveis que ícones, para haver o que rolar
+        dock.app.world.resource_mut::<Config>().visible_items = 1;
+
+        dock.right_click("firefox");
+        dock.press(KeyCode::Right);
+        assert_eq!(dock.app.world.resource::<ScrollState>().total_scroll_distance, 0.0);
+
+        dock.press(KeyCode::Escape);
+        dock.press(KeyCode::Right);
+        assert!(dock.app.world.resource::<ScrollState>().total_scroll_distance > 0.0);
+    }
+}
@@ -1,15 +1,47 @@
 pub mod animation;
 mod camera;
+mod click;
+mod config;
+mod connection;
+mod control;
+mod desktop;
 mod drag;
+mod events;
+mod favorites;
+mod group;
+mod hyprland;
 mod icon;
+mod indicators;
 mod scroll;
+mod setup;
 mod keybinds;
+mod menu;
+mod monitor;
 mod title;
+mod urgent;
+mod visibility;
+mod workspaces;
 
 pub use animation::icon_scale_animation_system;
 pub use camera::*;
+pub use click::*;
+pub use config::*;
+pub use connection::*;
+pub use control::*;
+pub use desktop::*;
 pub use drag::*;
+pub use events::*;
+pub use favorites::*;
+pub use group::*;
+pub use hyprland::*;
 pub use icon::*;
+pub use indicators::*;
 pub use scroll::*;
+pub use setup::*;
 pub use keybinds::*;
+pub use menu::*;
+pub use monitor::*;
 pub use title::*;
+pub use urgent::*;
+pub use visibility::*;
+pub use workspaces::*;
@@ -0,0 +1,462 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PrimaryWindow, WindowPosition};
+use bevy::winit::WinitWindows;
+use std::collections::{HashMap, HashSet};
+
+use crate::backend::MonitorInfo;
+use crate::config::{Config, WindowScope};
+use crate::systems::{is_hidden_workspace, sort_workspaces};
+use crate::types::*;
+
+/// Decides which windows this dock shows, from the monitor and workspace each one is on
+///
+/// Sits between the backend and `process_hyprland_events`: windows moving into
+/// the dock's scope arrive as `OpenWindow` and windows leaving it as
+/// `CloseWindow`, so with one dock per monitor an icon follows its window from
+/// dock to dock. Workspaces not yet seen on any monitor count as being on the
+/// one focused when they show up, which is where compositors create them.
+#[derive(Resource, Debug, Clone)]
+pub struct MonitorFilter {
+    scope: WindowScope,
+    /// Monitor this dock is on, `None` when the backend doesn't report monitors
+    monitor: Option<String>,
+    focused_monitor: Option<String>,
+    workspace_monitors: HashMap<String, String>,
+    /// Workspace each monitor is showing
+    active_workspaces: HashMap<String, String>,
+    /// Every open window, shown or not, oldest first
+    windows: Vec<Client>,
+    shown: HashSet<String>,
+}
+
+impl MonitorFilter {
+    /// A filter for the dock on `monitor`, or on the focused monitor when `None`
+    pub fn new(scope: WindowScope, monitor: Option<String>, monitors: &[MonitorInfo]) -> Self {
+        let focused_monitor = monitors
+            .iter()
+            .find(|m| m.focused)
+            .or(monitors.first())
+            .map(|m| m.name.clone());
+
+        let mut workspace_monitors = HashMap::new();
+        let mut active_workspaces = HashMap::new();
+        for info in monitors {
+            for workspace in info.workspaces.iter().chain([&info.active_workspace]) {
+                workspace_monitors.insert(workspace.clone(), info.name.clone());
+            }
+            active_workspaces.insert(info.name.clone(), info.active_workspace.clone());
+        }
+
+        if monitors.is_empty() && scope != WindowScope::All {
+            warn!("Window manager doesn't report monitors, showing every window");
+        }
+
+        Self {
+            scope,
+            monitor: monitor.or_else(|| focused_monitor.clone()).filter(|_| !monitors.is_empty()),
+            focused_monitor,
+            workspace_monitors,
+            active_workspaces,
+            windows: Vec::new(),
+            shown: HashSet::new(),
+        }
+    }
+
+    /// The monitor this dock is on
+    pub fn monitor(&self) -> Option<&str> {
+        self.monitor.as_deref()
+    }
+
+    /// Workspace the dock's monitor is showing
+    pub fn active_workspace(&self) -> Option<&str> {
+        let monitor = self.monitor.as_ref().or(self.focused_monitor.as_ref())?;
+        self.active_workspaces
+            .get(monitor)
+            .map(String::as_str)
+            .filter(|workspace| !workspace.is_empty())
+    }
+
+    /// Workspaces with windows, in strip order
+    ///
+    /// Only those on the dock's monitor, unless the dock shows every window.
+    pub fn occupied_workspaces(&self) -> Vec<String> {
+        let on_this_monitor = |workspace: &String| match (&self.monitor, self.scope) {
+            (Some(monitor), WindowScope::Monitor | WindowScope::Workspace) => {
+                !matches!(self.workspace_monitors.get(workspace), Some(m) if m != monitor)
+            }
+            _ => true,
+        };
+
+        let mut workspaces: Vec<String> = self
+            .windows
+            .iter()
+            .map(|client| &client.workspace.name)
+            .filter(|workspace| !is_hidden_workspace(workspace) && on_this_monitor(workspace))
+            .cloned()
+            .collect();
+        sort_workspaces(&mut workspaces);
+        workspaces
+    }
+
+    pub fn shows(&self, client: &Client) -> bool {
+        let Some(monitor) = &self.monitor else {
+            return true;
+        };
+        let workspace = &client.workspace.name;
+
+        match self.scope {
+            WindowScope::All => true,
+            // Sem informação, a janela aparece
+            WindowScope::Monitor => {
+                !matches!(self.workspace_monitors.get(workspace), Some(m) if m != monitor)
+            }
+            WindowScope::Workspace => {
+                !matches!(self.active_workspaces.get(monitor), Some(active) if active != workspace)
+            }
+        }
+    }
+
+    /// Take in the full window list, returning the windows to show
+    pub fn resync(&mut self, clients: Vec<Client>) -> Vec<Client> {
+        self.windows = clients;
+        let shown: Vec<Client> = self
+            .windows
+            .iter()
+            .filter(|client| self.shows(client))
+            .cloned()
+            .collect();
+        self.shown = shown.iter().map(|client| client.address.clone()).collect();
+        shown
+    }
+
+    /// Change which windows are shown, returning the events that apply the change
+    pub fn set_scope(&mut self, scope: WindowScope) -> Vec<HyprIpcEvent> {
+        if self.scope == scope {
+            return Vec::new();
+        }
+        self.scope = scope;
+        self.refresh()
+    }
+
+    /// Turn one backend event into the events this dock should see
+    pub fn handle(&mut self, event: HyprIpcEvent) -> Vec<HyprIpcEvent> {
+        match event {
+            HyprIpcEvent::OpenWindow {
+                address,
+                workspace,
+                class,
+                title,
+            } => {
+                // Sway não diz o workspace de janelas novas: é o que está em foco
+                let workspace = if workspace.is_empty() {
+                    self.focused_workspace().unwrap_or_default()
+                } else {
+                    workspace
+                };
+                self.place_workspace(&workspace);
+                let client = Client {
+                    address,
+                    class,
+                    title,
+                    pid: 0,
+                    workspace: ClientWorkspace { name: workspace },
+                };
+
+                self.windows.retain(|c| c.address != client.address);
+                self.windows.push(client.clone());
+                if self.shows(&client) {
+                    self.shown.insert(client.address.clone());
+                    vec![open_window_event(&client)]
+                } else {
+                    Vec::new()
+                }
+            }
+            HyprIpcEvent::CloseWindow { ref address } => {
+                self.windows.retain(|c| &c.address != address);
+                if self.shown.remove(address) {
+                    vec![event]
+                } else {
+                    Vec::new()
+                }
+            }
+            HyprIpcEvent::MoveWindow {
+                ref address,
+                ref workspace,
+                ..
+            } => {
+                self.place_workspace(workspace);
+                if let Some(client) = self.windows.iter_mut().find(|c| &c.address == address) {
+                    client.workspace.name = workspace.clone();
+                }
+                let was_shown = self.shown.contains(address);
+                let mut events = self.refresh();
+                if was_shown && self.shown.contains(address) {
+                    events.push(event);
+                }
+                events
+            }
+            HyprIpcEvent::Workspace { ref name, .. } => {
+                self.place_workspace(name);
+                if let Some(focused) = self.focused_monitor.clone() {
+                    self.active_workspaces.insert(focused, name.clone());
+                }
+                let mut events = self.refresh();
+                events.push(event);
+                events
+            }
+            HyprIpcEvent::FocusedMonitor {
+                ref monitor,
+                ref workspace,
+            } => {
+                self.focused_monitor = Some(monitor.clone());
+                self.workspace_monitors
+                    .insert(workspace.clone(), monitor.clone());
+                self.active_workspaces
+                    .insert(monitor.clone(), workspace.clone());
+                let mut events = self.refresh();
+                events.push(event);
+                events
+            }
+            HyprIpcEvent::WorkspaceMoved { workspace, monitor } => {
+                self.workspace_monitors.insert(workspace, monitor);
+                self.refresh()
+            }
+            HyprIpcEvent::Resync { clients } => vec![HyprIpcEvent::Resync {
+                clients: self.resync(clients),
+            }],
+            event => vec![event],
+        }
+    }
+
+    /// Workspaces first seen in an event are on the focused monitor
+    fn place_workspace(&mut self, workspace: &str) {
+        if let Some(focused) = &self.focused_monitor {
+            if !workspace.is_empty() && !self.workspace_monitors.contains_key(workspace) {
+                self.workspace_monitors
+                    .insert(workspace.to_string(), focused.clone());
+            }
+        }
+    }
+
+    fn focused_workspace(&self) -> Option<String> {
+        let focused = self.focused_monitor.as_ref()?;
+        self.active_workspaces.get(focused).cloned()
+    }
+
+    /// Open or close the windows whose visibility changed
+    fn refresh(&mut self) -> Vec<HyprIpcEvent> {
+        let changed: Vec<(Client, bool)> = self
+            .windows
+            .iter()
+            .map(|client| (client, self.shows(client)))
+            .filter(|(client, show)| *show != self.shown.contains(&client.address))
+            .map(|(client, show)| (client.clone(), show))
+            .collect();
+
+        changed
+            .into_iter()
+            .map(|(client, show)| {
+                if show {
+                    self.shown.insert(client.address.clone());
+                    open_window_event(&client)
+                } else {
+                    self.shown.remove(&client.address);
+                    HyprIpcEvent::CloseWindow {
+                        address: client.address,
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Backend events waiting for the dock, as its `MonitorFilter` lets them through
+#[derive(SystemParam)]
+pub struct FilteredWindowEvents<'w> {
+    receiver: Res<'w, HyprlandEventReceiver>,
+    filter: ResMut<'w, MonitorFilter>,
+    config: Res<'w, Config>,
+}
+
+impl FilteredWindowEvents<'_> {
+    /// Every event received since the last frame, plus any a config reload caused
+    pub fn drain(&mut self) -> Vec<HyprIpcEvent> {
+        let mut events = Vec::new();
+        if self.config.is_changed() {
+            events.extend(self.filter.set_scope(self.config.monitors.windows));
+        }
+
+        let receiver = self.receiver.0.lock().unwrap();
+        while let Ok(event) = receiver.try_recv() {
+            events.extend(self.filter.handle(event));
+        }
+        events
+    }
+}
+
+fn open_window_event(client: &Client) -> HyprIpcEvent {
+    HyprIpcEvent::OpenWindow {
+        address: client.address.clone(),
+        workspace: client.workspace.name.clone(),
+        class: client.class.clone(),
+        title: client.title.clone(),
+    }
+}
+
+/// Moves the dock to the monitor given with `--monitor` once its window exists
+///
+/// Monitors are matched by connector name (`DP-1`, `HDMI-A-1`, ...). Compositors
+/// that don't let clients place their windows may ignore the move.
+pub fn move_to_target_monitor(
+    target: Res<TargetMonitor>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut done: Local<bool>,
+) {
+    let (Some(name), Some(winit_windows)) = (&target.0, winit_windows) else {
+        return;
+    };
+    if *done {
+        return;
+    }
+    let Ok((entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+    let Some(winit_window) = winit_windows.get_window(entity) else {
+        return;
+    };
+    *done = true;
+
+    let index = winit_window
+        .available_monitors()
+        .position(|monitor| monitor.name().as_deref() == Some(name.as_str()));
+    match index {
+        Some(index) => {
+            info!("Moving dock to monitor {}", name);
+            window.position = WindowPosition::Centered(MonitorSelection::Index(index));
+        }
+        None => warn!("Monitor {} not found, staying on the current one", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{mock_client, mock_monitor, open_event};
+    use crate::testing::DockHarness;
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![
+            MonitorInfo {
+                focused: true,
+                ..mock_monitor("DP-1", &["1", "2"])
+            },
+            mock_monitor("HDMI-A-1", &["3"]),
+        ]
+    }
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "2"),
+            mock_client("0x3", "code", "3"),
+        ]
+    }
+
+    fn addresses(clients: &[Client]) -> Vec<&str> {
+        clients.iter().map(|c| c.address.as_str()).collect()
+    }
+
+    fn move_event(address: &str, workspace: &str) -> HyprIpcEvent {
+        HyprIpcEvent::MoveWindow {
+            address: address.to_string(),
+            workspace_id: 0,
+            workspace: workspace.to_string(),
+        }
+    }
+
+    fn close_event(address: &str) -> HyprIpcEvent {
+        HyprIpcEvent::CloseWindow {
+            address: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_window_moves_between_monitor_docks() {
+        let mut left = MonitorFilter::new(WindowScope::Monitor, Some("DP-1".to_string()), &monitors());
+        let mut right =
+            MonitorFilter::new(WindowScope::Monitor, Some("HDMI-A-1".to_string()), &monitors());
+        assert_eq!(addresses(&left.resync(clients())), vec!["0x1", "0x2"]);
+        assert_eq!(addresses(&right.resync(clients())), vec!["0x3"]);
+
+        assert_eq!(left.handle(move_event("0x2", "3")), vec![close_event("0x2")]);
+        assert_eq!(right.handle(move_event("0x2", "3")), vec![open_event("0x2", "kitty", "3")]);
+
+        // Mudar de workspace no mesmo monitor não mexe no dock
+        assert_eq!(left.handle(move_event("0x1", "2")), vec![move_event("0x1", "2")]);
+        assert!(right.handle(move_event("0x1", "2")).is_empty());
+    }
+
+    #[test]
+    fn test_workspace_scope_follows_active_workspace() {
+        let mut filter =
+            MonitorFilter::new(WindowScope::Workspace, Some("DP-1".to_string()), &monitors());
+        assert_eq!(addresses(&filter.resync(clients())), vec!["0x1"]);
+
+        let switch = HyprIpcEvent::Workspace {
+            id: 2,
+            name: "2".to_string(),
+        };
+        assert_eq!(
+            filter.handle(switch.clone()),
+            vec![close_event("0x1"), open_event("0x2", "kitty", "2"), switch]
+        );
+    }
+
+    #[test]
+    fn test_workspaces_stay_on_the_monitor_they_appeared_on() {
+        let mut filter = MonitorFilter::new(WindowScope::Monitor, Some("DP-1".to_string()), &monitors());
+        filter.resync(clients());
+
+        assert_eq!(
+            filter.handle(open_event("0x4", "steam", "9")),
+            vec![open_event("0x4", "steam", "9")]
+        );
+        let focus = HyprIpcEvent::FocusedMonitor {
+            monitor: "HDMI-A-1".to_string(),
+            workspace: "3".to_string(),
+        };
+        assert_eq!(filter.handle(focus.clone()), vec![focus]);
+
+        let moved = HyprIpcEvent::WorkspaceMoved {
+            workspace: "9".to_string(),
+            monitor: "HDMI-A-1".to_string(),
+        };
+        assert_eq!(filter.handle(moved), vec![close_event("0x4")]);
+        assert!(filter.handle(close_event("0x4")).is_empty());
+    }
+
+    #[test]
+    fn test_everything_shown_without_monitor_information() {
+        let mut filter = MonitorFilter::new(WindowScope::Workspace, Some("DP-1".to_string()), &[]);
+
+        assert_eq!(filter.monitor(), None);
+        assert_eq!(addresses(&filter.resync(clients())), vec!["0x1", "0x2", "0x3"]);
+    }
+
+    #[test]
+    fn test_dock_drops_icon_of_window_moved_to_other_monitor() {
+        let mut config = Config::default();
+        config.monitors.windows = WindowScope::Monitor;
+        let mut dock = DockHarness::on_monitor(clients(), monitors(), "HDMI-A-1", config);
+        assert_eq!(dock.icon_classes(), vec!["code"]);
+
+        dock.emit(move_event("0x1", "3"));
+        assert_eq!(dock.icon_classes(), vec!["code", "firefox"]);
+
+        dock.emit(move_event("0x3", "1"));
+        assert_eq!(dock.icon_classes(), vec!["firefox"]);
+        assert_eq!(dock.client_addresses(), vec!["0x1"]);
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::HashSet;
+
+use crate::components::{
+    add_client_address, add_favorite, add_icon_text, apply_pin_icon, Favorites, IconSpawner,
+};
+use crate::config::Config;
+use crate::icon::IconContext;
+use crate::layout::dock_layout;
+use crate::systems::workspace_sort_key;
+use crate::types::*;
+
+pub fn setup(
+    mut commands: Commands,
+    mut spawner: IconSpawner,
+    client_list: Res<ClientList>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    show_titles: Res<ShowTitles>,
+    favorites: Res<Favorites>,
+    config: Res<Config>,
+) {
+    commands
+        .spawn(Camera2dBundle {
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, 100.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(MainCamera);
+
+    let layout = dock_layout(&config, windows.single());
+
+    let mut all_apps: Vec<(String, Vec<Client>, bool)> = Vec::new();
+    let mut initial_order = Vec::new();
+    let mut processed_classes = HashSet::new();
+
+    let clients_of = |class: &str| -> Vec<Client> {
+        client_list
+            .0
+            .iter()
+            .filter(|c| c.class == class)
+            .cloned()
+            .collect()
+    };
+
+    // Primeiro, adiciona os favoritos na ordem salva
+    for fav_class in favorites.classes() {
+        if processed_classes.contains(fav_class) {
+            continue;
+        }
+
+        let clients = clients_of(fav_class);
+        if let Some(client) = clients.first() {
+            initial_order.push(client.address.clone());
+        } else {
+            initial_order.push(format!("pinned:{}", fav_class));
+        }
+        all_apps.push((fav_class.to_string(), clients, true));
+        processed_classes.insert(fav_class.to_string());
+    }
+
+    // Depois, adiciona as aplicações não-favoritas que estão abertas
+    let mut running: Vec<&Client> = client_list.0.iter().collect();
+    if config.workspaces.grouped {
+        running.sort_by_cached_key(|client| workspace_sort_key(&client.workspace.name));
+    }
+    for client in running {
+        if !processed_classes.contains(&client.class) {
+            all_apps.push((client.class.clone(), clients_of(&client.class), false));
+            initial_order.push(client.address.clone());
+            processed_classes.insert(client.class.clone());
+        }
+    }
+
+    commands.insert_resource(DockOrder(initial_order));
+
+    for (index, (class, clients, is_favorite)) in all_apps.iter().enumerate() {
+        let (translation, scale) = layout.icon_transform(index, all_apps.len(), 0.0);
+        let transform = Transform {
+            translation,
+            scale: Vec3::splat(scale),
+            ..default()
+        };
+        let alpha = if *is_favorite && clients.is_empty() {
+            0.5
+        } else {
+            1.0
+        };
+
+        let context = clients
+            .first()
+            .map(|client| client.icon_context())
+            .unwrap_or_else(|| IconContext::new(class.clone()));
+
+        let icon_entity = spawner.spawn(&mut commands, &context, transform, alpha, index);
+
+        if let Some(client) = clients.first() {
+            add_client_address(&mut commands, icon_entity, client.address.clone());
+        } else if *is_favorite {
+            let placeholder_address = format!("pinned:{}", class);
+            add_client_address(&mut commands, icon_entity, placeholder_address);
+        }
+        commands.entity(icon_entity).insert(WindowGroup::new(
+            clients.iter().map(|c| c.address.clone()).collect(),
+        ));
+
+        if *is_favorite {
+            add_favorite(&mut commands, icon_entity, &mut spawner.images, &config);
+            if let Some(pin) = favorites.get(class) {
+                apply_pin_icon(&mut commands, &mut spawner.images, icon_entity, pin);
+            }
+        }
+        if show_titles.0 {
+            add_icon_text(
+                &mut commands,
+                icon_entity,
+                favorites.label(class),
+                transform,
+                scale,
+                &spawner.asset_server,
+                &config,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::mock_client;
+    use crate::components::Favorite;
+    use crate::testing::DockHarness;
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "kitty", "2"),
+        ]
+    }
+
+    #[test]
+    fn test_setup_puts_favorites_first_and_groups_windows() {
+        let mut dock = DockHarness::new(clients(), &["steam", "kitty"]);
+
+        assert_eq!(dock.dock_order(), vec!["pinned:steam", "0x2", "0x1"]);
+        assert_eq!(dock.icon_classes(), vec!["firefox", "kitty", "steam"]);
+
+        let kitty = dock.icon("kitty").unwrap();
+        assert_eq!(dock.get::<WindowGroup>(kitty).unwrap().addresses, vec!["0x2", "0x3"]);
+        assert!(dock.get::<Favorite>(kitty).is_some());
+        let steam = dock.icon("steam").unwrap();
+        assert_eq!(dock.get::<Sprite>(steam).unwrap().color.a(), 0.5);
+    }
+}
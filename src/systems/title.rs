@@ -1,36 +1,41 @@
 use bevy::prelude::*;
 use crate::types::*;
-use crate::components::add_icon_text;
+use crate::components::{add_icon_text, Favorites};
 use crate::config::Config;
 
+/// Shows or hides titles when `T` is pressed or `ShowTitles` is changed elsewhere
+#[allow(clippy::too_many_arguments)]
 pub fn toggle_titles(
     mut commands: Commands,
     mut show_titles: ResMut<ShowTitles>,
     keyboard_input: Res<Input<KeyCode>>,
-    q_icons: Query<(Entity, &Transform, &ClientClass, &HoverTarget)>,
+    q_icons: Query<(Entity, &Transform, &ClientClass), With<HoverTarget>>,
     asset_server: Res<AssetServer>,
     config: Res<Config>,
     q_texts: Query<Entity, With<IconText>>,
+    favorites: Res<Favorites>,
 ) {
     if keyboard_input.just_pressed(KeyCode::T) {
         show_titles.0 = !show_titles.0;
+    }
+    if !show_titles.is_changed() || show_titles.is_added() {
+        return;
+    }
 
-        if show_titles.0 {
-            for (entity, transform, class, _hover) in q_icons.iter() {
-                add_icon_text(
-                    &mut commands,
-                    entity,
-                    &class.0,
-                    *transform,
-                    transform.scale.y,
-                    &asset_server,
-                    &config,
-                );
-            }
-        } else {
-            for entity in q_texts.iter() {
-                commands.entity(entity).despawn_recursive();
-            }
+    for entity in q_texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if show_titles.0 {
+        for (entity, transform, class) in q_icons.iter() {
+            add_icon_text(
+                &mut commands,
+                entity,
+                favorites.label(&class.0),
+                *transform,
+                transform.scale.y,
+                &asset_server,
+                &config,
+            );
         }
     }
 }
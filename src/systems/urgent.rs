@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use std::f32::consts::PI;
+use crate::types::*;
+use crate::config::{Config, UrgentAnimation};
+use crate::utils::hover::HoverState;
+
+const BOUNCE_HEIGHT: f32 = 20.0;
+const PULSE_SCALE: f32 = 0.2;
+
+/// Starts an attention animation on the icon owning an urgent window
+pub fn start_attention_animations(
+    mut commands: Commands,
+    mut events: EventReader<WindowUrgent>,
+    q_icons: Query<(Entity, &ClientClass, &WindowGroup), With<ClientIcon>>,
+    focused: Res<FocusedWindow>,
+    config: Res<Config>,
+) {
+    for event in events.read() {
+        if focused.0.as_deref() == Some(event.address.as_str()) {
+            continue;
+        }
+
+        let Some((entity, class, _)) = q_icons
+            .iter()
+            .find(|(_, _, group)| group.contains(&event.address))
+        else {
+            continue;
+        };
+
+        if config.urgent.is_enabled_for(&class.0) {
+            commands.entity(entity).insert(AttentionAnimation {
+                address: event.address.clone(),
+                elapsed: 0.0,
+            });
+        }
+    }
+}
+
+/// Drives `HoverState`'s attention lift/scale until the window is focused, closed,
+/// or the configured number of repeats has played
+pub fn attention_animation_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_icons: Query<(Entity, &mut AttentionAnimation, &mut HoverState, &WindowGroup)>,
+    focused: Res<FocusedWindow>,
+    config: Res<Config>,
+) {
+    let urgent = &config.urgent;
+    let duration = urgent.duration.max(0.05);
+
+    for (entity, mut animation, mut state, group) in q_icons.iter_mut() {
+        animation.elapsed += time.delta_seconds();
+
+        let finished = focused.0.as_deref() == Some(animation.address.as_str())
+            || !group.contains(&animation.address)
+            || urgent.animation == UrgentAnimation::None
+            || (urgent.repeat > 0 && animation.elapsed >= duration * urgent.repeat as f32);
+
+        if finished {
+            state.attention_lift = 0.0;
+            state.attention_scale = 1.0;
+            commands.entity(entity).remove::<AttentionAnimation>();
+            continue;
+        }
+
+        let wave = (PI * animation.elapsed / duration).sin();
+        match urgent.animation {
+            UrgentAnimation::Bounce => {
+                state.attention_lift = BOUNCE_HEIGHT * wave.abs();
+                state.attention_scale = 1.0;
+            }
+            UrgentAnimation::Pulse => {
+                state.attention_lift = 0.0;
+                state.attention_scale = 1.0 + PULSE_SCALE * wave * wave;
+            }
+            UrgentAnimation::None => {}
+        }
+    }
+}
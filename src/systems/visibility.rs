@@ -0,0 +1,398 @@
+use bevy::math::{IRect, IVec2};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+use crate::backend::{DesktopGeometry, WindowManager};
+use crate::config::{Config, Edge, VisibilityMode};
+use crate::layout::dock_layout;
+use crate::systems::{ContextMenu, MonitorFilter};
+use crate::types::*;
+
+/// Seconds between geometry requests while intellihide is on, for windows moved without an event
+const GEOMETRY_POLL: f32 = 0.5;
+
+/// Requests for desktop geometry, answered by a worker thread
+///
+/// The backend's socket round trip must not stall a frame, so `geometry()`
+/// runs off the Bevy schedule and its answers are picked up on later frames.
+#[derive(Resource)]
+pub struct GeometryChannel {
+    requests: Sender<()>,
+    replies: Mutex<Receiver<DesktopGeometry>>,
+}
+
+pub fn setup_geometry_watcher(mut commands: Commands, window_manager: Res<WindowManager>) {
+    let (request_sender, requests) = channel::<()>();
+    let (reply_sender, replies) = channel();
+    let window_manager = window_manager.clone();
+
+    std::thread::spawn(move || {
+        let mut failing = false;
+        while requests.recv().is_ok() {
+            // Pedidos acumulados durante a última resposta viram um só
+            while requests.try_recv().is_ok() {}
+
+            match window_manager.geometry() {
+                Ok(geometry) => {
+                    failing = false;
+                    if reply_sender.send(geometry).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    if !failing {
+                        warn!("Intellihide can't see where windows are: {}", e);
+                    }
+                    failing = true;
+                }
+            }
+        }
+    });
+
+    commands.insert_resource(GeometryChannel {
+        requests: request_sender,
+        replies: Mutex::new(replies),
+    });
+}
+
+/// Asks for fresh geometry while intellihide is on, after window events and every `GEOMETRY_POLL`
+#[allow(clippy::too_many_arguments)]
+pub fn request_desktop_geometry(
+    config: Res<Config>,
+    channel: Res<GeometryChannel>,
+    time: Res<Time>,
+    dock_order: Res<DockOrder>,
+    mut fullscreen: EventReader<FullscreenChanged>,
+    mut focused: EventReader<WindowFocused>,
+    mut moved: EventReader<WindowWorkspaceChanged>,
+    mut workspace: EventReader<WorkspaceChanged>,
+    mut floating: EventReader<FloatingModeChanged>,
+    mut until_poll: Local<f32>,
+) {
+    let window_event = !fullscreen.is_empty()
+        || !focused.is_empty()
+        || !moved.is_empty()
+        || !workspace.is_empty()
+        || !floating.is_empty()
+        || dock_order.is_changed();
+    fullscreen.clear();
+    focused.clear();
+    moved.clear();
+    workspace.clear();
+    floating.clear();
+
+    if config.visibility.mode != VisibilityMode::Intellihide {
+        *until_poll = 0.0;
+        return;
+    }
+
+    *until_poll -= time.delta_seconds();
+    if window_event || *until_poll <= 0.0 {
+        let _ = channel.requests.send(());
+        *until_poll = GEOMETRY_POLL;
+    }
+}
+
+/// Updates `DockVisibility::covered` from the latest desktop geometry
+pub fn receive_desktop_geometry(
+    channel: Res<GeometryChannel>,
+    mut visibility: ResMut<DockVisibility>,
+    filter: Res<MonitorFilter>,
+    config: Res<Config>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_icons: Query<&HoverTarget, With<ClientIcon>>,
+) {
+    let Some(geometry) = channel.replies.lock().unwrap().try_iter().last() else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let panel_size = Vec2::new(window.width(), window.height());
+    let icons = q_icons
+        .iter()
+        .map(|hover| (hover.original_position, hover.original_scale));
+    let covered = dock_rect(panel_size, config.icon_size, icons).is_some_and(|dock| {
+        dock_is_covered(&geometry, filter.monitor(), config.layer.edge, panel_size, dock)
+    });
+
+    if visibility.covered != covered {
+        visibility.covered = covered;
+    }
+}
+
+/// Hides and reveals the dock as `visibility.mode` asks, sliding the icons toward `progress`
+pub fn update_dock_visibility(
+    mut visibility: ResMut<DockVisibility>,
+    config: Res<Config>,
+    time: Res<Time>,
+    ui_state: Res<UiState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_hover: Query<&HoverTarget>,
+    menu: Res<ContextMenu>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let settings = &config.visibility;
+    let may_hide = match settings.mode {
+        VisibilityMode::Always => false,
+        VisibilityMode::AutoHide => true,
+        VisibilityMode::Intellihide => visibility.covered,
+    };
+
+    let edge = dock_layout(&config, window).edge();
+    let window_size = Vec2::new(window.width(), window.height());
+    let at_edge = window.cursor_position().is_some_and(|cursor| {
+        reveal_strip(edge, window_size, settings.reveal_distance)
+            .as_rect()
+            .contains(cursor)
+    });
+    let on_dock = ui_state.dragging.is_some()
+        || menu.is_open()
+        || at_edge
+        || q_hover.iter().any(|hover| hover.is_hovered);
+
+    let delta = time.delta_seconds();
+    if visibility.forced {
+        visibility.idle = 0.0;
+        visibility.hidden = true;
+    } else if !may_hide || on_dock {
+        visibility.idle = 0.0;
+        visibility.hidden = false;
+    } else {
+        visibility.idle += delta;
+        if visibility.idle >= settings.delay {
+            visibility.hidden = true;
+        }
+    }
+
+    let target = if visibility.hidden { 1.0 } else { 0.0 };
+    let step = delta / settings.duration;
+    let progress = if visibility.progress < target {
+        (visibility.progress + step).min(target)
+    } else {
+        (visibility.progress - step).max(target)
+    };
+    if visibility.progress != progress {
+        visibility.progress = progress;
+    }
+}
+
+type WorkspaceWidget = Or<(With<WorkspaceSeparator>, With<WorkspaceButton>)>;
+
+/// Hides the workspace strip and separators while the icons are away
+pub fn hide_workspace_widgets(
+    visibility: Res<DockVisibility>,
+    mut q_widgets: Query<&mut Visibility, WorkspaceWidget>,
+) {
+    let wanted = if visibility.progress > 0.0 {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut widget in &mut q_widgets {
+        if *widget != wanted {
+            *widget = wanted;
+        }
+    }
+}
+
+/// The strip of the window along `edge`, `distance` pixels wide, that reveals a hidden dock
+///
+/// In window pixels, from the top-left corner, like cursor positions.
+pub fn reveal_strip(edge: Edge, window_size: Vec2, distance: f32) -> IRect {
+    let size = window_size.round().as_ivec2();
+    let distance = (distance.round() as i32).clamp(1, size.x.min(size.y).max(1));
+    match edge {
+        Edge::Top => IRect::new(0, 0, size.x, distance),
+        Edge::Bottom => IRect::new(0, size.y - distance, size.x, size.y),
+        Edge::Left => IRect::new(0, 0, distance, size.y),
+        Edge::Right => IRect::new(size.x - distance, 0, size.x, size.y),
+    }
+}
+
+/// Area the icons take in the panel when shown, in panel pixels from the top-left corner
+///
+/// `icons` holds each icon's resting position, from the panel center with y up, and scale.
+/// Icons scrolled out of the panel don't count.
+pub fn dock_rect(
+    panel_size: Vec2,
+    icon_size: f32,
+    icons: impl Iterator<Item = (Vec2, f32)>,
+) -> Option<IRect> {
+    let panel = IRect::from_corners(IVec2::ZERO, panel_size.round().as_ivec2());
+    icons
+        .map(|(position, scale)| {
+            let center = Vec2::new(panel_size.x / 2.0 + position.x, panel_size.y / 2.0 - position.y);
+            let half = icon_size * scale / 2.0;
+            IRect::from_corners(
+                (center - half).floor().as_ivec2(),
+                (center + half).ceil().as_ivec2(),
+            )
+            .intersect(panel)
+        })
+        .filter(|rect| !rect.is_empty())
+        .reduce(|area, rect| area.union(rect))
+}
+
+/// Whether a window shown on the dock's monitor overlaps `dock` or is fullscreen
+///
+/// `dock` is in panel pixels, the panel being `panel_size` and anchored to `edge`
+/// of `monitor`, or of the focused monitor when `monitor` is `None`.
+pub fn dock_is_covered(
+    geometry: &DesktopGeometry,
+    monitor: Option<&str>,
+    edge: Edge,
+    panel_size: Vec2,
+    dock: IRect,
+) -> bool {
+    let Some(screen) = geometry.monitors.iter().find(|screen| match monitor {
+        Some(name) => screen.name == name,
+        None => screen.focused,
+    }) else {
+        return false;
+    };
+
+    let panel = panel_size.round().as_ivec2();
+    let origin = screen.rect.min
+        + match edge {
+            Edge::Top | Edge::Left => IVec2::ZERO,
+            Edge::Bottom => IVec2::new(0, screen.rect.height() - panel.y),
+            Edge::Right => IVec2::new(screen.rect.width() - panel.x, 0),
+        };
+    let dock = IRect::from_corners(dock.min + origin, dock.max + origin);
+
+    geometry
+        .windows
+        .iter()
+        .filter(|window| window.monitor == screen.name && window.workspace == screen.active_workspace)
+        .any(|window| window.fullscreen || !window.rect.intersect(dock).is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::mock_client;
+    use crate::backend::{MockBackend, MonitorGeometry, WindowGeometry};
+    use crate::testing::DockHarness;
+    use std::time::Duration;
+
+    fn desktop(windows: Vec<WindowGeometry>) -> DesktopGeometry {
+        DesktopGeometry {
+            monitors: vec![
+                MonitorGeometry {
+                    name: "DP-1".to_string(),
+                    rect: IRect::new(0, 0, 1920, 1080),
+                    focused: true,
+                    active_workspace: "1".to_string(),
+                },
+                MonitorGeometry {
+                    name: "HDMI-A-1".to_string(),
+                    rect: IRect::new(1920, 0, 3840, 1080),
+                    focused: false,
+                    active_workspace: "2".to_string(),
+                },
+            ],
+            windows,
+        }
+    }
+
+    fn window(monitor: &str, workspace: &str, rect: IRect) -> WindowGeometry {
+        WindowGeometry {
+            address: "0x1".to_string(),
+            monitor: monitor.to_string(),
+            workspace: workspace.to_string(),
+            rect,
+            fullscreen: false,
+        }
+    }
+
+    #[test]
+    fn test_dock_is_covered_by_windows_over_it() {
+        // Painel de 360px na borda de baixo; o dock ocupa 100x50 no canto esquerdo
+        let panel = Vec2::new(1920.0, 360.0);
+        let dock = IRect::new(0, 300, 100, 350);
+        let covered = |windows: Vec<WindowGeometry>, monitor: Option<&str>| {
+            dock_is_covered(&desktop(windows), monitor, Edge::Bottom, panel, dock)
+        };
+
+        assert!(!covered(vec![], None));
+        assert!(covered(vec![window("DP-1", "1", IRect::new(0, 900, 500, 1040))], None));
+        assert!(!covered(vec![window("DP-1", "1", IRect::new(0, 0, 1920, 1000))], None));
+        // Janelas de outro workspace ou monitor não contam
+        assert!(!covered(vec![window("DP-1", "3", IRect::new(0, 0, 1920, 1080))], None));
+        assert!(!covered(vec![window("DP-1", "1", IRect::new(0, 0, 1920, 1080))], Some("HDMI-A-1")));
+        assert!(covered(vec![window("HDMI-A-1", "2", IRect::new(1920, 900, 2100, 1080))], Some("HDMI-A-1")));
+
+        let fullscreen = WindowGeometry {
+            fullscreen: true,
+            ..window("DP-1", "1", IRect::new(600, 0, 900, 100))
+        };
+        assert!(covered(vec![fullscreen], None));
+    }
+
+    #[test]
+    fn test_reveal_strip_follows_the_edge() {
+        let size = Vec2::new(1280.0, 720.0);
+
+        assert_eq!(reveal_strip(Edge::Bottom, size, 4.0), IRect::new(0, 716, 1280, 720));
+        assert_eq!(reveal_strip(Edge::Left, size, 4.0), IRect::new(0, 0, 4, 720));
+        assert_eq!(reveal_strip(Edge::Right, size, 2.4), IRect::new(1278, 0, 1280, 720));
+    }
+
+    #[test]
+    fn test_dock_rect_skips_icons_out_of_the_panel() {
+        let icons = [(Vec2::new(0.0, 0.0), 1.0), (Vec2::new(60.0, 0.0), 1.0), (Vec2::new(5000.0, 0.0), 1.0)];
+
+        let rect = dock_rect(Vec2::new(200.0, 100.0), 40.0, icons.into_iter()).unwrap();
+
+        assert_eq!(rect, IRect::new(80, 30, 180, 70));
+        assert_eq!(dock_rect(Vec2::new(200.0, 100.0), 40.0, std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_intellihide_hides_under_a_fullscreen_window() {
+        let clients = vec![mock_client("0x1", "firefox", "1")];
+        let fullscreen = WindowGeometry {
+            fullscreen: true,
+            ..window("DP-1", "1", IRect::new(0, 0, 1920, 1080))
+        };
+        let backend = MockBackend::new(clients.clone()).with_geometry(desktop(vec![fullscreen]));
+        let mut config = Config::default();
+        config.visibility.mode = VisibilityMode::Intellihide;
+        config.visibility.delay = 0.0;
+        let mut dock = DockHarness::with_backend(backend, clients, config);
+
+        for _ in 0..500 {
+            if dock.app.world.resource::<DockVisibility>().hidden {
+                break;
+            }
+            dock.app.update();
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        let visibility = dock.app.world.resource::<DockVisibility>();
+        assert!(visibility.covered);
+        assert!(visibility.hidden);
+    }
+
+    #[test]
+    fn test_auto_hide_slides_icons_all_the_way_out() {
+        let clients = vec![mock_client("0x1", "firefox", "1"), mock_client("0x2", "kitty", "1")];
+        let mut config = Config::default();
+        config.visibility.mode = VisibilityMode::AutoHide;
+        config.visibility.delay = 0.0;
+        config.visibility.duration = 0.05;
+        let mut dock = DockHarness::with_config(clients, config);
+
+        dock.slide_to(1.0);
+
+        let distances = dock.distances_from_hidden();
+        assert_eq!(distances.len(), 2);
+        assert!(distances.iter().all(|distance| *distance < 0.5), "{:?}", distances);
+    }
+}
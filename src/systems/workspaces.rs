@@ -0,0 +1,343 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::backend::WindowManager;
+use crate::components::Favorite;
+use crate::config::Config;
+use crate::layout::dock_layout;
+use crate::systems::MonitorFilter;
+use crate::types::*;
+
+const SEPARATOR_WIDTH: f32 = 3.0;
+const SEPARATOR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+const STRIP_BUTTON_SIZE: f32 = 26.0;
+const STRIP_SPACING: f32 = 6.0;
+const STRIP_FONT_SIZE: f32 = 13.0;
+const STRIP_COLOR: Color = Color::rgba(0.2, 0.2, 0.25, 0.75);
+const STRIP_ACTIVE_COLOR: Color = Color::rgba(0.35, 0.55, 0.95, 0.9);
+
+/// Workspaces, the active one and the window size the strip was laid out for
+type StripLayout = (Vec<String>, Option<String>, Vec2);
+
+/// Scratchpads and other workspaces compositors keep out of sight
+pub fn is_hidden_workspace(name: &str) -> bool {
+    name.is_empty() || name.starts_with("special:") || name == "__i3_scratch"
+}
+
+/// Numbered workspaces first, by number, then named ones, then hidden ones
+pub fn workspace_sort_key(name: &str) -> (u8, i64, String) {
+    match name.parse::<i64>() {
+        Ok(number) => (0, number, String::new()),
+        Err(_) if is_hidden_workspace(name) => (2, 0, name.to_string()),
+        Err(_) => (1, 0, name.to_string()),
+    }
+}
+
+pub fn sort_workspaces(workspaces: &mut Vec<String>) {
+    workspaces.sort_by_cached_key(|name| workspace_sort_key(name));
+    workspaces.dedup();
+}
+
+/// Workspace of the window at `address`, empty for pinned icons and unknown windows
+pub fn workspace_of<'a>(client_list: &'a ClientList, address: &str) -> &'a str {
+    client_list
+        .0
+        .iter()
+        .find(|client| client.address == address)
+        .map(|client| client.workspace.name.as_str())
+        .unwrap_or_default()
+}
+
+/// Draws a separator wherever the next icon in `DockOrder` is on another workspace
+///
+/// Pinned applications count as one group of their own, ahead of the workspaces.
+#[allow(clippy::too_many_arguments)]
+pub fn update_workspace_separators(
+    mut commands: Commands,
+    mut q_separators: Query<(Entity, &mut Transform, &mut Sprite), With<WorkspaceSeparator>>,
+    q_favorites: Query<&ClientAddress, With<Favorite>>,
+    dock_order: Res<DockOrder>,
+    client_list: Res<ClientList>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    scroll_state: Res<ScrollState>,
+    config: Res<Config>,
+) {
+    let mut boundaries = Vec::new();
+    if config.workspaces.grouped {
+        let groups: Vec<Option<&str>> = dock_order
+            .0
+            .iter()
+            .map(|address| {
+                let pinned = address.starts_with("pinned:")
+                    || q_favorites.iter().any(|favorite| &favorite.0 == address);
+                (!pinned).then(|| workspace_of(&client_list, address))
+            })
+            .collect();
+        boundaries = (1..groups.len())
+            .filter(|&index| groups[index] != groups[index - 1])
+            .collect();
+    }
+
+    let mut separators = q_separators.iter_mut();
+    if !boundaries.is_empty() {
+        let layout = dock_layout(&config, windows.single());
+        let count = dock_order.0.len();
+        let scroll = scroll_state.total_scroll_distance;
+
+        for index in boundaries {
+            let (before, before_scale) = layout.icon_transform(index - 1, count, scroll);
+            let (after, after_scale) = layout.icon_transform(index, count, scroll);
+            let translation = (before + after) / 2.0;
+            let size = Vec2::new(
+                SEPARATOR_WIDTH,
+                config.icon_size * (before_scale + after_scale) / 2.0 * 0.8,
+            );
+            // Em pé ao longo da direção em que os ícones sobem
+            let lift = layout.lift_direction(translation.truncate());
+            let rotation = Quat::from_rotation_z(lift.y.atan2(lift.x) - FRAC_PI_2);
+
+            match separators.next() {
+                Some((_, mut transform, mut sprite)) => {
+                    transform.translation = translation;
+                    transform.rotation = rotation;
+                    sprite.custom_size = Some(size);
+                }
+                None => {
+                    commands.spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: SEPARATOR_COLOR,
+                                custom_size: Some(size),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(translation)
+                                .with_rotation(rotation),
+                            ..default()
+                        },
+                        WorkspaceSeparator,
+                    ));
+                }
+            }
+        }
+    }
+
+    for (entity, _, _) in separators {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Keeps one button per occupied workspace above the dock, with `workspaces.strip` set
+///
+/// The workspace the dock's monitor shows is always there and highlighted.
+pub fn update_workspace_strip(
+    mut commands: Commands,
+    q_buttons: Query<Entity, With<WorkspaceButton>>,
+    filter: Res<MonitorFilter>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    config: Res<Config>,
+    mut drawn: Local<Option<StripLayout>>,
+) {
+    let wanted = if config.workspaces.strip {
+        let window = windows.single();
+        let active = filter.active_workspace().map(str::to_string);
+        let mut workspaces = filter.occupied_workspaces();
+        if let Some(active) = &active {
+            workspaces.push(active.clone());
+            sort_workspaces(&mut workspaces);
+        }
+        Some((
+            workspaces,
+            active,
+            Vec2::new(window.width(), window.height()),
+        ))
+    } else {
+        None
+    };
+    if *drawn == wanted {
+        return;
+    }
+
+    for entity in q_buttons.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some((workspaces, active, window_size)) = &wanted {
+        let x = -window_size.x / 2.0 + config.margin_x;
+        let y = window_size.y / 2.0 - STRIP_SPACING - STRIP_BUTTON_SIZE / 2.0;
+
+        for (index, workspace) in workspaces.iter().enumerate() {
+            let color = if active.as_ref() == Some(workspace) {
+                STRIP_ACTIVE_COLOR
+            } else {
+                STRIP_COLOR
+            };
+            commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(STRIP_BUTTON_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            x + index as f32 * (STRIP_BUTTON_SIZE + STRIP_SPACING),
+                            y,
+                            50.0,
+                        ),
+                        ..default()
+                    },
+                    WorkspaceButton(workspace.clone()),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(Text2dBundle {
+                        text: Text::from_section(
+                            workspace.clone(),
+                            TextStyle {
+                                font: TextStyle::default().font,
+                                font_size: STRIP_FONT_SIZE,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_alignment(TextAlignment::Center),
+                        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                        ..default()
+                    });
+                });
+        }
+    }
+    *drawn = wanted;
+}
+
+/// Switches to the workspace whose strip button was clicked
+pub fn workspace_strip_click(
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    q_buttons: Query<(&WorkspaceButton, &Transform)>,
+    mouse_button: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_state: Res<UiState>,
+    window_manager: Res<WindowManager>,
+) {
+    if !mouse_button.just_released(MouseButton::Left) || ui_state.dragging.is_some() {
+        return;
+    }
+    let Some(cursor_pos) = windows.single().cursor_position() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = q_camera.get_single() else {
+        return;
+    };
+    let Some(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) else {
+        return;
+    };
+
+    let clicked = q_buttons.iter().find(|(_, transform)| {
+        Rect::from_center_size(
+            transform.translation.truncate(),
+            Vec2::splat(STRIP_BUTTON_SIZE),
+        )
+        .contains(world_pos)
+    });
+    if let Some((button, _)) = clicked {
+        switch_workspace(&window_manager, &button.0);
+    }
+}
+
+pub fn switch_workspace(window_manager: &WindowManager, workspace: &str) {
+    if let Err(e) = window_manager.switch_workspace(workspace) {
+        warn!("Failed to switch workspace: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::{mock_client, mock_monitor, MockCall};
+    use crate::backend::MonitorInfo;
+    use crate::config::WindowScope;
+    use crate::testing::DockHarness;
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![MonitorInfo {
+            focused: true,
+            ..mock_monitor("DP-1", &["1", "2", "web"])
+        }]
+    }
+
+    fn clients() -> Vec<Client> {
+        vec![
+            mock_client("0x1", "firefox", "web"),
+            mock_client("0x2", "kitty", "2"),
+            mock_client("0x3", "code", "10"),
+            mock_client("0x4", "foot", "2"),
+            mock_client("0x5", "keepassxc", "special:magic"),
+        ]
+    }
+
+    fn buttons(dock: &mut DockHarness) -> Vec<String> {
+        let mut query = dock.app.world.query::<&WorkspaceButton>();
+        let mut names: Vec<String> = query.iter(&dock.app.world).map(|b| b.0.clone()).collect();
+        sort_workspaces(&mut names);
+        names
+    }
+
+    #[test]
+    fn test_workspaces_sort_numbers_first() {
+        let mut workspaces = vec!["web", "10", "2", "", "chat", "2"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        sort_workspaces(&mut workspaces);
+
+        assert_eq!(workspaces, vec!["2", "10", "chat", "web", ""]);
+        assert!(workspace_sort_key("web") < workspace_sort_key("special:magic"));
+        assert!(is_hidden_workspace("special:magic"));
+        assert!(!is_hidden_workspace("web"));
+    }
+
+    #[test]
+    fn test_grouped_dock_orders_icons_by_workspace() {
+        let mut config = Config::default();
+        config.workspaces.grouped = true;
+        let mut dock = DockHarness::on_monitor(clients(), monitors(), "DP-1", config);
+        dock.app.update();
+
+        assert_eq!(dock.dock_order(), vec!["0x2", "0x4", "0x3", "0x1", "0x5"]);
+        let mut separators = dock
+            .app
+            .world
+            .query_filtered::<(), With<WorkspaceSeparator>>();
+        assert_eq!(separators.iter(&dock.app.world).count(), 3);
+
+        // Mudou de workspace, mudou de grupo
+        dock.emit(HyprIpcEvent::MoveWindow {
+            address: "0x2".to_string(),
+            workspace_id: -1,
+            workspace: "web".to_string(),
+        });
+        dock.app.update();
+        assert_eq!(dock.dock_order(), vec!["0x4", "0x3", "0x2", "0x1", "0x5"]);
+    }
+
+    #[test]
+    fn test_strip_shows_occupied_and_active_workspaces() {
+        let mut config = Config::default();
+        config.workspaces.strip = true;
+        config.monitors.windows = WindowScope::Workspace;
+        let mut dock = DockHarness::on_monitor(clients(), monitors(), "DP-1", config);
+        dock.app.update();
+
+        // Only workspace 1's windows are shown, but every occupied workspace is listed
+        assert!(dock.icon_classes().is_empty());
+        assert_eq!(buttons(&mut dock), vec!["1", "2", "10", "web"]);
+
+        switch_workspace(&dock.app.world.resource::<WindowManager>().clone(), "web");
+        dock.app.update();
+        dock.app.update();
+        assert_eq!(
+            dock.backend.calls(),
+            vec![MockCall::SwitchWorkspace("web".to_string())]
+        );
+        assert_eq!(dock.icon_classes(), vec!["firefox"]);
+        assert_eq!(buttons(&mut dock), vec!["2", "10", "web"]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Headless harness running the dock's systems against a `MockBackend`
+//!
+//! Runs the full `AnnyDockPlugin`, but no window, GPU or compositor is needed:
+//! the primary window is a bare `Window` entity and every window manager event
+//! comes from the mock.
+
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use crate::backend::{MockBackend, MonitorInfo, WindowManager};
+use crate::components::{Favorites, Pin};
+use crate::config::Config;
+use crate::control::{self, ControlError, ControlReply, ControlRequest};
+use crate::desktop::DesktopEntryIndex;
+use crate::layout::dock_layout;
+use crate::systems::ContextMenu;
+use crate::types::*;
+use crate::utils::hover::{hover_animation_system, hover_system};
+use crate::AnnyDockPlugin;
+
+/// The icon under the pretend cursor, as headless windows have no real one
+#[derive(Resource, Default)]
+struct PointedIcon(Option<Entity>);
+
+fn point_at_icon(pointed: Res<PointedIcon>, mut q_hover: Query<(Entity, &mut HoverTarget)>) {
+    let Some(pointed) = pointed.0 else {
+        return;
+    };
+    for (entity, mut hover) in &mut q_hover {
+        hover.is_hovered = entity == pointed;
+    }
+}
+
+pub struct DockHarness {
+    pub app: App,
+    pub backend: Arc<MockBackend>,
+    socket: PathBuf,
+    dir: TempDir,
+}
+
+impl DockHarness {
+    /// A dock started with `clients` open and `pins` pinned, after its first frame
+    pub fn new(clients: Vec<Client>, pins: &[&str]) -> Self {
+        Self::with_favorites(clients, Favorites(pins.iter().map(|class| Pin::new(*class)).collect()))
+    }
+
+    pub fn with_favorites(clients: Vec<Client>, favorites: Favorites) -> Self {
+        let backend = MockBackend::new(clients.clone());
+        Self::build(backend, clients, favorites, Config::default(), None)
+    }
+
+    pub fn with_config(clients: Vec<Client>, config: Config) -> Self {
+        let backend = MockBackend::new(clients.clone());
+        Self::build(backend, clients, Favorites::default(), config, None)
+    }
+
+    /// A dock talking to `backend`, which should already list `clients`
+    pub fn with_backend(backend: MockBackend, clients: Vec<Client>, config: Config) -> Self {
+        Self::build(backend, clients, Favorites::default(), config, None)
+    }
+
+    /// A dock on `monitor` of `monitors`, showing the windows `config` asks for
+    pub fn on_monitor(
+        clients: Vec<Client>,
+        monitors: Vec<MonitorInfo>,
+        monitor: &str,
+        config: Config,
+    ) -> Self {
+        let backend = MockBackend::new(clients.clone()).with_monitors(monitors);
+        Self::build(backend, clients, Favorites::default(), config, Some(monitor.to_string()))
+    }
+
+    fn build(
+        backend: MockBackend,
+        clients: Vec<Client>,
+        favorites: Favorites,
+        config: Config,
+        monitor: Option<String>,
+    ) -> Self {
+        let dir = TempDir::new().unwrap();
+        let socket = dir.path().join("anny-dock.sock");
+        let backend = Arc::new(backend);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), InputPlugin))
+            .init_asset::<Image>()
+            .insert_resource(ClientList(clients))
+            .insert_resource(favorites)
+            .insert_resource(config)
+            .insert_resource(WindowManager(backend.clone()))
+            .insert_resource(DesktopEntryIndex::default())
+            .add_plugins(AnnyDockPlugin {
+                config_path: Some(dir.path().join("config.toml")),
+                favorites_path: Some(dir.path().join("favorites.json")),
+                backend: None,
+                monitor,
+                control_socket: Some(socket.clone()),
+            })
+            .init_resource::<PointedIcon>()
+            .add_systems(
+                Update,
+                point_at_icon
+                    .in_set(DockSet::Hover)
+                    .after(hover_system)
+                    .before(hover_animation_system),
+            );
+
+        app.world.spawn((Window::default(), PrimaryWindow));
+        app.update();
+
+        Self {
+            app,
+            backend,
+            socket,
+            dir,
+        }
+    }
+
+    /// Deliver `event` from the mock compositor and run a frame
+    pub fn emit(&mut self, event: HyprIpcEvent) {
+        self.emit_all(vec![event]);
+    }
+
+    /// Deliver all of `events` and run a single frame, as when they arrive together
+    pub fn emit_all(&mut self, events: Vec<HyprIpcEvent>) {
+        for event in events {
+            assert!(self.backend.emit(event), "dock is not subscribed");
+        }
+        self.app.update();
+    }
+
+    /// Right-click the icon of `class`, opening its context menu
+    pub fn right_click(&mut self, class: &str) {
+        let entity = self.icon(class).expect("no icon for class");
+        self.app.world.resource_mut::<PointedIcon>().0 = Some(entity);
+
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(MouseButtonInput {
+                button: MouseButton::Right,
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+        self.app.update();
+
+        self.app.world.resource_mut::<PointedIcon>().0 = None;
+    }
+
+    /// Press and release `key`, a frame each
+    pub fn press(&mut self, key: KeyCode) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key),
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+            self.app.update();
+        }
+    }
+
+    /// Pick the entry labelled `label` in the open context menu with the arrow keys and `Return`
+    pub fn choose(&mut self, label: &str) {
+        let menu = self.app.world.resource::<ContextMenu>();
+        let index = menu
+            .entries
+            .iter()
+            .position(|entry| entry.label == label)
+            .unwrap_or_else(|| panic!("no {:?} entry in {:?}", label, menu.entries));
+        for _ in menu.selected..index {
+            self.press(KeyCode::Down);
+        }
+        self.press(KeyCode::Return);
+    }
+
+    /// Keep the pretend cursor over the icon of `class`, running a frame
+    pub fn hover(&mut self, class: &str) {
+        let entity = self.icon(class).expect("no icon for class");
+        self.app.world.resource_mut::<PointedIcon>().0 = Some(entity);
+        self.app.update();
+    }
+
+    /// Run frames until the dock has slid all the way to `progress` and the icons have followed
+    pub fn slide_to(&mut self, progress: f32) {
+        for _ in 0..500 {
+            self.app.update();
+            if self.app.world.resource::<DockVisibility>().progress == progress {
+                // Os ícones se movem no frame seguinte
+                self.app.update();
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        panic!("the dock never slid to {}", progress);
+    }
+
+    /// How far each icon is drawn from where it sits once the dock is fully hidden
+    pub fn distances_from_hidden(&mut self) -> Vec<f32> {
+        let config = self.app.world.resource::<Config>().clone();
+        let window = self.app.world.query::<&Window>().single(&self.app.world).clone();
+        let layout = dock_layout(&config, &window);
+        let window_size = Vec2::new(window.width(), window.height());
+        let extent = config.icon_size * config.base_scale;
+        let mut query = self
+            .app
+            .world
+            .query_filtered::<(&Transform, &HoverTarget), With<ClientIcon>>();
+        query
+            .iter(&self.app.world)
+            .map(|(transform, hover)| {
+                let hidden = hover.original_position
+                    + layout.hide_offset(hover.original_position, extent, window_size);
+                transform.translation.truncate().distance(hidden)
+            })
+            .collect()
+    }
+
+    /// Send `request` over the control socket, running frames until it is answered
+    pub fn send(&mut self, request: ControlRequest) -> Result<ControlReply, ControlError> {
+        let socket = self.socket.clone();
+        let client = std::thread::spawn(move || control::send(&socket, &request));
+        while !client.is_finished() {
+            self.app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        client.join().unwrap()
+    }
+
+    pub fn socket(&self) -> &PathBuf {
+        &self.socket
+    }
+
+    pub fn dir(&self) -> &TempDir {
+        &self.dir
+    }
+
+    pub fn dock_order(&self) -> Vec<String> {
+        self.app.world.resource::<DockOrder>().0.clone()
+    }
+
+    pub fn client_addresses(&self) -> Vec<String> {
+        let clients = &self.app.world.resource::<ClientList>().0;
+        clients.iter().map(|c| c.address.clone()).collect()
+    }
+
+    /// The icon entity showing `class`
+    pub fn icon(&mut self, class: &str) -> Option<Entity> {
+        let mut query = self.app.world.query::<(Entity, &ClientClass)>();
+        query
+            .iter(&self.app.world)
+            .find(|(_, icon_class)| icon_class.0 == class)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Classes of every icon, however many there are of each
+    pub fn icon_classes(&mut self) -> Vec<String> {
+        let mut query = self.app.world.query_filtered::<&ClientClass, With<ClientIcon>>();
+        let mut classes: Vec<String> = query.iter(&self.app.world).map(|c| c.0.clone()).collect();
+        classes.sort();
+        classes
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world.get::<T>(entity)
+    }
+}
@@ -3,12 +3,41 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, mpsc::Receiver};
 
-#[derive(Deserialize, Debug, Clone)]
+use crate::icon::IconContext;
+use crate::layout::Layout;
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Client {
     pub class: String,
     pub address: String,
     #[serde(default)]
-    pub _name: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub pid: i32,
+    #[serde(default)]
+    pub workspace: ClientWorkspace,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ClientWorkspace {
+    #[serde(default)]
+    pub name: String,
+}
+
+impl Client {
+    pub fn icon_context(&self) -> IconContext {
+        let mut context = IconContext::new(self.class.clone());
+        if !self.title.is_empty() {
+            context.title = Some(self.title.clone());
+        }
+        if self.pid > 0 {
+            context = context.with_pid(self.pid as u32);
+        }
+        if !self.workspace.name.is_empty() {
+            context = context.with_workspace(self.workspace.name.clone());
+        }
+        context
+    }
 }
 
 #[derive(Component)]
@@ -20,6 +49,54 @@ pub struct ClientAddress(pub String);
 #[derive(Component, Debug)]
 pub struct ClientClass(pub String);
 
+/// All windows of one application class shown by a single dock icon
+#[derive(Component, Debug, Default)]
+pub struct WindowGroup {
+    pub addresses: Vec<String>,
+    pub next_focus: usize,
+}
+
+impl WindowGroup {
+    pub fn new(addresses: Vec<String>) -> Self {
+        Self {
+            addresses,
+            next_focus: 0,
+        }
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.addresses.iter().any(|a| a == address)
+    }
+
+    pub fn add(&mut self, address: String) {
+        if !self.contains(&address) {
+            self.addresses.push(address);
+        }
+    }
+
+    pub fn remove(&mut self, address: &str) {
+        if let Some(index) = self.addresses.iter().position(|a| a == address) {
+            self.addresses.remove(index);
+            if self.next_focus > index {
+                self.next_focus -= 1;
+            }
+        }
+    }
+
+    /// Returns the window to focus on the next click, cycling through the group
+    pub fn next_address(&mut self) -> Option<String> {
+        if self.addresses.is_empty() {
+            return None;
+        }
+        let index = self.next_focus % self.addresses.len();
+        self.next_focus = (index + 1) % self.addresses.len();
+        Some(self.addresses[index].clone())
+    }
+}
+
+#[derive(Component)]
+pub struct WindowCountIndicator;
+
 #[derive(Resource)]
 pub struct ClientList(pub Vec<Client>);
 
@@ -53,15 +130,9 @@ pub struct IconText(pub Entity);
 #[derive(Resource, Default)]
 pub struct IconPositions(pub HashMap<Entity, (Vec3, Vec3)>);
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct ReorderTrigger(pub bool);
 
-impl Default for ReorderTrigger {
-    fn default() -> Self {
-        ReorderTrigger(false)
-    }
-}
-
 #[derive(Component)]
 pub struct MainCamera;
 
@@ -71,16 +142,42 @@ pub struct StateUpdate;
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub struct ReorderIcons;
 
+/// Stages of a dock frame, run in the order listed
+///
+/// Systems added by an embedding app can go `before` or `after` any of them.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum DockSet {
+    /// Scrolling, keybinds, config reloads and control socket requests
+    Input,
+    /// Hover detection and icon animations
+    Hover,
+    /// Clicking an icon to focus or launch it
+    Activate,
+    /// The right-click menu, including pinning and unpinning
+    Favorites,
+    /// Dragging icons to a new place
+    Drag,
+    /// Placing icons in `DockOrder`
+    Layout,
+    /// Window manager events, which spawn and despawn icons
+    WindowManager,
+    /// Indicators, counters and banners drawn from the state above
+    Visuals,
+}
+
 #[derive(Resource, Default)]
 pub struct DockOrder(pub Vec<String>);
 
+/// Monitor requested with `--monitor`, by connector name
+#[derive(Resource, Default)]
+pub struct TargetMonitor(pub Option<String>);
+
 
 #[derive(Event)]
 pub struct IconRemovedEvent;
 
 #[derive(Resource, Default)]
 pub struct ScrollState {
-    pub offset: Vec2,
     pub total_scroll_distance: f32,
 }
 
@@ -89,11 +186,11 @@ pub struct ScrollState {
 #[derive(Resource, Clone)]
 pub struct HyprlandEventReceiver(pub Arc<Mutex<Receiver<HyprIpcEvent>>>);
 
-#[derive(Debug, Clone)]
+/// Window manager events, as translated by the active backend
+#[derive(Debug, Clone, PartialEq)]
 pub enum HyprIpcEvent {
     OpenWindow {
         address: String,
-        #[allow(dead_code)]
         workspace: String,
         class: String,
         title: String,
@@ -101,5 +198,253 @@ pub enum HyprIpcEvent {
     CloseWindow {
         address: String,
     },
+    /// Focus moved to `address`, or to no window at all
+    ActiveWindow {
+        address: Option<String>,
+    },
+    WindowTitle {
+        address: String,
+        title: String,
+    },
+    MoveWindow {
+        address: String,
+        workspace_id: i64,
+        workspace: String,
+    },
+    /// The focused workspace changed
+    Workspace {
+        id: i64,
+        name: String,
+    },
+    FocusedMonitor {
+        monitor: String,
+        workspace: String,
+    },
+    /// The focused workspace entered or left fullscreen
+    Fullscreen {
+        enabled: bool,
+    },
+    Urgent {
+        address: String,
+    },
+    ChangeFloatingMode {
+        address: String,
+        floating: bool,
+    },
+    MonitorAdded {
+        name: String,
+    },
+    MonitorRemoved {
+        name: String,
+    },
+    /// `workspace` now shows on `monitor`, windows included
+    WorkspaceMoved {
+        workspace: String,
+        monitor: String,
+    },
+    ConfigReloaded,
+    /// The event stream was lost; the backend is trying to reconnect
+    Disconnected,
+    /// Full window list taken right after (re)connecting
+    Resync {
+        clients: Vec<Client>,
+    },
     Other,
 }
+
+/// Whether the dock is currently receiving window manager events
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowManagerStatus {
+    #[default]
+    Connected,
+    Disconnected,
+}
+
+#[derive(Component)]
+pub struct DisconnectedBanner;
+
+/// Whether the dock is hidden off screen, as `[visibility]` in the config decides
+#[derive(Resource, Debug, Default)]
+pub struct DockVisibility {
+    /// Whether the dock is hiding or hidden, rather than showing
+    pub hidden: bool,
+    /// How far the icons have slid out, from 0 in place to 1 off screen
+    pub progress: f32,
+    /// A window covers the dock or one is fullscreen on its monitor, for intellihide
+    pub covered: bool,
+    /// Seconds since the pointer was last on the dock
+    pub idle: f32,
+    /// Hidden by `anny-dock hide` until the next `show`, whatever the mode says
+    pub forced: bool,
+}
+
+impl DockVisibility {
+    /// `progress` eased in and out, as the icons are drawn
+    pub fn slide(&self) -> f32 {
+        let t = self.progress.clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Slide the dock out and keep it out whatever the mode, or let the mode decide again
+    pub fn hold_hidden(&mut self, hidden: bool) {
+        self.forced = hidden;
+        self.hidden = hidden;
+        self.idle = 0.0;
+    }
+
+    /// Where the icon resting at `position` is drawn from, as far as it has slid out
+    pub fn offset(&self, layout: &dyn Layout, position: Vec2, icon_extent: f32, window_size: Vec2) -> Vec2 {
+        let slide = self.slide();
+        if slide > 0.0 {
+            layout.hide_offset(position, icon_extent, window_size) * slide
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+/// Address of the window that currently has keyboard focus
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct FocusedWindow(pub Option<String>);
+
+/// Attention animation running on an icon until `address` gets focus
+#[derive(Component, Debug)]
+pub struct AttentionAnimation {
+    pub address: String,
+    pub elapsed: f32,
+}
+
+/// Running/focused marker drawn as a child of a `ClientIcon`
+#[derive(Component)]
+pub struct WindowIndicator;
+
+/// Line between the icons of two workspaces, with `workspaces.grouped` set
+#[derive(Component)]
+pub struct WorkspaceSeparator;
+
+/// Button of the workspace strip, switching to the workspace it names
+#[derive(Component, Debug)]
+pub struct WorkspaceButton(pub String);
+
+#[derive(Event, Debug, Clone)]
+pub struct WindowFocused {
+    pub address: Option<String>,
+}
+
+/// A window's title changed
+///
+/// The dock itself doesn't read it; it is sent for apps embedding `AnnyDockPlugin`.
+#[derive(Event, Debug, Clone)]
+pub struct WindowTitleChanged {
+    pub address: String,
+    pub title: String,
+}
+
+/// A window moved to another workspace
+#[derive(Event, Debug, Clone)]
+pub struct WindowWorkspaceChanged {
+    pub address: String,
+    pub workspace_id: i64,
+    pub workspace: String,
+}
+
+/// The active workspace changed
+#[derive(Event, Debug, Clone)]
+pub struct WorkspaceChanged {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Focus moved to another monitor, showing `workspace`
+///
+/// The dock itself doesn't read it; it is sent for apps embedding `AnnyDockPlugin`.
+#[derive(Event, Debug, Clone)]
+pub struct MonitorFocused {
+    pub monitor: String,
+    pub workspace: String,
+}
+
+/// The focused window entered or left fullscreen
+#[derive(Event, Debug, Clone)]
+pub struct FullscreenChanged {
+    pub enabled: bool,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct WindowUrgent {
+    pub address: String,
+}
+
+/// A window was made floating, or tiled again
+#[derive(Event, Debug, Clone)]
+pub struct FloatingModeChanged {
+    pub address: String,
+    pub floating: bool,
+}
+
+/// A monitor was plugged in
+///
+/// The dock itself doesn't read it; it is sent for apps embedding `AnnyDockPlugin`.
+#[derive(Event, Debug, Clone)]
+pub struct MonitorAdded {
+    pub name: String,
+}
+
+/// A monitor was unplugged
+///
+/// The dock itself doesn't read it; it is sent for apps embedding `AnnyDockPlugin`.
+#[derive(Event, Debug, Clone)]
+pub struct MonitorRemoved {
+    pub name: String,
+}
+
+/// The window manager reloaded its own configuration
+#[derive(Event, Debug, Clone)]
+pub struct WindowManagerConfigReloaded;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(addresses: &[&str]) -> WindowGroup {
+        WindowGroup::new(addresses.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_window_group_cycles_focus() {
+        let mut group = group(&["0x1", "0x2", "0x3"]);
+
+        assert_eq!(group.next_address().as_deref(), Some("0x1"));
+        assert_eq!(group.next_address().as_deref(), Some("0x2"));
+        assert_eq!(group.next_address().as_deref(), Some("0x3"));
+        assert_eq!(group.next_address().as_deref(), Some("0x1"));
+    }
+
+    #[test]
+    fn test_window_group_remove_keeps_cycle_position() {
+        let mut group = group(&["0x1", "0x2", "0x3"]);
+        group.next_address();
+        group.next_address();
+
+        group.remove("0x1");
+
+        assert_eq!(group.addresses, vec!["0x2", "0x3"]);
+        assert_eq!(group.next_address().as_deref(), Some("0x3"));
+    }
+
+    #[test]
+    fn test_window_group_add_ignores_duplicates() {
+        let mut group = group(&["0x1"]);
+        group.add("0x1".to_string());
+        group.add("0x2".to_string());
+
+        assert_eq!(group.addresses, vec!["0x1", "0x2"]);
+        assert!(group.contains("0x2"));
+    }
+
+    #[test]
+    fn test_empty_window_group_has_nothing_to_focus() {
+        let mut group = WindowGroup::default();
+        assert_eq!(group.next_address(), None);
+    }
+}
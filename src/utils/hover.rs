@@ -6,12 +6,12 @@ use bevy::time::Time;
 use bevy::transform::components::{GlobalTransform, Transform};
 use bevy::window::PrimaryWindow;
 use bevy::time::Timer;
+use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::{config::Config, types::*};
+use crate::config::{Config, MagnificationConfig};
+use crate::{layout::dock_layout, types::*};
 
-const HOVER_LIFT: f32 = 15.0;
-const HOVER_SCALE: f32 = 1.15;
 const ANIMATION_SMOOTHNESS: f32 = 0.85;
 
 pub fn hover_system(
@@ -91,6 +91,12 @@ pub struct HoverState {
     pub current_scale: f32,
     pub target_lift: f32,
     pub target_scale: f32,
+    /// Distance moved along the dock to make room for magnified neighbours
+    pub current_shift: f32,
+    pub target_shift: f32,
+    /// Extra lift and scale factor applied on top of hover, driven by `AttentionAnimation`
+    pub attention_lift: f32,
+    pub attention_scale: f32,
 }
 
 impl Default for HoverState {
@@ -100,10 +106,53 @@ impl Default for HoverState {
             current_scale: 1.0,
             target_lift: 0.0,
             target_scale: 1.0,
+            current_shift: 0.0,
+            target_shift: 0.0,
+            attention_lift: 0.0,
+            attention_scale: 1.0,
         }
     }
 }
 
+/// Share of the full magnification each icon gets, and how far it moves along the dock
+///
+/// `icons` holds each icon's position along the dock and its size, `focus` the
+/// cursor's position along the dock. Every magnified icon pushes the others away
+/// from it by half the size it gained, so neighbours on both sides make room.
+pub fn magnification_field(
+    icons: &[(f32, f32)],
+    focus: f32,
+    config: &MagnificationConfig,
+) -> Vec<(f32, f32)> {
+    let weights: Vec<f32> = icons
+        .iter()
+        .map(|(along, _)| config.falloff.weight(along - focus, config.radius))
+        .collect();
+    let growth: Vec<f32> = icons
+        .iter()
+        .zip(&weights)
+        .map(|((_, size), weight)| (config.scale - 1.0) * weight * size * config.spread)
+        .collect();
+
+    icons
+        .iter()
+        .zip(&weights)
+        .enumerate()
+        .map(|(index, ((along, _), weight))| {
+            let shift = icons
+                .iter()
+                .zip(&growth)
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != index)
+                .map(|(_, ((other, _), grown))| (along - other).signum() * grown / 2.0)
+                .filter(|shift| shift.is_finite())
+                .sum::<f32>();
+            (*weight, shift)
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn hover_animation_system(
     time: Res<Time>,
     mut q: Query<(
@@ -114,10 +163,17 @@ pub fn hover_animation_system(
     ui_state: Res<UiState>,
     scroll_state: Res<ScrollState>,
     config: Res<Config>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    visibility: Res<DockVisibility>,
 ) {
     if ui_state.dragging.is_some() {
         return;
     }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let layout = dock_layout(&config, window);
 
     let delta_time = time.delta_seconds();
 
@@ -125,13 +181,15 @@ pub fn hover_animation_system(
     let interp = scroll - scroll.floor();
 
     let mut scales = vec![1.0; config.visible_items];
-    for i in 0..config.visible_items {
-        if i == 0 {
-            scales[i] = 1.2 - 0.2 * interp as f32;
-        } else if i == 1 {
-            scales[i] = 1.0 + 0.2 * interp as f32;
-        } else {
-            scales[i] = 1.0;
+    if layout.emphasizes_front() {
+        for i in 0..config.visible_items {
+            if i == 0 {
+                scales[i] = 1.2 - 0.2 * interp as f32;
+            } else if i == 1 {
+                scales[i] = 1.0 + 0.2 * interp as f32;
+            } else {
+                scales[i] = 1.0;
+            }
         }
     }
 
@@ -141,11 +199,43 @@ pub fn hover_animation_system(
         *s *= norm;
     }
 
+    let in_window = |index: usize| {
+        index >= first_visible_index && index < first_visible_index + config.visible_items
+    };
+    let magnification = &config.magnification;
+    let window_size = Vec2::new(window.width(), window.height());
+
+    // Com raio, o campo segue o cursor; sem cursor (ou sem raio), só o ícone em foco cresce
+    let mut field: HashMap<usize, (f32, f32)> = HashMap::new();
+    let hovered = q
+        .iter()
+        .find(|(_, hover, _)| hover.is_hovered && in_window(hover.index))
+        .map(|(_, hover, _)| hover.original_position);
+    if let (Some(hovered), true) = (hovered, magnification.radius > 0.0) {
+        let cursor = window.cursor_position().and_then(|cursor| {
+            let (camera, camera_transform) = q_camera.get_single().ok()?;
+            camera.viewport_to_world_2d(camera_transform, cursor)
+        });
+        let focus = layout.position_along(cursor.unwrap_or(hovered));
+
+        let icons: Vec<(usize, f32, f32)> = q
+            .iter()
+            .filter(|(_, hover, _)| in_window(hover.index))
+            .map(|(_, hover, _)| {
+                let along = layout.position_along(hover.original_position);
+                let size = config.icon_size * layout.icon_scale(hover.index);
+                (hover.index, along, size)
+            })
+            .collect();
+        let sizes: Vec<(f32, f32)> = icons.iter().map(|(_, along, size)| (*along, *size)).collect();
+        let values = magnification_field(&sizes, focus, magnification);
+        field = icons.iter().map(|(index, _, _)| *index).zip(values).collect();
+    }
+
     for (mut transform, hover, mut state) in &mut q {
-        let base_scale = config.base_scale * config.scale_factor.powi(hover.index as i32);
+        let base_scale = layout.icon_scale(hover.index);
 
-        let in_window = hover.index >= first_visible_index
-            && hover.index < first_visible_index + config.visible_items;
+        let in_window = in_window(hover.index);
 
         let mut target_scale = 0.0;
         let mut z_offset = 0.0;
@@ -163,12 +253,20 @@ pub fn hover_animation_system(
             }
         }
 
-        if hover.is_hovered && in_window {
-            target_scale = base_scale * HOVER_SCALE;
+        let (weight, shift) = match field.get(&hover.index) {
+            Some(&(weight, shift)) => (weight, shift),
+            None if hover.is_hovered && in_window && magnification.radius <= 0.0 => (1.0, 0.0),
+            None => (0.0, 0.0),
+        };
+        if weight > 0.0 && magnification.radius > 0.0 {
+            target_scale += base_scale * (magnification.scale - 1.0) * weight;
+        } else if weight > 0.0 {
+            target_scale = base_scale * magnification.scale;
         }
 
         state.target_scale = target_scale;
-        state.target_lift = if hover.is_hovered && in_window { HOVER_LIFT } else { 0.0 };
+        state.target_lift = magnification.lift * weight;
+        state.target_shift = shift;
 
         state.current_lift += (state.target_lift - state.current_lift)
             * (1.0 - ANIMATION_SMOOTHNESS.powf(delta_time * 60.0));
@@ -176,12 +274,83 @@ pub fn hover_animation_system(
         state.current_scale += (state.target_scale - state.current_scale)
             * (1.0 - ANIMATION_SMOOTHNESS.powf(delta_time * 60.0));
 
-        transform.translation = Vec3::new(
-            hover.original_position.x,
-            hover.original_position.y + state.current_lift,
-            hover.original_z - z_offset,
-        );
+        state.current_shift += (state.target_shift - state.current_shift)
+            * (1.0 - ANIMATION_SMOOTHNESS.powf(delta_time * 60.0));
 
-        transform.scale = Vec3::splat(state.current_scale);
+        let lift = layout.lift_direction(hover.original_position)
+            * (state.current_lift + state.attention_lift);
+        let shift = layout.tangent(hover.original_position) * state.current_shift;
+        let extent = config.icon_size * config.base_scale;
+        let hide = visibility.offset(layout.as_ref(), hover.original_position, extent, window_size);
+        transform.translation =
+            (hover.original_position + lift + shift + hide).extend(hover.original_z - z_offset);
+
+        transform.scale = Vec3::splat(state.current_scale * state.attention_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::mock_client;
+    use crate::config::Falloff;
+    use crate::testing::DockHarness;
+
+    fn magnification(radius: f32) -> MagnificationConfig {
+        MagnificationConfig {
+            radius,
+            scale: 2.0,
+            falloff: Falloff::Linear,
+            ..MagnificationConfig::default()
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_magnification_field_spreads_neighbours() {
+        let icons = [(0.0, 50.0), (50.0, 50.0), (100.0, 50.0), (400.0, 50.0)];
+        let field = magnification_field(&icons, 50.0, &magnification(100.0));
+
+        assert_eq!(field[1], (1.0, 0.0));
+        assert_eq!(field[0].0, 0.5);
+        assert_eq!(field[2].0, 0.5);
+        // Cada vizinho recua metade do que o do meio e o outro vizinho cresceram
+        assert_eq!(field[0].1, -(25.0 + 12.5));
+        assert_eq!(field[2].1, 25.0 + 12.5);
+        assert_eq!(field[3], (0.0, 50.0));
+    }
+
+    #[test]
+    fn test_hover_magnifies_neighbours_within_radius() {
+        let clients = vec![
+            mock_client("0x1", "firefox", "1"),
+            mock_client("0x2", "kitty", "1"),
+            mock_client("0x3", "code", "1"),
+        ];
+        let config = Config {
+            magnification: magnification(1000.0),
+            ..Config::default()
+        };
+        let mut dock = DockHarness::with_config(clients.clone(), config);
+        dock.hover("kitty");
+
+        let target = |dock: &mut DockHarness, class: &str| {
+            let icon = dock.icon(class).unwrap();
+            dock.get::<HoverState>(icon).unwrap().target_scale
+        };
+        let hovered = target(&mut dock, "kitty");
+        let neighbour = target(&mut dock, "code");
+        assert!(hovered > neighbour);
+        assert!(neighbour > Config::default().base_scale * Config::default().scale_factor.powi(2));
+
+        // Sem raio, só o ícone em foco cresce, como antes
+        let mut dock = DockHarness::with_config(clients, Config::default());
+        let resting = target(&mut dock, "code");
+        dock.hover("kitty");
+        let config = Config::default();
+        assert_eq!(
+            target(&mut dock, "kitty"),
+            config.base_scale * config.scale_factor * config.magnification.scale
+        );
+        assert_eq!(target(&mut dock, "code"), resting);
+    }
+}
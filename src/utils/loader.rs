@@ -1,61 +1,245 @@
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use bevy::{
     log::{info, warn},
+    prelude::Resource,
     render::texture::Image,
 };
+use serde::{Deserialize, Serialize};
 
 use image::io::Reader as ImageReader;
-use std::path::Path;
-use std::process::Command;
-use xdgkit::icon_finder;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::components::Favorites;
-use crate::Client;
+use crate::components::{Favorites, Pin};
+use crate::icon::{IconCache, IconContext, IconResolver};
 
+/// Version of the favorites file written by this build
+const FAVORITES_VERSION: u32 = 1;
+/// Where favorites lived before they moved to the data directory
+const LEGACY_FAVORITES_PATH: &str = "favorites.json";
 
-pub fn get_current_clients() -> Result<Vec<Client>, std::io::Error> {
-    let output = Command::new("hyprctl").args(["clients", "-j"]).output()?;
+#[derive(Serialize, Deserialize)]
+struct FavoritesFile {
+    version: u32,
+    pins: Vec<Pin>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFavorites {
+    Versioned(FavoritesFile),
+    /// Bare list of classes, as written before the file was versioned
+    Legacy(Vec<String>),
+}
+
+/// Temporary files written so far by this process, to keep their names apart
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// What a save changes, replayed on the file as it is on disk
+///
+/// Every dock of a multi-monitor session writes the same file, so a save
+/// can't just write the dock's own pins over pins another dock added.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FavoritesChange {
+    Pin(String),
+    Unpin(String),
+    Reorder(Vec<String>),
+}
+
+/// Where favorites are stored: `--favorites`, or the default location in the data directory
+#[derive(Resource, Clone, Debug, Default)]
+pub struct FavoritesPath(pub Option<PathBuf>);
+
+pub fn default_favorites_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("anny-dock").join("favorites.json"))
+}
 
-    if !output.status.success() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to execute hyprctl",
-        ));
+pub fn load_favorites(path: &FavoritesPath) -> Favorites {
+    match &path.0 {
+        // Só o local padrão herda o favorites.json antigo do diretório atual
+        Some(path) => {
+            let legacy_path = (Some(path) == default_favorites_path().as_ref())
+                .then_some(Path::new(LEGACY_FAVORITES_PATH));
+            load_favorites_from(path, legacy_path)
+        }
+        None => {
+            warn!("No data directory found, favorites will not be loaded");
+            Favorites::default()
+        }
     }
+}
 
-    let clients: Vec<Client> = serde_json::from_slice(&output.stdout)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+/// Apply `change` to the favorites file, keeping pins other docks saved meanwhile
+///
+/// `favorites` are this dock's pins, with `change` already made.
+pub fn save_favorites(path: &FavoritesPath, favorites: &Favorites, change: FavoritesChange) {
+    let Some(path) = &path.0 else {
+        warn!("No data directory found, favorites will not be saved");
+        return;
+    };
+    if let Err(e) = update_favorites(path, favorites, &change) {
+        warn!("Failed to save favorites to {}: {}", path.display(), e);
+    }
+}
 
-    Ok(clients)
+fn update_favorites(path: &Path, favorites: &Favorites, change: &FavoritesChange) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Um dock por vez lê e reescreve o arquivo
+    let lock = fs::File::create(path.with_extension("json.lock"))?;
+    if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let merged = if path.exists() {
+        let mut stored = read_favorites(path)?;
+        apply_change(&mut stored, favorites, change);
+        stored
+    } else {
+        favorites.clone()
+    };
+    write_favorites(path, &merged)
+}
+
+/// Make `change` on `stored`, taking new pins and their place from `favorites`
+fn apply_change(stored: &mut Favorites, favorites: &Favorites, change: &FavoritesChange) {
+    match change {
+        FavoritesChange::Pin(class) => {
+            if stored.contains(class) {
+                return;
+            }
+            let pin = favorites.get(class).cloned().unwrap_or_else(|| Pin::new(class));
+            let index = favorites
+                .0
+                .iter()
+                .position(|pin| pin.class == *class)
+                .unwrap_or(usize::MAX)
+                .min(stored.0.len());
+            stored.0.insert(index, pin);
+        }
+        FavoritesChange::Unpin(class) => stored.remove(class),
+        FavoritesChange::Reorder(classes) => stored.reorder(classes),
+    }
 }
 
-pub fn load_favorites() -> Favorites {
-    match std::fs::read_to_string("favorites.json") {
-        Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
-        Err(_) => Favorites::default(),
+/// Load favorites from `path`, migrating `legacy_path` the first time
+///
+/// A file that can't be read is moved aside to `favorites.json.bad`, so the
+/// next save doesn't replace the user's pins with an empty list.
+fn load_favorites_from(path: &Path, legacy_path: Option<&Path>) -> Favorites {
+    if path.exists() {
+        return read_favorites(path).unwrap_or_else(|e| {
+            let bad_path = path.with_extension("json.bad");
+            warn!(
+                "Failed to read favorites from {}, moving it to {}: {}",
+                path.display(),
+                bad_path.display(),
+                e
+            );
+            if let Err(e) = fs::rename(path, &bad_path) {
+                warn!("Failed to move {} aside: {}", path.display(), e);
+            }
+            Favorites::default()
+        });
+    }
+
+    if let Some(legacy_path) = legacy_path.filter(|legacy_path| legacy_path.exists()) {
+        match read_favorites(legacy_path) {
+            Ok(favorites) => {
+                info!(
+                    "Migrating favorites from {} to {}",
+                    legacy_path.display(),
+                    path.display()
+                );
+                if let Err(e) = write_favorites(path, &favorites) {
+                    warn!("Failed to migrate favorites: {}", e);
+                }
+                return favorites;
+            }
+            Err(e) => warn!("Failed to read legacy favorites: {}", e),
+        }
     }
+
+    Favorites::default()
+}
+
+fn read_favorites(path: &Path) -> io::Result<Favorites> {
+    let data = fs::read_to_string(path)?;
+    let stored: StoredFavorites = serde_json::from_str(&data)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut pins = match stored {
+        StoredFavorites::Versioned(file) => {
+            if file.version > FAVORITES_VERSION {
+                warn!(
+                    "Favorites file version {} is newer than supported version {}",
+                    file.version, FAVORITES_VERSION
+                );
+            }
+            file.pins
+        }
+        StoredFavorites::Legacy(classes) => classes.into_iter().map(Pin::new).collect(),
+    };
+    pins.sort_by_key(|pin| pin.position.unwrap_or(usize::MAX));
+
+    Ok(Favorites(pins))
 }
 
-pub fn save_favorites(favorites: &Favorites) {
-    if let Ok(json) = serde_json::to_string(favorites) {
-        let _ = std::fs::write("favorites.json", json);
+/// Write favorites through a temporary file and a rename, so a crash never leaves a truncated file
+fn write_favorites(path: &Path, favorites: &Favorites) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
+
+    let file = FavoritesFile {
+        version: FAVORITES_VERSION,
+        pins: favorites
+            .0
+            .iter()
+            .enumerate()
+            .map(|(position, pin)| Pin {
+                position: Some(position),
+                ..pin.clone()
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let temp_path = path.with_extension(format!(
+        "json.{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut temp_file = fs::File::create(&temp_path)?;
+    temp_file.write_all(json.as_bytes())?;
+    temp_file.sync_all()?;
+    fs::rename(&temp_path, path)
 }
 
-pub fn get_icon_path(class: &str) -> String {
-    let lowercase = class.to_lowercase();
-    match icon_finder::find_icon(lowercase, 56, 1) {
+pub fn get_icon_path(
+    resolver: &IconResolver,
+    cache: &mut IconCache,
+    context: &IconContext,
+) -> Option<PathBuf> {
+    if let Some(path) = cache.get_persistent_path(&context.class) {
+        info!("icon found in cache for {}: {}", context.class, path.display());
+        return Some(path);
+    }
+
+    match resolver.resolve_path(context) {
         Some(path) => {
-            info!(
-                "icon found for {}: {}",
-                class,
-                path.to_string_lossy().to_string()
-            );
-            path.to_string_lossy().to_string()
+            info!("icon found for {}: {}", context.class, path.display());
+            cache.store_path_mapping(context.class.clone(), path.clone());
+            Some(path)
         }
         _ => {
-            warn!("No icons found for {}, using fallback", class);
-            "memory://fallback_icon".to_string()
+            warn!("No icons found for {}, using fallback", context.class);
+            None
         }
     }
 }
@@ -118,6 +302,152 @@ pub fn load_svg_image(path: &Path) -> Option<Image> {
     Some(image)
 }
 
-pub fn load_clients() -> Vec<Client> {
-    get_current_clients().unwrap_or_default()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migrates_legacy_favorites() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data/anny-dock/favorites.json");
+        let legacy_path = dir.path().join("favorites.json");
+        fs::write(&legacy_path, r#"["firefox","kitty"]"#).unwrap();
+
+        let favorites = load_favorites_from(&path, Some(&legacy_path));
+
+        assert_eq!(favorites.classes().collect::<Vec<_>>(), vec!["firefox", "kitty"]);
+        assert!(path.exists());
+
+        // The legacy file is only read once
+        fs::write(&legacy_path, r#"["steam"]"#).unwrap();
+        let favorites = load_favorites_from(&path, Some(&legacy_path));
+        assert_eq!(favorites.classes().collect::<Vec<_>>(), vec!["firefox", "kitty"]);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_pin_fields() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("favorites.json");
+        let favorites = Favorites(vec![
+            Pin {
+                command: Some("firefox --private-window".to_string()),
+                icon: Some(PathBuf::from("/home/user/icons/fox.png")),
+                label: Some("Private".to_string()),
+                terminal: true,
+                ..Pin::new("firefox")
+            },
+            Pin::new("kitty"),
+        ]);
+
+        write_favorites(&path, &favorites).unwrap();
+        let loaded = read_favorites(&path).unwrap();
+
+        assert_eq!(loaded.0[0].command.as_deref(), Some("firefox --private-window"));
+        assert_eq!(loaded.0[0].label.as_deref(), Some("Private"));
+        assert_eq!(loaded.0[0].position, Some(0));
+        assert!(loaded.0[0].terminal && !loaded.0[1].terminal);
+        assert_eq!(loaded.0[1].class, "kitty");
+        assert_eq!(loaded.0[1].position, Some(1));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_saves_keep_pins_saved_by_other_docks() {
+        let dir = TempDir::new().unwrap();
+        let path = FavoritesPath(Some(dir.path().join("favorites.json")));
+        let favorites = |classes: &[&str]| Favorites(classes.iter().map(|class| Pin::new(*class)).collect());
+
+        // Dois docks começam com os mesmos pins e cada um fixa um app diferente
+        save_favorites(&path, &favorites(&["firefox"]), FavoritesChange::Pin("firefox".to_string()));
+        save_favorites(&path, &favorites(&["firefox", "kitty"]), FavoritesChange::Pin("kitty".to_string()));
+        save_favorites(&path, &favorites(&["steam", "firefox"]), FavoritesChange::Pin("steam".to_string()));
+        let stored = |path: &FavoritesPath| read_favorites(path.0.as_ref().unwrap()).unwrap();
+        assert_eq!(stored(&path).classes().collect::<Vec<_>>(), vec!["steam", "firefox", "kitty"]);
+
+        save_favorites(&path, &favorites(&["steam"]), FavoritesChange::Unpin("firefox".to_string()));
+        save_favorites(
+            &path,
+            &favorites(&["kitty", "steam"]),
+            FavoritesChange::Reorder(vec!["kitty".to_string(), "steam".to_string()]),
+        );
+        assert_eq!(stored(&path).classes().collect::<Vec<_>>(), vec!["kitty", "steam"]);
+    }
+
+    #[test]
+    fn test_concurrent_saves_lose_no_pins() {
+        let dir = TempDir::new().unwrap();
+        let path = FavoritesPath(Some(dir.path().join("favorites.json")));
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let class = format!("app{}", i);
+                    let favorites = Favorites(vec![Pin::new(class.clone())]);
+                    save_favorites(&path, &favorites, FavoritesChange::Pin(class));
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let stored = read_favorites(path.0.as_ref().unwrap()).unwrap();
+        assert_eq!(stored.0.len(), 8);
+    }
+
+    #[test]
+    fn test_pins_sorted_by_position() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("favorites.json");
+        fs::write(
+            &path,
+            r#"{"version": 1, "pins": [
+                {"class": "kitty", "position": 1},
+                {"class": "steam"},
+                {"class": "firefox", "position": 0}
+            ]}"#,
+        )
+        .unwrap();
+
+        let favorites = read_favorites(&path).unwrap();
+
+        assert_eq!(
+            favorites.classes().collect::<Vec<_>>(),
+            vec!["firefox", "kitty", "steam"]
+        );
+    }
+
+    #[test]
+    fn test_unreadable_favorites_are_not_replaced_by_legacy() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("favorites.json");
+        let legacy_path = dir.path().join("legacy.json");
+        fs::write(&path, "not json").unwrap();
+        fs::write(&legacy_path, r#"["firefox"]"#).unwrap();
+
+        let favorites = load_favorites_from(&path, Some(&legacy_path));
+        assert!(favorites.0.is_empty());
+
+        save_favorites(
+            &FavoritesPath(Some(path.clone())),
+            &Favorites(vec![Pin::new("kitty")]),
+            FavoritesChange::Pin("kitty".to_string()),
+        );
+        assert_eq!(fs::read_to_string(path.with_extension("json.bad")).unwrap(), "not json");
+        assert_eq!(read_favorites(&path).unwrap().classes().collect::<Vec<_>>(), vec!["kitty"]);
+    }
+
+    #[test]
+    fn test_no_migration_without_legacy_path() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("favorites.json");
+        let legacy_path = dir.path().join("legacy.json");
+        fs::write(&legacy_path, r#"["firefox"]"#).unwrap();
+
+        assert!(load_favorites_from(&path, None).0.is_empty());
+        assert!(!path.exists());
+        assert!(load_favorites(&FavoritesPath(Some(path.clone()))).0.is_empty());
+    }
 }